@@ -3,6 +3,7 @@ use anyhow::Result;
 use std::env;
 
 /// 主应用程序结构体
+#[derive(Default)]
 pub struct App {}
 
 impl App {
@@ -58,6 +59,15 @@ impl App {
                 "6" => {
                     self.run_sysinfo()?;
                 }
+                "7" => {
+                    self.run_dupes()?;
+                }
+                "8" => {
+                    self.run_disk_usage()?;
+                }
+                "9" => {
+                    self.run_replace()?;
+                }
                 "0" => {
                     self.show_about_info()?;
                 }
@@ -91,6 +101,9 @@ impl App {
             ("4", "批量转换", "图片格式转换，文档格式转换"),
             ("5", "批量清理", "清理空文件夹、临时文件"),
             ("6", "系统信息", "CPU/内存/磁盘/网络监控"),
+            ("7", "重复文件", "按内容查找重复文件，删除或硬链接"),
+            ("8", "磁盘占用", "统计目录大小，逐级查看占用"),
+            ("9", "文本替换", "批量查找替换文件内容，保留编码"),
             ("0", "关于/更新", "查看程序信息，检查更新"),
         ];
 
@@ -222,6 +235,60 @@ impl App {
         }
     }
 
+    /// 运行重复文件查找工具
+    fn run_dupes(&self) -> Result<()> {
+        match crate::features::dupes::interface::run_interactive() {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let e: anyhow::Error = e.into();
+                // 检查是否是返回主菜单的错误
+                if let Some(hekit_error) = e.downcast_ref::<crate::error::HekitError>() {
+                    if matches!(hekit_error, crate::error::HekitError::BackToMainMenu(_)) {
+                        // 返回主菜单，不显示错误信息
+                        return Ok(());
+                    }
+                }
+                Err(anyhow::anyhow!("重复文件工具执行失败: {}", e))
+            }
+        }
+    }
+
+    /// 运行磁盘占用分析工具
+    fn run_disk_usage(&self) -> Result<()> {
+        match crate::features::diskusage::interface::run_interactive() {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let e: anyhow::Error = e.into();
+                // 检查是否是返回主菜单的错误
+                if let Some(hekit_error) = e.downcast_ref::<crate::error::HekitError>() {
+                    if matches!(hekit_error, crate::error::HekitError::BackToMainMenu(_)) {
+                        // 返回主菜单，不显示错误信息
+                        return Ok(());
+                    }
+                }
+                Err(anyhow::anyhow!("磁盘占用工具执行失败: {}", e))
+            }
+        }
+    }
+
+    /// 运行文本替换工具
+    fn run_replace(&self) -> Result<()> {
+        match crate::features::replace::interface::run_interactive() {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let e: anyhow::Error = e.into();
+                // 检查是否是返回主菜单的错误
+                if let Some(hekit_error) = e.downcast_ref::<crate::error::HekitError>() {
+                    if matches!(hekit_error, crate::error::HekitError::BackToMainMenu(_)) {
+                        // 返回主菜单，不显示错误信息
+                        return Ok(());
+                    }
+                }
+                Err(anyhow::anyhow!("文本替换工具执行失败: {}", e))
+            }
+        }
+    }
+
     /// 显示关于信息（使用紧凑格式）
     fn show_about_info(&self) -> Result<()> {
         let description = env!("CARGO_PKG_DESCRIPTION");
@@ -230,7 +297,7 @@ impl App {
         utils::print_super_compact_program_title("HEKIT", &format!("v{}", version));
 
         utils::print_compatible_info(&format!("项目描述: {}", description));
-        utils::print_compatible_info(&format!("作者: zhanghed"));
+        utils::print_compatible_info("作者: zhanghed");
         utils::print_compatible_info(&format!("版本: {}", version));
         println!();
 
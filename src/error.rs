@@ -137,7 +137,7 @@ fn classify_error(error: &dyn std::error::Error) -> HekitError {
 #[macro_export]
 macro_rules! hekit_error {
     ($error_type:ident, $msg:expr) => {
-        Err(crate::error::HekitError::$error_type($msg.to_string()).into())
+        Err($crate::error::HekitError::$error_type($msg.to_string()).into())
     };
 }
 
@@ -90,12 +90,29 @@ where
         return Err(HekitError::UserInput("命令过长，请简化输入".to_string()));
     }
 
-    // 危险字符检查
+    // 路径遍历检查（包括引号内的参数）
+    for pattern in ["..\\", "../"] {
+        if input.contains(pattern) {
+            return Err(HekitError::UserInput(format!(
+                "检测到潜在危险字符: {}",
+                pattern
+            )));
+        }
+    }
+
+    // 预处理Windows路径，并按最终的分词规则区分引号内外
+    let preprocessed_input = preprocess_windows_paths(input);
+    let full_command = format!("{} {}", command_prefix, preprocessed_input);
+    let Some((args, unquoted)) = split(&full_command) else {
+        return Err(HekitError::UserInput("引号不匹配，请检查输入".to_string()));
+    };
+
+    // 危险字符检查：引号内的内容作为普通参数值（如正则表达式），只检查引号外的部分
     let dangerous_patterns = [
-        "..\\", "../", "|", "&", ";", "`", "$", ">", "<", "(", ")", "{", "}", "[", "]", "~",
+        "|", "&", ";", "`", "$", ">", "<", "(", ")", "{", "}", "[", "]", "~",
     ];
     for pattern in dangerous_patterns {
-        if input.contains(pattern) {
+        if unquoted.contains(pattern) {
             return Err(HekitError::UserInput(format!(
                 "检测到潜在危险字符: {}",
                 pattern
@@ -104,7 +121,7 @@ where
     }
 
     // 检查路径遍历攻击
-    if input.contains("..") && (input.contains("\\") || input.contains("/")) {
+    if unquoted.contains("..") && (unquoted.contains("\\") || unquoted.contains("/")) {
         return Err(HekitError::UserInput("检测到路径遍历攻击尝试".to_string()));
     }
 
@@ -122,21 +139,10 @@ where
         ));
     }
 
-    // 预处理Windows路径
-    let preprocessed_input = preprocess_windows_paths(input);
-
-    // 解析命令行参数
-    let full_command = format!("{} {}", command_prefix, preprocessed_input);
-    let args = match split(&full_command) {
-        Some(args) => {
-            // 验证参数数量
-            if args.len() > 20 {
-                return Err(HekitError::UserInput("参数过多，请简化命令".to_string()));
-            }
-            args
-        }
-        None => return Err(HekitError::ArgumentParse("命令行参数解析失败".to_string())),
-    };
+    // 验证参数数量
+    if args.len() > 20 {
+        return Err(HekitError::UserInput("参数过多，请简化命令".to_string()));
+    }
 
     // 执行命令并处理结果
     match build_command().try_get_matches_from(&args) {
@@ -160,28 +166,42 @@ where
     }
 }
 
-/// 命令行参数分割函数
-fn split(input: &str) -> Option<Vec<String>> {
+/// 命令行参数分割函数，返回 (参数列表, 引号外的文本)，引号不成对时返回 None
+///
+/// 引号内的反斜杠只转义引号和反斜杠本身，其余原样保留（便于输入正则表达式）。
+/// 引号外的文本（引号替换为空格，被转义的字符照常计入）用于危险字符检查，
+/// 与分词共用同一套规则，保证检查的正是最终被当作引号外的内容。
+fn split(input: &str) -> Option<(Vec<String>, String)> {
     let mut args = Vec::new();
+    let mut unquoted = String::new();
     let mut current = String::new();
     let mut in_quotes = false;
     let mut escape_next = false;
+    let mut chars = input.chars().peekable();
 
-    for c in input.chars() {
+    while let Some(c) = chars.next() {
         if escape_next {
             current.push(c);
+            if !in_quotes {
+                unquoted.push(c);
+            }
             escape_next = false;
             continue;
         }
 
         match c {
+            '\\' if in_quotes && !matches!(chars.peek(), Some('"') | Some('\\')) => {
+                current.push(c);
+            }
             '\\' => {
                 escape_next = true;
             }
             '"' => {
                 in_quotes = !in_quotes;
+                unquoted.push(' ');
             }
             ' ' | '\t' if !in_quotes => {
+                unquoted.push(c);
                 if !current.is_empty() {
                     args.push(current.clone());
                     current.clear();
@@ -190,15 +210,21 @@ fn split(input: &str) -> Option<Vec<String>> {
             _ => {
                 // 处理所有其他字符 - 修复：使用current而不是result
                 current.push(c);
+                if !in_quotes {
+                    unquoted.push(c);
+                }
             }
         }
     }
 
+    if in_quotes {
+        return None;
+    }
     if !current.is_empty() {
         args.push(current);
     }
 
-    Some(args)
+    Some((args, unquoted))
 }
 
 // 预处理Windows路径，修复单反斜杠问题
@@ -329,3 +355,96 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, ArgAction};
+
+    fn test_command() -> Command {
+        Command::new("test").arg(
+            Arg::new("value")
+                .short('v')
+                .long("value")
+                .action(ArgAction::Append),
+        )
+    }
+
+    fn parse(input: &str) -> HekitResult<Vec<String>> {
+        let matches = execute_common_command(input, "test", test_command, || {})?;
+        Ok(matches
+            .get_many::<String>("value")
+            .unwrap_or_default()
+            .cloned()
+            .collect())
+    }
+
+    #[test]
+    fn metacharacters_inside_quotes_are_literal_values() {
+        assert_eq!(
+            parse(r#"-v "s/(\d+)-[a-z]{2}/${1}_x/g" -v "a|b & c; d > e < f ~""#).unwrap(),
+            vec![r"s/(\d+)-[a-z]{2}/${1}_x/g", "a|b & c; d > e < f ~"]
+        );
+    }
+
+    #[test]
+    fn metacharacters_outside_quotes_are_rejected() {
+        for input in [
+            "-v a|b", "-v a&b", "-v a;b", "-v a`b", "-v $a", "-v a>b", "-v a<b", "-v (a)",
+            "-v {a}", "-v [a]", "-v ~",
+        ] {
+            assert!(
+                matches!(parse(input), Err(HekitError::UserInput(_))),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn escaped_metacharacters_outside_quotes_are_rejected() {
+        assert!(parse(r"-v a\;b").is_err());
+        assert!(parse(r"-v \$HOME").is_err());
+    }
+
+    #[test]
+    fn backslashes_inside_quotes_are_kept_except_doubled_ones() {
+        assert_eq!(
+            parse(r#"-v "c\\d" -v "\w\n\(""#).unwrap(),
+            vec![r"c\d", r"\w\n\("]
+        );
+    }
+
+    #[test]
+    fn trailing_backslash_in_quoted_windows_path_is_kept() {
+        assert_eq!(parse(r#"-v "C:\dir\""#).unwrap(), vec![r"C:\dir\"]);
+    }
+
+    #[test]
+    fn quote_at_end_of_quoted_value_cannot_hide_metacharacters() {
+        // 路径预处理会把 \" 变成 \\"，此时引号在 ; 之前结束，必须按引号外检查
+        assert!(parse(r#"-v "a\" ; -v b""#).is_err());
+    }
+
+    #[test]
+    fn unbalanced_quotes_are_rejected() {
+        assert!(matches!(
+            parse(r#"-v "a; b"#),
+            Err(HekitError::UserInput(_))
+        ));
+    }
+
+    #[test]
+    fn path_traversal_is_rejected_even_inside_quotes() {
+        assert!(parse(r#"-v "../etc""#).is_err());
+        assert!(parse(r#"-v "..\windows""#).is_err());
+    }
+
+    #[test]
+    fn split_returns_unquoted_text_with_quotes_as_spaces() {
+        let (args, unquoted) = split(r#"cmd -v "x;y" z"#).unwrap();
+        assert_eq!(args, vec!["cmd", "-v", "x;y", "z"]);
+        assert_eq!(unquoted, "cmd -v    z");
+        assert!(split(r#"cmd "open"#).is_none());
+    }
+}
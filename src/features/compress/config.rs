@@ -11,6 +11,10 @@ pub struct BatchCompressConfig {
     pub compression_level: u32,
     pub recursive: bool,
     pub preview: bool,
+    /// 把所有文件打包成一个压缩包（保留相对路径）
+    pub single: bool,
+    /// 单个压缩包的文件名（默认使用目标文件夹名）
+    pub archive_name: Option<String>,
 }
 
 // 压缩相关常量定义
@@ -68,7 +72,7 @@ impl BatchCompressConfig {
                     .short('r')
                     .long("recursive")
                     .action(ArgAction::SetTrue)
-                    .help("递归处理子目录"),
+                    .help("递归处理子目录（配合 --single 使用）"),
             )
             .arg(
                 Arg::new("preview")
@@ -77,6 +81,20 @@ impl BatchCompressConfig {
                     .action(ArgAction::SetTrue)
                     .help("预览效果（不真压缩）"),
             )
+            .arg(
+                Arg::new("single")
+                    .short('s')
+                    .long("single")
+                    .action(ArgAction::SetTrue)
+                    .help("所有文件打包成一个压缩包（保留相对路径，-o 为压缩包所在目录）"),
+            )
+            .arg(
+                Arg::new("archive-name")
+                    .short('a')
+                    .long("archive-name")
+                    .value_name("压缩包名")
+                    .help("单个压缩包的文件名（默认用文件夹名，隐含 --single）"),
+            )
     }
 
     /// 从CLAP匹配结果创建配置
@@ -96,9 +114,7 @@ impl BatchCompressConfig {
             .map(|s| s.to_string())
             .unwrap_or_else(|| "zip".to_string());
 
-        let output_path = matches
-            .get_one::<String>("output")
-            .map(|s| PathBuf::from(s));
+        let output_path = matches.get_one::<String>("output").map(PathBuf::from);
 
         let compression_level = matches
             .get_one::<String>("level")
@@ -107,8 +123,10 @@ impl BatchCompressConfig {
 
         let recursive = matches.get_flag("recursive");
         let preview = matches.get_flag("preview");
+        let archive_name = matches.get_one::<String>("archive-name").cloned();
+        let single = matches.get_flag("single") || archive_name.is_some();
 
-        Ok(Self {
+        let config = Self {
             path,
             match_pattern,
             output_format,
@@ -116,7 +134,12 @@ impl BatchCompressConfig {
             compression_level,
             recursive,
             preview,
-        })
+            single,
+            archive_name,
+        };
+
+        config.validate()?;
+        Ok(config)
     }
 
     /// 验证配置
@@ -143,6 +166,15 @@ impl BatchCompressConfig {
             ));
         }
 
+        if let Some(name) = &self.archive_name {
+            if name.trim().is_empty() {
+                return Err(anyhow!("压缩包名不能为空"));
+            }
+            if name.contains(['/', '\\']) {
+                return Err(anyhow!("压缩包名不能包含路径分隔符（用 -o 指定输出目录）"));
+            }
+        }
+
         Ok(())
     }
 }
@@ -27,7 +27,18 @@ impl BatchCompressCore {
             return hekit_error!(Compression, "没有找到匹配的文件");
         }
 
-        if self.config.preview {
+        if self.config.single {
+            let output_path = self.single_output_path()?;
+            let files = self.exclude_output(files, &output_path);
+            if files.is_empty() {
+                return hekit_error!(Compression, "没有找到匹配的文件");
+            }
+            if self.config.preview {
+                self.execute_single_preview(&files, &output_path)
+            } else {
+                self.execute_single_compression(&files, &output_path)
+            }
+        } else if self.config.preview {
             self.execute_preview(&files)
         } else {
             self.execute_compression(&files)
@@ -36,7 +47,15 @@ impl BatchCompressCore {
 
     /// 扫描匹配的文件
     pub fn scan_files(&self) -> HekitResult<Vec<PathBuf>> {
-        let pattern = self.config.path.join(&self.config.match_pattern);
+        // 单压缩包递归模式下，不含目录部分的模式匹配所有子目录中的文件（逐个压缩时不受影响）
+        let pattern = if self.config.single
+            && self.config.recursive
+            && !self.config.match_pattern.contains(['/', '\\'])
+        {
+            self.config.path.join("**").join(&self.config.match_pattern)
+        } else {
+            self.config.path.join(&self.config.match_pattern)
+        };
         let pattern = pattern.to_string_lossy().to_string();
 
         let mut files = Vec::new();
@@ -106,6 +125,122 @@ impl BatchCompressCore {
         }
     }
 
+    /// 预览单个压缩包的内容
+    fn execute_single_preview(&self, files: &[PathBuf], output_path: &Path) -> HekitResult<()> {
+        println!("预览压缩结果: {}", output_path.display());
+
+        for file_path in files {
+            println!("  {}", self.entry_name(file_path));
+        }
+
+        if output_path.exists() {
+            println!("注意: 压缩包已存在，执行时会询问是否覆盖");
+        }
+        println!("总计: {} 个文件", files.len());
+        Ok(())
+    }
+
+    /// 把所有文件打包成一个压缩包
+    fn execute_single_compression(&self, files: &[PathBuf], output_path: &Path) -> HekitResult<()> {
+        if output_path.exists() {
+            let answer = crate::utils::get_user_input(&format!(
+                "压缩包已存在: {}，是否覆盖? (y/N): ",
+                output_path.display()
+            ))
+            .map_err(|e| HekitError::UserInput(format!("读取输入失败: {}", e)))?;
+            if !answer.eq_ignore_ascii_case("y") {
+                crate::utils::print_info("已取消操作");
+                return Ok(());
+            }
+        }
+
+        println!("开始打包到 {} ...", output_path.display());
+        let progress = ProgressManager::new(files.len() as u64, "打包中...");
+
+        let entries: Vec<(PathBuf, String)> = files
+            .iter()
+            .map(|file_path| (file_path.clone(), self.entry_name(file_path)))
+            .collect();
+
+        if let Err(e) = self.write_archive_replacing(&entries, output_path, Some(&progress)) {
+            progress.finish_with_message("打包失败");
+            return Err(e);
+        }
+
+        progress.finish_with_message(&format!("完成: 已打包 {} 个文件", files.len()));
+        let size = std::fs::metadata(output_path).map(|m| m.len()).unwrap_or(0);
+        println!(
+            "✓ {} ({})",
+            output_path.display(),
+            crate::utils::format_size(size)
+        );
+        Ok(())
+    }
+
+    /// 单个压缩包的输出路径：输出目录（默认目标文件夹）+ 压缩包名（默认文件夹名）
+    fn single_output_path(&self) -> HekitResult<PathBuf> {
+        let extension = self.extension();
+        let name = match &self.config.archive_name {
+            Some(name) => name.trim().to_string(),
+            None => self
+                .config
+                .path
+                .canonicalize()
+                .ok()
+                .and_then(|path| path.file_name().map(|n| n.to_string_lossy().to_string()))
+                .unwrap_or_else(|| "archive".to_string()),
+        };
+        let file_name = if name.ends_with(&format!(".{}", extension)) {
+            name
+        } else {
+            format!("{}.{}", name, extension)
+        };
+
+        let output_dir = self
+            .config
+            .output_path
+            .as_deref()
+            .unwrap_or(&self.config.path);
+        Ok(output_dir.join(file_name))
+    }
+
+    /// 去掉输出压缩包本身（避免把自己打包进去）
+    fn exclude_output(&self, files: Vec<PathBuf>, output_path: &Path) -> Vec<PathBuf> {
+        let Ok(output) = output_path.canonicalize() else {
+            return files;
+        };
+        files
+            .into_iter()
+            .filter(|file| file.canonicalize().map_or(true, |path| path != output))
+            .collect()
+    }
+
+    /// 文件在压缩包内的名称（相对目标文件夹的路径，用 / 分隔）
+    fn entry_name(&self, file_path: &Path) -> String {
+        match file_path.strip_prefix(&self.config.path) {
+            Ok(relative) => relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+            Err(_) => file_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+        }
+    }
+
+    /// 压缩格式对应的扩展名
+    fn extension(&self) -> &'static str {
+        match self.config.output_format.as_str() {
+            "zip" => "zip",
+            "tar.gz" => "tar.gz",
+            "tar.bz2" => "tar.bz2",
+            _ => "zip",
+        }
+    }
+
     /// 生成输出文件路径
     fn generate_output_path(
         &self,
@@ -117,12 +252,7 @@ impl BatchCompressCore {
             .file_stem()
             .ok_or_else(|| HekitError::Compression("无法获取文件名".to_string()))?
             .to_string_lossy();
-        let extension = match self.config.output_format.as_str() {
-            "zip" => "zip",
-            "tar.gz" => "tar.gz",
-            "tar.bz2" => "tar.bz2",
-            _ => "zip",
-        };
+        let extension = self.extension();
 
         let output_filename = if total_files > 1 {
             format!("{}_{}.{}", file_stem, index, extension)
@@ -142,33 +272,81 @@ impl BatchCompressCore {
 
     /// 压缩单个文件
     fn compress_file(&self, input_path: &Path, output_path: &Path) -> HekitResult<()> {
+        let name = input_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let file = File::create(output_path)
+            .map_err(|e| HekitError::Compression(format!("创建压缩文件失败: {}", e)))?;
+        self.write_archive(&[(input_path.to_path_buf(), name)], file, None)
+    }
+
+    /// 先写入输出目录中的临时文件，成功后再重命名为压缩包，失败时只删除临时文件
+    fn write_archive_replacing(
+        &self,
+        entries: &[(PathBuf, String)],
+        output_path: &Path,
+        progress: Option<&ProgressManager>,
+    ) -> HekitResult<()> {
+        let (file, temp_path) = create_temp_file(output_path)
+            .map_err(|e| HekitError::Compression(format!("创建临时文件失败: {}", e)))?;
+        let result = self.write_archive(entries, file, progress).and_then(|_| {
+            std::fs::rename(&temp_path, output_path)
+                .map_err(|e| HekitError::Compression(format!("保存压缩包失败: {}", e)))
+        });
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+        result
+    }
+
+    /// 把 (文件路径, 包内名称) 列表写入压缩包
+    fn write_archive(
+        &self,
+        entries: &[(PathBuf, String)],
+        file: File,
+        progress: Option<&ProgressManager>,
+    ) -> HekitResult<()> {
         match self.config.output_format.as_str() {
-            "zip" => self.compress_zip(input_path, output_path),
-            "tar.gz" => self.compress_tar_gz(input_path, output_path),
-            "tar.bz2" => self.compress_tar_bz2(input_path, output_path),
-            _ => self.compress_zip(input_path, output_path),
+            "zip" => self.compress_zip(entries, file, progress),
+            "tar.gz" => self.compress_tar_gz(entries, file, progress),
+            "tar.bz2" => self.compress_tar_bz2(entries, file, progress),
+            _ => self.compress_zip(entries, file, progress),
         }
     }
 
     /// 压缩为ZIP格式
-    fn compress_zip(&self, input_path: &Path, output_path: &Path) -> HekitResult<()> {
-        let file = File::create(output_path)
-            .map_err(|e| HekitError::Compression(format!("创建ZIP文件失败: {}", e)))?;
+    fn compress_zip(
+        &self,
+        entries: &[(PathBuf, String)],
+        file: File,
+        progress: Option<&ProgressManager>,
+    ) -> HekitResult<()> {
         let mut zip = zip::ZipWriter::new(file);
 
         let options = zip::write::FileOptions::default()
             .compression_method(zip::CompressionMethod::Deflated)
             .compression_level(Some(self.config.compression_level as i32));
 
-        let file_name = input_path.file_name().unwrap_or_default().to_string_lossy();
-
-        zip.start_file(file_name.as_ref(), options)
-            .map_err(|e| HekitError::Compression(format!("ZIP文件写入失败: {}", e)))?;
-
-        let mut input_file = File::open(input_path)
-            .map_err(|e| HekitError::Compression(format!("打开输入文件失败: {}", e)))?;
-        io::copy(&mut input_file, &mut zip)
-            .map_err(|e| HekitError::Compression(format!("文件复制失败: {}", e)))?;
+        for (input_path, name) in entries {
+            let mut input_file = File::open(input_path).map_err(|e| {
+                HekitError::Compression(format!(
+                    "打开输入文件失败: {}: {}",
+                    input_path.display(),
+                    e
+                ))
+            })?;
+
+            zip.start_file(name.as_str(), options)
+                .map_err(|e| HekitError::Compression(format!("ZIP文件写入失败: {}", e)))?;
+            io::copy(&mut input_file, &mut zip)
+                .map_err(|e| HekitError::Compression(format!("文件复制失败: {}", e)))?;
+
+            if let Some(progress) = progress {
+                progress.inc(1);
+            }
+        }
 
         zip.finish()
             .map_err(|e| HekitError::Compression(format!("完成ZIP文件失败: {}", e)))?;
@@ -176,62 +354,239 @@ impl BatchCompressCore {
     }
 
     /// 压缩为tar.gz格式
-    fn compress_tar_gz(&self, input_path: &Path, output_path: &Path) -> HekitResult<()> {
-        let tar_gz_file = File::create(output_path)
-            .map_err(|e| HekitError::Compression(format!("创建tar.gz文件失败: {}", e)))?;
+    fn compress_tar_gz(
+        &self,
+        entries: &[(PathBuf, String)],
+        file: File,
+        progress: Option<&ProgressManager>,
+    ) -> HekitResult<()> {
         let encoder = GzEncoder::new(
-            tar_gz_file,
+            file,
             flate2::Compression::new(self.config.compression_level),
         );
         let mut tar = Builder::new(encoder);
 
-        self.add_file_to_tar(input_path, &mut tar)?;
-        tar.finish()
+        self.add_files_to_tar(entries, &mut tar, progress)?;
+        tar.into_inner()
+            .and_then(|encoder| encoder.finish())
             .map_err(|e| HekitError::Compression(format!("完成tar.gz文件失败: {}", e)))?;
 
         Ok(())
     }
 
     /// 压缩为tar.bz2格式
-    fn compress_tar_bz2(&self, input_path: &Path, output_path: &Path) -> HekitResult<()> {
-        let tar_bz2_file = File::create(output_path)
-            .map_err(|e| HekitError::Compression(format!("创建tar.bz2文件失败: {}", e)))?;
+    fn compress_tar_bz2(
+        &self,
+        entries: &[(PathBuf, String)],
+        file: File,
+        progress: Option<&ProgressManager>,
+    ) -> HekitResult<()> {
         let encoder = bzip2::write::BzEncoder::new(
-            tar_bz2_file,
-            bzip2::Compression::new(self.config.compression_level as u32),
+            file,
+            bzip2::Compression::new(self.config.compression_level),
         );
         let mut tar = Builder::new(encoder);
 
-        self.add_file_to_tar(input_path, &mut tar)?;
-        tar.finish()
+        self.add_files_to_tar(entries, &mut tar, progress)?;
+        tar.into_inner()
+            .and_then(|encoder| encoder.finish())
             .map_err(|e| HekitError::Compression(format!("完成tar.bz2文件失败: {}", e)))?;
 
         Ok(())
     }
 
+    /// 依次添加文件到tar包
+    fn add_files_to_tar<T: Write>(
+        &self,
+        entries: &[(PathBuf, String)],
+        tar: &mut Builder<T>,
+        progress: Option<&ProgressManager>,
+    ) -> HekitResult<()> {
+        for (input_path, name) in entries {
+            self.add_file_to_tar(input_path, name, tar)?;
+            if let Some(progress) = progress {
+                progress.inc(1);
+            }
+        }
+        Ok(())
+    }
+
     /// 添加文件到tar包
     fn add_file_to_tar<T: Write>(
         &self,
         input_path: &Path,
+        name: &str,
         tar: &mut Builder<T>,
     ) -> HekitResult<()> {
-        let mut file = File::open(input_path)
-            .map_err(|e| HekitError::Compression(format!("打开输入文件失败: {}", e)))?;
+        let mut file = File::open(input_path).map_err(|e| {
+            HekitError::Compression(format!("打开输入文件失败: {}: {}", input_path.display(), e))
+        })?;
         let metadata = file
             .metadata()
             .map_err(|e| HekitError::Compression(format!("获取文件元数据失败: {}", e)))?;
 
+        // 保留原文件的权限、修改时间等元数据
         let mut header = Header::new_gnu();
-        header
-            .set_path(input_path.file_name().unwrap_or_default())
-            .map_err(|e| HekitError::Compression(format!("设置tar头路径失败: {}", e)))?;
-        header.set_size(metadata.len());
-        header.set_mode(0o644);
-        header.set_cksum();
-
-        tar.append(&header, &mut file)
+        header.set_metadata(&metadata);
+
+        // append_data 会为超过100字节的长路径写入GNU长文件名记录
+        tar.append_data(&mut header, name, &mut file)
             .map_err(|e| HekitError::Compression(format!("添加文件到tar包失败: {}", e)))?;
 
         Ok(())
     }
 }
+
+/// 在压缩包所在目录创建不与现有文件冲突的临时文件，返回 (文件, 路径)
+fn create_temp_file(output_path: &Path) -> io::Result<(File, PathBuf)> {
+    let name = output_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let mut attempt = 0;
+    loop {
+        let temp_path = output_path.with_file_name(format!(
+            ".{}.hekit-tmp-{}-{}",
+            name,
+            std::process::id(),
+            attempt
+        ));
+        // create_new 不会打开已存在的文件或符号链接，名称被占用时换下一个
+        match File::options()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+        {
+            Ok(file) => return Ok((file, temp_path)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("hekit_compress_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), "a").unwrap();
+        fs::write(dir.join("sub").join("b.txt"), "b").unwrap();
+        dir
+    }
+
+    fn core(dir: &Path, args: &[&str]) -> BatchCompressCore {
+        let dir = dir.to_string_lossy().into_owned();
+        let mut argv = vec!["compress", "-d", &dir, "-m", "*.txt"];
+        argv.extend_from_slice(args);
+        let matches = BatchCompressConfig::build_clap_command()
+            .try_get_matches_from(argv)
+            .unwrap();
+        BatchCompressCore::new(BatchCompressConfig::from_matches(&matches).unwrap())
+    }
+
+    fn names(dir: &Path, files: Vec<PathBuf>) -> Vec<String> {
+        files
+            .iter()
+            .map(|file| {
+                file.strip_prefix(dir)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recursive_only_descends_in_single_mode() {
+        let dir = temp_dir("recursive");
+        let per_file = core(&dir, &["-r"]).scan_files().unwrap();
+        assert_eq!(names(&dir, per_file), vec!["a.txt"]);
+
+        let single = core(&dir, &["-r", "-s"]).scan_files().unwrap();
+        assert_eq!(names(&dir, single), vec!["a.txt", "sub/b.txt"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn tar_entries_keep_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("mode");
+        let script = dir.join("run.sh");
+        fs::write(&script, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let core = core(&dir, &["-f", "tar.gz"]);
+        let output = dir.join("out.tar.gz");
+        core.write_archive(
+            &[(script, "run.sh".to_string())],
+            File::create(&output).unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let decoder = flate2::read::GzDecoder::new(File::open(&output).unwrap());
+        let mut archive = tar::Archive::new(decoder);
+        let entry = archive.entries().unwrap().next().unwrap().unwrap();
+        assert_eq!(entry.path().unwrap().to_string_lossy(), "run.sh");
+        assert_eq!(entry.header().mode().unwrap() & 0o777, 0o755);
+        assert_eq!(entry.header().size().unwrap(), 10);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_archive_keeps_existing_output_and_removes_temp() {
+        let dir = temp_dir("failed");
+        let output = dir.join("out.zip");
+        fs::write(&output, "old archive").unwrap();
+
+        let core = core(&dir, &["-s"]);
+        let entries = [
+            (dir.join("a.txt"), "a.txt".to_string()),
+            (dir.join("missing.txt"), "missing.txt".to_string()),
+        ];
+        assert!(core
+            .write_archive_replacing(&entries, &output, None)
+            .is_err());
+
+        assert_eq!(fs::read_to_string(&output).unwrap(), "old archive");
+        let leftovers = fs::read_dir(&dir)
+            .unwrap()
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .contains("hekit-tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn archive_replaces_existing_output_on_success() {
+        let dir = temp_dir("replace");
+        let output = dir.join("out.zip");
+        fs::write(&output, "old archive").unwrap();
+        // 占用第一个临时名称，应自动换用下一个且不改动该文件
+        let taken = dir.join(format!(".out.zip.hekit-tmp-{}-0", std::process::id()));
+        fs::write(&taken, "busy").unwrap();
+
+        let core = core(&dir, &["-s"]);
+        core.write_archive_replacing(&[(dir.join("a.txt"), "a.txt".to_string())], &output, None)
+            .unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        assert_eq!(archive.by_index(0).unwrap().name(), "a.txt");
+        assert_eq!(fs::read_to_string(&taken).unwrap(), "busy");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -25,14 +25,20 @@ impl ToolInterface for CompressTool {
         println!("  -f, --format     压缩格式（zip, tar.gz, tar.bz2）");
         println!("  -o, --output     输出文件路径");
         println!("  -l, --level      压缩级别 1-9（默认6）");
-        println!("  -r, --recursive  递归处理子目录");
+        println!("  -r, --recursive  递归处理子目录（配合 --single 使用）");
         println!("  -p, --preview    预览效果（不真压缩）");
+        println!(
+            "  -s, --single     所有文件打包成一个压缩包（保留相对路径，-o 为压缩包所在目录）"
+        );
+        println!("  -a, --archive-name  压缩包名（默认用文件夹名，隐含 --single）");
         println!();
 
         println!("实用示例:");
         println!("  压缩所有txt文件: --match *.txt");
         println!("  压缩图片到tar.gz: --match *.jpg --format tar.gz");
         println!("  高压缩级别: --level 9");
+        println!("  打包成一个zip: --match *.txt --single");
+        println!("  递归打包到指定文件: --match *.rs -r -a src_backup -f tar.gz");
 
         utils::print_compact_separator();
     }
@@ -59,18 +65,17 @@ impl ToolInterface for CompressTool {
             .map_err(|e| crate::error::HekitError::UserInput(format!("配置错误: {}", e)))?;
         let core = BatchCompressCore::new(config);
 
-        // 修复：使用scan_files方法而不是scan
-        let files = core.scan_files()?;
-        let count = files.len();
-
-        if core.config.preview {
-            println!("预览模式：找到 {} 个待压缩文件", count);
-        } else {
-            // 修复：execute方法返回HekitResult<()>，不需要接收返回值
-            core.execute()?;
-            println!("成功压缩 {} 个文件", count);
+        // 预览和单压缩包模式由核心逻辑输出结果
+        if core.config.preview || core.config.single {
+            return core.execute();
         }
 
+        // 修复：使用scan_files方法而不是scan
+        let count = core.scan_files()?.len();
+        // 修复：execute方法返回HekitResult<()>，不需要接收返回值
+        core.execute()?;
+        println!("成功压缩 {} 个文件", count);
+
         Ok(())
     }
 }
@@ -97,9 +97,7 @@ impl BatchConvertConfig {
         let source_format = matches.get_one::<String>("from").unwrap().clone();
         let target_format = matches.get_one::<String>("to").unwrap().clone();
 
-        let output_dir = matches
-            .get_one::<String>("output")
-            .map(|s| PathBuf::from(s));
+        let output_dir = matches.get_one::<String>("output").map(PathBuf::from);
 
         let quality = matches
             .get_one::<String>("quality")
@@ -175,7 +173,7 @@ impl BatchConvertConfig {
 
         // 验证质量参数
         if let Some(quality) = self.quality {
-            if quality < 1 || quality > 100 {
+            if !(1..=100).contains(&quality) {
                 return Err(anyhow!("质量参数必须在1-100之间"));
             }
         }
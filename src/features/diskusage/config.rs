@@ -0,0 +1,138 @@
+use crate::features::export::ExportFormat;
+use crate::features::search::walker;
+use anyhow::{anyhow, Result};
+use clap::{Arg, Command};
+use std::path::PathBuf;
+
+/// 磁盘占用分析配置
+#[derive(Debug, Clone)]
+pub struct DiskUsageConfig {
+    pub path: PathBuf,
+    pub top: usize,
+    pub threads: usize,
+    pub follow_symlinks: bool,
+    pub one_file_system: bool,
+    pub export: Option<PathBuf>,
+    pub browse: bool,
+}
+
+impl DiskUsageConfig {
+    /// 构建CLAP命令
+    pub fn build_clap_command() -> Command {
+        Command::new("du")
+            .about("磁盘占用分析工具")
+            .arg(
+                Arg::new("path")
+                    .short('d')
+                    .long("path")
+                    .value_name("目标文件夹")
+                    .help("要分析的文件夹（默认当前目录）")
+                    .default_value("."),
+            )
+            .arg(
+                Arg::new("top")
+                    .short('n')
+                    .long("top")
+                    .value_name("数量")
+                    .help("显示最大的前N个目录和文件（默认10）")
+                    .default_value("10"),
+            )
+            .arg(
+                Arg::new("threads")
+                    .short('j')
+                    .long("threads")
+                    .value_name("线程数")
+                    .help("遍历线程数（默认按CPU核心数）"),
+            )
+            .arg(
+                Arg::new("follow-symlinks")
+                    .short('L')
+                    .long("follow-symlinks")
+                    .help("跟随符号链接（自动跳过循环）")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("one-file-system")
+                    .short('x')
+                    .long("one-file-system")
+                    .help("不统计其他文件系统（挂载点、网络盘）")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("export")
+                    .short('o')
+                    .long("export")
+                    .value_name("JSON文件")
+                    .help("导出目录树（.json）"),
+            )
+            .arg(
+                Arg::new("browse")
+                    .short('i')
+                    .long("browse")
+                    .help("分析完成后逐级浏览目录")
+                    .action(clap::ArgAction::SetTrue),
+            )
+    }
+
+    /// 从命令行参数解析配置
+    pub fn from_matches(matches: &clap::ArgMatches) -> Result<Self> {
+        let path = matches
+            .get_one::<String>("path")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("."));
+
+        let top = matches
+            .get_one::<String>("top")
+            .map(|s| s.parse::<usize>())
+            .transpose()
+            .map_err(|_| anyhow!("显示数量必须是数字"))?
+            .unwrap_or(10);
+
+        let threads = matches
+            .get_one::<String>("threads")
+            .map(|s| s.parse::<usize>())
+            .transpose()
+            .map_err(|_| anyhow!("线程数必须是数字"))?
+            .unwrap_or_else(walker::default_threads);
+
+        let config = Self {
+            path,
+            top,
+            threads,
+            follow_symlinks: matches.get_flag("follow-symlinks"),
+            one_file_system: matches.get_flag("one-file-system"),
+            export: matches.get_one::<String>("export").map(PathBuf::from),
+            browse: matches.get_flag("browse"),
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 验证配置参数
+    pub fn validate(&self) -> Result<()> {
+        if !self.path.exists() {
+            return Err(anyhow!("目录不存在: {}", self.path.display()));
+        }
+
+        if !self.path.is_dir() {
+            return Err(anyhow!("路径不是目录: {}", self.path.display()));
+        }
+
+        if self.top == 0 || self.top > 1000 {
+            return Err(anyhow!("显示数量必须在1到1000之间"));
+        }
+
+        if self.threads == 0 || self.threads > 64 {
+            return Err(anyhow!("线程数必须在1到64之间"));
+        }
+
+        if let Some(export) = &self.export {
+            if ExportFormat::from_path(export)? != ExportFormat::Json {
+                return Err(anyhow!("目录树只能导出为 .json 文件"));
+            }
+        }
+
+        Ok(())
+    }
+}
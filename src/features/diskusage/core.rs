@@ -0,0 +1,484 @@
+use crate::error::{HekitError, HekitResult};
+use crate::features::diskusage::config::DiskUsageConfig;
+use crate::features::export;
+use crate::features::search::walker::{self, WalkOptions};
+use crate::progress::ProgressManager;
+use crate::utils;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// 占用比例条的长度
+const BAR_WIDTH: usize = 20;
+
+/// 目录节点（大小为实际占用的磁盘空间，包含所有子目录）
+#[derive(Debug, Clone)]
+pub struct DirNode {
+    pub name: String,
+    pub size: u64,
+    /// 目录下（含子目录）的文件总数
+    pub file_count: u64,
+    /// 子目录，按大小从大到小排序
+    pub children: Vec<DirNode>,
+    /// 直接包含的文件 (名称, 占用空间)，按大小从大到小排序
+    pub files: Vec<(String, u64)>,
+}
+
+/// 构建目录树时使用的可变节点
+#[derive(Default)]
+struct BuildNode {
+    files: Vec<(String, u64)>,
+    children: BTreeMap<String, BuildNode>,
+}
+
+/// 磁盘占用分析核心逻辑
+pub struct DiskUsageCore {
+    pub config: DiskUsageConfig,
+}
+
+impl DiskUsageCore {
+    /// 创建新的磁盘占用分析实例
+    pub fn new(config: DiskUsageConfig) -> Self {
+        Self { config }
+    }
+
+    /// 执行分析
+    pub fn execute(&self) -> HekitResult<()> {
+        let start_time = Instant::now();
+        let root = self.scan()?;
+        println!(
+            "{}  总计 {}，{} 个文件 (耗时: {:.2}秒)",
+            self.config.path.display(),
+            utils::format_size(root.size),
+            root.file_count,
+            start_time.elapsed().as_secs_f64()
+        );
+        println!();
+
+        self.print_top_dirs(&root);
+        self.print_top_files(&root);
+
+        if let Some(export_path) = &self.config.export {
+            self.export_json(&root, export_path)?;
+            utils::print_success(&format!("目录树已导出: {}", export_path.display()));
+        }
+
+        if self.config.browse {
+            self.browse(&root)?;
+        }
+        Ok(())
+    }
+
+    /// 遍历目录并汇总为目录树（按已分配的磁盘空间统计，同一文件的多个硬链接只统计一次）
+    fn scan(&self) -> HekitResult<DirNode> {
+        let options = WalkOptions {
+            threads: self.config.threads,
+            recursive: true,
+            follow_symlinks: self.config.follow_symlinks,
+            one_file_system: self.config.one_file_system,
+            ..WalkOptions::default()
+        };
+        let cancel = AtomicBool::new(false);
+        let files = Mutex::new(Vec::new());
+        let seen_ids = Mutex::new(HashSet::new());
+
+        let progress = ProgressManager::create_indeterminate("统计文件大小中...");
+        let stats = walker::walk(&self.config.path, &options, &cancel, |path| {
+            let metadata = if self.config.follow_symlinks {
+                fs::metadata(path)
+            } else {
+                fs::symlink_metadata(path)
+            };
+            let Ok(metadata) = metadata else {
+                return;
+            };
+            if !metadata.is_file() {
+                return;
+            }
+            if let Some(id) = utils::file_id(&metadata) {
+                if !seen_ids.lock().unwrap().insert(id) {
+                    return;
+                }
+            }
+            files
+                .lock()
+                .unwrap()
+                .push((path.to_path_buf(), utils::allocated_size(&metadata)));
+        });
+        drop(progress);
+
+        if stats.skipped > 0 {
+            utils::print_warning(&format!("因权限问题跳过 {} 个目录", stats.skipped));
+        }
+        if stats.other_fs > 0 {
+            utils::print_info(&format!("跳过 {} 个位于其他文件系统的目录", stats.other_fs));
+        }
+
+        let mut root = BuildNode::default();
+        for (path, size) in files.into_inner().unwrap() {
+            let Ok(relative) = path.strip_prefix(&self.config.path) else {
+                continue;
+            };
+            let mut parts: Vec<String> = relative
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            let Some(file_name) = parts.pop() else {
+                continue;
+            };
+            let mut node = &mut root;
+            for part in parts {
+                node = node.children.entry(part).or_default();
+            }
+            node.files.push((file_name, size));
+        }
+
+        let name = self.config.path.display().to_string();
+        Ok(finish_node(name, root))
+    }
+
+    /// 显示最大的目录（包含所有层级，不含根目录）
+    fn print_top_dirs(&self, root: &DirNode) {
+        let mut dirs = Vec::new();
+        collect_dirs(root, &self.config.path, &mut dirs);
+        dirs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        if dirs.is_empty() {
+            return;
+        }
+
+        println!("最大的 {} 个目录:", self.config.top.min(dirs.len()));
+        for (path, size) in dirs.iter().take(self.config.top) {
+            println!(
+                "  {:>10}  {:>5.1}%  {}",
+                utils::format_size(*size),
+                percent(*size, root.size),
+                path.display()
+            );
+        }
+        println!();
+    }
+
+    /// 显示最大的文件
+    fn print_top_files(&self, root: &DirNode) {
+        let mut files = Vec::new();
+        collect_files(root, &self.config.path, &mut files);
+        files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        if files.is_empty() {
+            return;
+        }
+
+        println!("最大的 {} 个文件:", self.config.top.min(files.len()));
+        for (path, size) in files.iter().take(self.config.top) {
+            println!(
+                "  {:>10}  {:>5.1}%  {}",
+                utils::format_size(*size),
+                percent(*size, root.size),
+                path.display()
+            );
+        }
+        println!();
+    }
+
+    /// 逐级浏览目录（类似 ncdu）：输入序号进入子目录，u 返回上一级，回车或 q 退出
+    fn browse(&self, root: &DirNode) -> HekitResult<()> {
+        // 从根目录到当前目录经过的子目录序号
+        let mut trail: Vec<usize> = Vec::new();
+        loop {
+            let mut node = root;
+            for &index in &trail {
+                node = &node.children[index];
+            }
+            let path = trail_path(root, &self.config.path, &trail);
+            self.print_listing(&path, node);
+
+            let input = utils::get_user_input("浏览 (序号进入, u 上一级, 回车或 q 退出) > ")
+                .map_err(|e| HekitError::UserInput(format!("读取输入失败: {}", e)))?;
+            match input.as_str() {
+                "" | "q" | "quit" => return Ok(()),
+                "u" | ".." => {
+                    if trail.pop().is_none() {
+                        utils::print_info("已经是最上层目录");
+                    }
+                }
+                text => match text.parse::<usize>() {
+                    Ok(number) if number >= 1 && number <= node.children.len() => {
+                        trail.push(number - 1);
+                    }
+                    Ok(_) => utils::print_warning("序号超出范围（只能进入目录）"),
+                    Err(_) => utils::print_warning("无效的输入"),
+                },
+            }
+        }
+    }
+
+    /// 显示目录内容：先列子目录（带序号），再列最大的文件
+    fn print_listing(&self, path: &Path, node: &DirNode) {
+        println!();
+        println!(
+            "== {}  ({}，{} 个文件) ==",
+            path.display(),
+            utils::format_size(node.size),
+            node.file_count
+        );
+
+        for (index, child) in node.children.iter().enumerate().take(self.config.top) {
+            println!(
+                "  {:>3}. {:>10}  {:>5.1}%  {}  {}/",
+                index + 1,
+                utils::format_size(child.size),
+                percent(child.size, node.size),
+                usage_bar(child.size, node.size),
+                child.name
+            );
+        }
+        if node.children.len() > self.config.top {
+            let rest: u64 = node.children[self.config.top..]
+                .iter()
+                .map(|c| c.size)
+                .sum();
+            println!(
+                "       ... 另有 {} 个目录，共 {}",
+                node.children.len() - self.config.top,
+                utils::format_size(rest)
+            );
+        }
+
+        for (name, size) in node.files.iter().take(self.config.top) {
+            println!(
+                "       {:>10}  {:>5.1}%  {}  {}",
+                utils::format_size(*size),
+                percent(*size, node.size),
+                usage_bar(*size, node.size),
+                name
+            );
+        }
+        if node.files.len() > self.config.top {
+            let rest: u64 = node.files[self.config.top..].iter().map(|f| f.1).sum();
+            println!(
+                "       ... 另有 {} 个文件，共 {}",
+                node.files.len() - self.config.top,
+                utils::format_size(rest)
+            );
+        }
+    }
+
+    /// 导出目录树为 JSON（每个目录包含大小、文件数和子目录）
+    fn export_json(&self, root: &DirNode, path: &Path) -> HekitResult<()> {
+        let mut output = String::new();
+        write_json_node(root, 0, &mut output);
+        output.push('\n');
+        fs::write(path, output)
+            .map_err(|e| HekitError::FileOperation(format!("导出目录树失败: {}", e)))
+    }
+}
+
+/// 汇总子节点大小并排序
+fn finish_node(name: String, node: BuildNode) -> DirNode {
+    let mut children: Vec<DirNode> = node
+        .children
+        .into_iter()
+        .map(|(name, child)| finish_node(name, child))
+        .collect();
+    children.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+    let mut files = node.files;
+    files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let size =
+        files.iter().map(|f| f.1).sum::<u64>() + children.iter().map(|c| c.size).sum::<u64>();
+    let file_count = files.len() as u64 + children.iter().map(|c| c.file_count).sum::<u64>();
+    DirNode {
+        name,
+        size,
+        file_count,
+        children,
+        files,
+    }
+}
+
+/// 收集所有子目录的 (路径, 大小)
+fn collect_dirs(node: &DirNode, path: &Path, output: &mut Vec<(PathBuf, u64)>) {
+    for child in &node.children {
+        let child_path = path.join(&child.name);
+        output.push((child_path.clone(), child.size));
+        collect_dirs(child, &child_path, output);
+    }
+}
+
+/// 收集所有文件的 (路径, 大小)
+fn collect_files(node: &DirNode, path: &Path, output: &mut Vec<(PathBuf, u64)>) {
+    output.extend(
+        node.files
+            .iter()
+            .map(|(name, size)| (path.join(name), *size)),
+    );
+    for child in &node.children {
+        collect_files(child, &path.join(&child.name), output);
+    }
+}
+
+/// 根据浏览路径拼出当前目录
+fn trail_path(root: &DirNode, base: &Path, trail: &[usize]) -> PathBuf {
+    let mut path = base.to_path_buf();
+    let mut node = root;
+    for &index in trail {
+        node = &node.children[index];
+        path.push(&node.name);
+    }
+    path
+}
+
+/// 占总大小的百分比
+fn percent(size: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        size as f64 / total as f64 * 100.0
+    }
+}
+
+/// 占用比例条
+fn usage_bar(size: u64, total: u64) -> String {
+    let filled = if total == 0 {
+        0
+    } else {
+        ((size as f64 / total as f64) * BAR_WIDTH as f64).round() as usize
+    };
+    let (full, empty) = if utils::supports_unicode() {
+        ("█", "░")
+    } else {
+        ("#", "-")
+    };
+    format!(
+        "[{}{}]",
+        full.repeat(filled.min(BAR_WIDTH)),
+        empty.repeat(BAR_WIDTH - filled.min(BAR_WIDTH))
+    )
+}
+
+/// 递归写出 JSON 节点
+fn write_json_node(node: &DirNode, indent: usize, output: &mut String) {
+    let pad = "  ".repeat(indent);
+    output.push_str(&format!(
+        "{}{{\"name\": {}, \"size\": {}, \"files\": {}, \"children\": [",
+        pad,
+        export::json_string(&node.name),
+        node.size,
+        node.file_count
+    ));
+    if node.children.is_empty() {
+        output.push_str("]}");
+        return;
+    }
+    output.push('\n');
+    for (index, child) in node.children.iter().enumerate() {
+        write_json_node(child, indent + 1, output);
+        if index + 1 < node.children.len() {
+            output.push(',');
+        }
+        output.push('\n');
+    }
+    output.push_str(&format!("{}]}}", pad));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(files: &[(&str, u64)], children: Vec<(&str, BuildNode)>) -> BuildNode {
+        BuildNode {
+            files: files
+                .iter()
+                .map(|(name, size)| (name.to_string(), *size))
+                .collect(),
+            children: children
+                .into_iter()
+                .map(|(name, child)| (name.to_string(), child))
+                .collect(),
+        }
+    }
+
+    /// root: a.txt(10) b.txt(30)，small/ 含 x(5)，big/ 含 y(100) 和 deep/z(1)，empty/
+    fn sample() -> DirNode {
+        let big = build(&[("y", 100)], vec![("deep", build(&[("z", 1)], vec![]))]);
+        let root = build(
+            &[("a.txt", 10), ("b.txt", 30)],
+            vec![
+                ("small", build(&[("x", 5)], vec![])),
+                ("big", big),
+                ("empty", build(&[], vec![])),
+            ],
+        );
+        finish_node("root".to_string(), root)
+    }
+
+    #[test]
+    fn finish_node_rolls_up_size_and_file_count() {
+        let root = sample();
+        assert_eq!(root.size, 146);
+        assert_eq!(root.file_count, 5);
+
+        let big = &root.children[0];
+        assert_eq!(
+            (big.name.as_str(), big.size, big.file_count),
+            ("big", 101, 2)
+        );
+        assert_eq!(big.children[0].size, 1);
+
+        let empty = root.children.iter().find(|c| c.name == "empty").unwrap();
+        assert_eq!((empty.size, empty.file_count), (0, 0));
+    }
+
+    #[test]
+    fn finish_node_sorts_by_size_then_name() {
+        let root = sample();
+        let children: Vec<&str> = root.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(children, ["big", "small", "empty"]);
+        assert_eq!(
+            root.files,
+            [("b.txt".to_string(), 30), ("a.txt".to_string(), 10)]
+        );
+
+        // 大小相同时按名称排列
+        let tied = finish_node(
+            "tied".to_string(),
+            build(
+                &[("d", 1), ("c", 1)],
+                vec![("b", build(&[], vec![])), ("a", build(&[], vec![]))],
+            ),
+        );
+        let children: Vec<&str> = tied.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(children, ["a", "b"]);
+        assert_eq!(tied.files[0].0, "c");
+    }
+
+    #[test]
+    fn write_json_node_nests_children() {
+        let root = finish_node(
+            "r\"oot".to_string(),
+            build(
+                &[("f", 3)],
+                vec![
+                    ("a", build(&[("x", 2)], vec![("b", build(&[], vec![]))])),
+                    ("c", build(&[], vec![])),
+                ],
+            ),
+        );
+        let mut output = String::new();
+        write_json_node(&root, 0, &mut output);
+        assert_eq!(
+            output,
+            concat!(
+                "{\"name\": \"r\\\"oot\", \"size\": 5, \"files\": 2, \"children\": [\n",
+                "  {\"name\": \"a\", \"size\": 2, \"files\": 1, \"children\": [\n",
+                "    {\"name\": \"b\", \"size\": 0, \"files\": 0, \"children\": []}\n",
+                "  ]},\n",
+                "  {\"name\": \"c\", \"size\": 0, \"files\": 0, \"children\": []}\n",
+                "]}"
+            )
+        );
+    }
+}
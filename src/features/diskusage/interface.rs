@@ -0,0 +1,75 @@
+use crate::error::HekitResult;
+use crate::features::common::ToolInterface;
+use crate::features::diskusage::config::DiskUsageConfig;
+use crate::features::diskusage::core::DiskUsageCore;
+
+/// 磁盘占用分析工具接口
+pub struct DiskUsageTool;
+
+impl ToolInterface for DiskUsageTool {
+    /// 工具名称
+    fn tool_name() -> &'static str {
+        "磁盘占用"
+    }
+
+    /// 显示使用说明
+    fn show_usage() {
+        use crate::utils;
+
+        utils::print_compact_tool_title("磁盘占用分析");
+        println!();
+
+        println!("参数说明:");
+        println!("  -d, --path       要分析的文件夹（默认当前目录）");
+        println!("  -n, --top        显示最大的前N个目录和文件（默认10）");
+        println!("  -j, --threads    遍历线程数（默认按CPU核心数）");
+        println!("  -L, --follow-symlinks  跟随符号链接（自动跳过循环）");
+        println!("  -x, --one-file-system  不统计其他文件系统（挂载点、网络盘）");
+        println!("  -o, --export     导出目录树（.json）");
+        println!("  -i, --browse     分析完成后逐级浏览: 输入序号进入目录，u 返回上一级，回车退出");
+        println!();
+
+        println!("实用示例:");
+        println!("  分析当前目录: -d .");
+        println!("  找出最大的20项: -d D:\\下载 -n 20");
+        println!("  逐级查看占用: -d D:\\ -i");
+        println!("  导出目录树: -d D:\\项目 -o usage.json");
+        println!("  整个磁盘的总量和剩余空间请使用 系统信息 工具的 --disk");
+        println!("  统计的是实际占用的磁盘空间，稀疏文件或压缩文件可能小于文件本身大小");
+
+        utils::print_compact_separator();
+    }
+
+    /// 执行命令
+    fn execute_command(input: &str) -> HekitResult<()> {
+        if input.trim().is_empty() {
+            Self::show_usage();
+            return Ok(());
+        }
+
+        let matches = crate::features::common::execute_common_command(
+            input,
+            "du",
+            DiskUsageConfig::build_clap_command,
+            Self::show_usage,
+        )?;
+
+        if input.trim() == "help" {
+            return Ok(());
+        }
+
+        let config = DiskUsageConfig::from_matches(&matches)
+            .map_err(|e| crate::error::HekitError::UserInput(format!("配置错误: {}", e)))?;
+        let core = DiskUsageCore::new(config);
+        core.execute()
+    }
+}
+
+/// 运行交互式界面
+pub fn run_interactive() -> HekitResult<()> {
+    crate::features::common::run_interactive(
+        DiskUsageTool::tool_name(),
+        DiskUsageTool::execute_command,
+        DiskUsageTool::show_usage,
+    )
+}
@@ -0,0 +1,7 @@
+pub mod config;
+pub mod core;
+pub mod interface;
+
+pub use config::DiskUsageConfig;
+pub use core::DiskUsageCore;
+pub use interface::{run_interactive, DiskUsageTool};
@@ -0,0 +1,270 @@
+use crate::features::dupes::similar::ImageHashKind;
+use crate::features::export::ExportFormat;
+use crate::features::hashing::HashAlgorithm;
+use crate::features::parse;
+use crate::features::search::walker;
+use anyhow::{anyhow, Result};
+use clap::{Arg, Command};
+use std::path::PathBuf;
+
+/// 重复文件查找配置
+#[derive(Debug, Clone)]
+pub struct BatchDupesConfig {
+    pub path: PathBuf,
+    pub name_pattern: String,
+    pub recursive: bool,
+    pub min_size: u64,
+    pub algorithm: HashAlgorithm,
+    pub threads: usize,
+    pub action: DupeAction,
+    pub keep: KeepPolicy,
+    pub export: Option<PathBuf>,
+    pub preview: bool,
+    /// 查找相似图片时使用的感知哈希算法
+    pub similar: Option<ImageHashKind>,
+    /// 相似图片的最大汉明距离
+    pub threshold: u32,
+}
+
+/// 对重复文件执行的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DupeAction {
+    /// 只报告
+    Report,
+    /// 每组只保留一个，删除其余
+    Delete,
+    /// 每组只保留一个，其余替换为指向它的硬链接
+    Hardlink,
+}
+
+/// 每组保留哪个文件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepPolicy {
+    /// 修改时间最早的
+    Oldest,
+    /// 修改时间最新的
+    Newest,
+    /// 路径最短的
+    Shortest,
+}
+
+impl BatchDupesConfig {
+    /// 构建CLAP命令
+    pub fn build_clap_command() -> Command {
+        Command::new("dupes")
+            .about("重复文件查找工具")
+            .arg(
+                Arg::new("path")
+                    .short('d')
+                    .long("path")
+                    .value_name("目标文件夹")
+                    .help("目标文件夹（默认当前目录）")
+                    .default_value("."),
+            )
+            .arg(
+                Arg::new("name")
+                    .short('n')
+                    .long("name")
+                    .value_name("文件名模式")
+                    .help("只比较匹配的文件（支持通配符 *）")
+                    .default_value("*"),
+            )
+            .arg(
+                Arg::new("no-recursive")
+                    .long("no-recursive")
+                    .help("不扫描子目录")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("min-size")
+                    .long("min-size")
+                    .value_name("最小大小")
+                    .help("忽略小于此大小的文件（如 4K、1MB，默认1字节，即跳过空文件）")
+                    .default_value("1"),
+            )
+            .arg(
+                Arg::new("algo")
+                    .long("algo")
+                    .value_name("算法")
+                    .help("哈希算法（blake3, sha256，默认blake3）")
+                    .default_value("blake3"),
+            )
+            .arg(
+                Arg::new("threads")
+                    .short('j')
+                    .long("threads")
+                    .value_name("线程数")
+                    .help("扫描和哈希的线程数（默认按CPU核心数）"),
+            )
+            .arg(
+                Arg::new("action")
+                    .short('a')
+                    .long("action")
+                    .value_name("操作")
+                    .help("操作: report(只报告), delete(删除多余副本), hardlink(替换为硬链接)")
+                    .default_value("report"),
+            )
+            .arg(
+                Arg::new("keep")
+                    .short('k')
+                    .long("keep")
+                    .value_name("保留策略")
+                    .help("每组保留: oldest(最早), newest(最新), shortest(路径最短)")
+                    .default_value("oldest"),
+            )
+            .arg(
+                Arg::new("export")
+                    .short('o')
+                    .long("export")
+                    .value_name("报告文件")
+                    .help("导出报告（.csv, .json, .txt）"),
+            )
+            .arg(
+                Arg::new("preview")
+                    .short('v')
+                    .long("preview")
+                    .help("预览操作（不实际删除或链接）")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("similar")
+                    .long("similar")
+                    .value_name("算法")
+                    .help("查找相似图片（感知哈希: dhash, ahash, phash，默认dhash）")
+                    .num_args(0..=1)
+                    .default_missing_value("dhash"),
+            )
+            .arg(
+                Arg::new("threshold")
+                    .long("threshold")
+                    .value_name("距离")
+                    .help("相似图片的最大汉明距离（0-32，默认8，越小越严格）")
+                    .requires("similar"),
+            )
+    }
+
+    /// 从命令行参数解析配置
+    pub fn from_matches(matches: &clap::ArgMatches) -> Result<Self> {
+        let path = matches
+            .get_one::<String>("path")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("."));
+        let name_pattern = matches
+            .get_one::<String>("name")
+            .cloned()
+            .unwrap_or_else(|| "*".to_string());
+        let recursive = !matches.get_flag("no-recursive");
+
+        let min_size = matches
+            .get_one::<String>("min-size")
+            .map(|text| parse::parse_size(text))
+            .transpose()?
+            .unwrap_or(1);
+
+        let algorithm = matches
+            .get_one::<String>("algo")
+            .map(|name| {
+                HashAlgorithm::parse(name).ok_or_else(|| anyhow!("无效的哈希算法: {}", name))
+            })
+            .transpose()?
+            .unwrap_or(HashAlgorithm::Blake3);
+
+        let threads = matches
+            .get_one::<String>("threads")
+            .map(|s| s.parse::<usize>())
+            .transpose()
+            .map_err(|_| anyhow!("线程数必须是数字"))?
+            .unwrap_or_else(walker::default_threads);
+
+        let action = match matches
+            .get_one::<String>("action")
+            .map(|s| s.as_str())
+            .unwrap_or("report")
+        {
+            "report" => DupeAction::Report,
+            "delete" => DupeAction::Delete,
+            "hardlink" => DupeAction::Hardlink,
+            other => return Err(anyhow!("无效的操作: {}", other)),
+        };
+
+        let keep = match matches
+            .get_one::<String>("keep")
+            .map(|s| s.as_str())
+            .unwrap_or("oldest")
+        {
+            "oldest" => KeepPolicy::Oldest,
+            "newest" => KeepPolicy::Newest,
+            "shortest" => KeepPolicy::Shortest,
+            other => return Err(anyhow!("无效的保留策略: {}", other)),
+        };
+
+        let export = matches.get_one::<String>("export").map(PathBuf::from);
+        let preview = matches.get_flag("preview");
+        let similar = matches
+            .get_one::<String>("similar")
+            .map(|name| {
+                ImageHashKind::parse(name).ok_or_else(|| {
+                    anyhow!("无效的感知哈希算法: {}（可用 dhash, ahash, phash）", name)
+                })
+            })
+            .transpose()?;
+        let threshold = matches
+            .get_one::<String>("threshold")
+            .map(|s| s.parse::<u32>())
+            .transpose()
+            .map_err(|_| anyhow!("汉明距离必须是数字"))?
+            .unwrap_or(8);
+
+        let config = Self {
+            path,
+            name_pattern,
+            recursive,
+            min_size,
+            algorithm,
+            threads,
+            action,
+            keep,
+            export,
+            preview,
+            similar,
+            threshold,
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 验证配置参数
+    pub fn validate(&self) -> Result<()> {
+        if !self.path.exists() {
+            return Err(anyhow!("目录不存在: {}", self.path.display()));
+        }
+
+        if !self.path.is_dir() {
+            return Err(anyhow!("路径不是目录: {}", self.path.display()));
+        }
+
+        if self.name_pattern.trim().is_empty() {
+            return Err(anyhow!("文件名匹配模式不能为空"));
+        }
+
+        if self.threads == 0 || self.threads > 64 {
+            return Err(anyhow!("线程数必须在1到64之间"));
+        }
+
+        if let Some(export) = &self.export {
+            ExportFormat::from_path(export)?;
+        }
+
+        if self.threshold > 32 {
+            return Err(anyhow!("汉明距离必须在0到32之间"));
+        }
+
+        // 相似图片并非完全相同，只报告，由用户确认后处理
+        if self.similar.is_some() && self.action != DupeAction::Report {
+            return Err(anyhow!("查找相似图片时只支持报告（-a report）"));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,738 @@
+use crate::error::{HekitError, HekitResult};
+use crate::features::dupes::config::{BatchDupesConfig, DupeAction, KeepPolicy};
+use crate::features::dupes::similar::{self, ImageHashKind, SimilarSet};
+use crate::features::export::{self, ExportFormat};
+use crate::features::hashing;
+use crate::features::search::walker::{self, WalkOptions};
+use crate::hekit_error;
+use crate::progress::ProgressManager;
+use crate::utils;
+use glob::Pattern;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::SystemTime;
+
+/// 部分哈希读取的字节数
+const PARTIAL_HASH_LEN: u64 = 4096;
+
+/// 扫描到的文件及扫描时的元数据，执行删除或链接前据此确认文件未被改动
+#[derive(Debug, Clone)]
+pub struct ScannedFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl ScannedFile {
+    /// 重新读取元数据，确认文件仍是扫描时的大小和修改时间
+    fn unchanged(&self) -> bool {
+        fs::symlink_metadata(&self.path).is_ok_and(|metadata| {
+            metadata.is_file()
+                && metadata.len() == self.size
+                && metadata.modified().ok() == self.modified
+        })
+    }
+}
+
+/// 一组内容完全相同的文件（第一个为按策略保留的文件）
+#[derive(Debug, Clone)]
+pub struct DupeSet {
+    pub size: u64,
+    pub hash: String,
+    pub files: Vec<ScannedFile>,
+}
+
+impl DupeSet {
+    /// 多余副本占用的空间
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.files.len() as u64 - 1)
+    }
+}
+
+/// 重复文件查找核心逻辑
+pub struct BatchDupesCore {
+    pub config: BatchDupesConfig,
+}
+
+impl BatchDupesCore {
+    /// 创建新的重复文件查找实例
+    pub fn new(config: BatchDupesConfig) -> Self {
+        Self { config }
+    }
+
+    /// 执行查找并按配置处理重复文件
+    pub fn execute(&self) -> HekitResult<()> {
+        if let Some(kind) = self.config.similar {
+            return self.execute_similar(kind);
+        }
+
+        let files = self.scan_files()?;
+        println!("扫描到 {} 个文件", files.len());
+
+        let sets = self.find_duplicates(files);
+        if sets.is_empty() {
+            utils::print_info("未发现重复文件");
+        } else {
+            for line in self.report_lines(&sets) {
+                println!("{}", line);
+            }
+        }
+
+        if let Some(export_path) = &self.config.export {
+            self.export_report(&sets, export_path)?;
+            utils::print_success(&format!("报告已导出: {}", export_path.display()));
+        }
+
+        if sets.is_empty() {
+            return Ok(());
+        }
+        match self.config.action {
+            DupeAction::Report => Ok(()),
+            DupeAction::Delete => self.delete_duplicates(&sets),
+            DupeAction::Hardlink => self.hardlink_duplicates(&sets),
+        }
+    }
+
+    /// 并行扫描文件；跳过符号链接，同一文件的多个硬链接只保留一个
+    fn scan_files(&self) -> HekitResult<Vec<ScannedFile>> {
+        let pattern = Pattern::new(&self.config.name_pattern)
+            .map_err(|e| HekitError::ArgumentParse(format!("文件名模式解析失败: {}", e)))?;
+        let options = WalkOptions {
+            threads: self.config.threads,
+            recursive: self.config.recursive,
+            ..WalkOptions::default()
+        };
+        let cancel = AtomicBool::new(false);
+        let files = Mutex::new(Vec::new());
+        let seen_ids = Mutex::new(HashSet::new());
+
+        let progress = ProgressManager::create_indeterminate("扫描文件中...");
+        let stats = walker::walk(&self.config.path, &options, &cancel, |path| {
+            let name_matches = path
+                .file_name()
+                .is_some_and(|name| pattern.matches(&name.to_string_lossy()));
+            if !name_matches {
+                return;
+            }
+            if self.config.similar.is_some() && !similar::is_supported_image(path) {
+                return;
+            }
+            let metadata = match fs::symlink_metadata(path) {
+                Ok(metadata) if metadata.is_file() => metadata,
+                _ => return,
+            };
+            if metadata.len() < self.config.min_size {
+                return;
+            }
+            if let Some(id) = utils::file_id(&metadata) {
+                if !seen_ids.lock().unwrap().insert(id) {
+                    return;
+                }
+            }
+            files.lock().unwrap().push(ScannedFile {
+                path: path.to_path_buf(),
+                size: metadata.len(),
+                modified: metadata.modified().ok(),
+            });
+        });
+        drop(progress);
+
+        if stats.skipped > 0 {
+            utils::print_warning(&format!("因权限问题跳过 {} 个目录", stats.skipped));
+        }
+
+        let mut files = files.into_inner().unwrap();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    /// 查找相似图片并报告（不执行删除或链接）
+    fn execute_similar(&self, kind: ImageHashKind) -> HekitResult<()> {
+        let files = self.scan_files()?;
+        println!(
+            "扫描到 {} 张图片（{}，最大距离 {}）",
+            files.len(),
+            kind.name(),
+            self.config.threshold
+        );
+
+        let (images, failed) = similar::hash_images(&files, kind, self.config.threads);
+        if failed > 0 {
+            utils::print_warning(&format!("{} 张图片无法解码，已跳过", failed));
+        }
+
+        let sets = similar::group_similar(images, self.config.threshold);
+        if sets.is_empty() {
+            utils::print_info("未发现相似图片");
+        } else {
+            for line in self.similar_report_lines(&sets) {
+                println!("{}", line);
+            }
+        }
+
+        if let Some(export_path) = &self.config.export {
+            self.export_similar(&sets, export_path)?;
+            utils::print_success(&format!("报告已导出: {}", export_path.display()));
+        }
+        Ok(())
+    }
+
+    /// 生成相似图片的文本报告（每组第一张为分辨率最高的图片）
+    fn similar_report_lines(&self, sets: &[SimilarSet]) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (index, set) in sets.iter().enumerate() {
+            lines.push(format!("相似组 {}: {} 张图片", index + 1, set.images.len()));
+            for (position, image) in set.images.iter().enumerate() {
+                let mark = if position == 0 {
+                    "[最佳]  ".to_string()
+                } else {
+                    format!("距离 {:<2} ", set.distance(position))
+                };
+                lines.push(format!(
+                    "  {} {:>11}  {:>10}  {}",
+                    mark,
+                    format!("{}x{}", image.width, image.height),
+                    utils::format_size(image.size),
+                    image.path.display()
+                ));
+            }
+        }
+
+        let extra: usize = sets.iter().map(|set| set.images.len() - 1).sum();
+        let extra_size: u64 = sets
+            .iter()
+            .flat_map(|set| set.images[1..].iter().map(|image| image.size))
+            .sum();
+        lines.push(String::new());
+        lines.push(format!(
+            "共 {} 组相似图片，{} 张较差的副本，共 {}",
+            sets.len(),
+            extra,
+            utils::format_size(extra_size)
+        ));
+        lines
+    }
+
+    /// 导出相似图片报告（格式由扩展名决定）
+    fn export_similar(&self, sets: &[SimilarSet], path: &Path) -> HekitResult<()> {
+        let format = ExportFormat::from_path(path)
+            .map_err(|e| HekitError::UserInput(format!("配置错误: {}", e)))?;
+
+        let content = match format {
+            ExportFormat::Txt => {
+                let mut text = self.similar_report_lines(sets).join("\n");
+                text.push('\n');
+                text
+            }
+            ExportFormat::Csv => {
+                let mut text = String::from("group,best,distance,width,height,size,path\n");
+                for (index, set) in sets.iter().enumerate() {
+                    for (position, image) in set.images.iter().enumerate() {
+                        text.push_str(&format!(
+                            "{},{},{},{},{},{},{}\n",
+                            index + 1,
+                            position == 0,
+                            set.distance(position),
+                            image.width,
+                            image.height,
+                            image.size,
+                            export::csv_field(&image.path.to_string_lossy())
+                        ));
+                    }
+                }
+                text
+            }
+            ExportFormat::Json => {
+                let items: Vec<String> = sets
+                    .iter()
+                    .map(|set| {
+                        let images: Vec<String> = set
+                            .images
+                            .iter()
+                            .enumerate()
+                            .map(|(position, image)| {
+                                format!(
+                                    "{{\"path\": {}, \"width\": {}, \"height\": {}, \"size\": {}, \"distance\": {}}}",
+                                    export::json_string(&image.path.to_string_lossy()),
+                                    image.width,
+                                    image.height,
+                                    image.size,
+                                    set.distance(position)
+                                )
+                            })
+                            .collect();
+                        format!("    {{\"images\": [{}]}}", images.join(", "))
+                    })
+                    .collect();
+                format!("{{\n  \"sets\": [\n{}\n  ]\n}}\n", items.join(",\n"))
+            }
+        };
+
+        fs::write(path, content)
+            .map_err(|e| HekitError::FileOperation(format!("导出报告失败: {}", e)))
+    }
+
+    /// 依次按大小、部分哈希、完整哈希分组，返回按浪费空间从大到小排序的重复组
+    fn find_duplicates(&self, files: Vec<ScannedFile>) -> Vec<DupeSet> {
+        let mut by_size: HashMap<u64, Vec<ScannedFile>> = HashMap::new();
+        for file in files {
+            by_size.entry(file.size).or_default().push(file);
+        }
+        let candidates: Vec<(u64, Vec<ScannedFile>)> = by_size
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .collect();
+
+        // 第二步：只读取文件开头计算部分哈希
+        let partial_groups = self.regroup(candidates, Some(PARTIAL_HASH_LEN), "部分哈希");
+
+        // 第三步：大于部分哈希长度的文件再计算完整哈希
+        let (small, large): (Vec<_>, Vec<_>) = partial_groups
+            .into_iter()
+            .partition(|(size, _, _)| *size <= PARTIAL_HASH_LEN);
+        let large = large
+            .into_iter()
+            .map(|(size, _, paths)| (size, paths))
+            .collect();
+        let full_groups = self.regroup(large, None, "完整哈希");
+
+        let mut sets: Vec<DupeSet> = small
+            .into_iter()
+            .chain(full_groups)
+            .map(|(size, hash, files)| DupeSet {
+                size,
+                hash,
+                files: self.order_by_keep_policy(files),
+            })
+            .collect();
+        sets.sort_by(|a, b| {
+            b.wasted()
+                .cmp(&a.wasted())
+                .then_with(|| a.files[0].path.cmp(&b.files[0].path))
+        });
+        sets
+    }
+
+    /// 对每组候选文件计算哈希（limit 为读取字节数上限），返回仍有多个文件的 (大小, 哈希, 文件) 分组
+    fn regroup(
+        &self,
+        groups: Vec<(u64, Vec<ScannedFile>)>,
+        limit: Option<u64>,
+        stage: &str,
+    ) -> Vec<(u64, String, Vec<ScannedFile>)> {
+        let tasks: Vec<ScannedFile> = groups.into_iter().flat_map(|(_, files)| files).collect();
+        if tasks.is_empty() {
+            return Vec::new();
+        }
+
+        let hashes = self.hash_parallel(&tasks, limit, stage);
+
+        let mut regrouped: HashMap<(u64, String), Vec<ScannedFile>> = HashMap::new();
+        let mut failed = 0;
+        for (file, hash) in tasks.into_iter().zip(hashes) {
+            match hash {
+                Some(hash) => regrouped.entry((file.size, hash)).or_default().push(file),
+                None => failed += 1,
+            }
+        }
+        if failed > 0 {
+            utils::print_warning(&format!(
+                "{}阶段有 {} 个文件读取失败，已跳过",
+                stage, failed
+            ));
+        }
+
+        regrouped
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|((size, hash), mut files)| {
+                files.sort_by(|a, b| a.path.cmp(&b.path));
+                (size, hash, files)
+            })
+            .collect()
+    }
+
+    /// 多线程计算哈希，结果与 tasks 顺序一致（读取失败为 None）
+    fn hash_parallel(
+        &self,
+        tasks: &[ScannedFile],
+        limit: Option<u64>,
+        stage: &str,
+    ) -> Vec<Option<String>> {
+        let progress = ProgressManager::new(tasks.len() as u64, &format!("计算{}...", stage));
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(vec![None; tasks.len()]);
+        let algorithm = self.config.algorithm;
+
+        thread::scope(|scope| {
+            for _ in 0..self.config.threads.min(tasks.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(ScannedFile { path, .. }) = tasks.get(index) else {
+                        break;
+                    };
+                    let hash = match limit {
+                        Some(limit) => File::open(path)
+                            .and_then(|file| hashing::hash_reader(file.take(limit), algorithm)),
+                        None => hashing::hash_file(path, algorithm),
+                    };
+                    results.lock().unwrap()[index] = hash.ok();
+                    progress.inc(1);
+                });
+            }
+        });
+
+        progress.finish_with_message(&format!("{}完成", stage));
+        results.into_inner().unwrap()
+    }
+
+    /// 按保留策略排序，第一个为保留的文件
+    fn order_by_keep_policy(&self, mut files: Vec<ScannedFile>) -> Vec<ScannedFile> {
+        let modified = |file: &ScannedFile| file.modified.unwrap_or(SystemTime::UNIX_EPOCH);
+
+        match self.config.keep {
+            KeepPolicy::Oldest => {
+                files.sort_by_cached_key(|file| (modified(file), file.path.clone()))
+            }
+            KeepPolicy::Newest => files
+                .sort_by_cached_key(|file| (std::cmp::Reverse(modified(file)), file.path.clone())),
+            KeepPolicy::Shortest => files.sort_by_cached_key(|file| {
+                (
+                    file.path.to_string_lossy().chars().count(),
+                    file.path.clone(),
+                )
+            }),
+        }
+        files
+    }
+
+    /// 生成文本报告
+    fn report_lines(&self, sets: &[DupeSet]) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (index, set) in sets.iter().enumerate() {
+            lines.push(format!(
+                "重复组 {}: {} 个文件 × {}，浪费 {}  [{}]",
+                index + 1,
+                set.files.len(),
+                utils::format_size(set.size),
+                utils::format_size(set.wasted()),
+                &set.hash[..12]
+            ));
+            for (position, file) in set.files.iter().enumerate() {
+                let mark = if position == 0 { "[保留]" } else { "      " };
+                lines.push(format!("  {} {}", mark, file.path.display()));
+            }
+        }
+
+        let extra: usize = sets.iter().map(|set| set.files.len() - 1).sum();
+        let wasted: u64 = sets.iter().map(DupeSet::wasted).sum();
+        lines.push(String::new());
+        lines.push(format!(
+            "共 {} 组重复文件，{} 个多余副本，可释放 {}",
+            sets.len(),
+            extra,
+            utils::format_size(wasted)
+        ));
+        lines
+    }
+
+    /// 导出报告（格式由扩展名决定）
+    fn export_report(&self, sets: &[DupeSet], path: &Path) -> HekitResult<()> {
+        let format = ExportFormat::from_path(path)
+            .map_err(|e| HekitError::UserInput(format!("配置错误: {}", e)))?;
+
+        let content = match format {
+            ExportFormat::Txt => {
+                let mut text = self.report_lines(sets).join("\n");
+                text.push('\n');
+                text
+            }
+            ExportFormat::Csv => {
+                let mut text = String::from("group,size,hash,keep,path\n");
+                for (index, set) in sets.iter().enumerate() {
+                    for (position, file) in set.files.iter().enumerate() {
+                        text.push_str(&format!(
+                            "{},{},{},{},{}\n",
+                            index + 1,
+                            set.size,
+                            set.hash,
+                            position == 0,
+                            export::csv_field(&file.path.to_string_lossy())
+                        ));
+                    }
+                }
+                text
+            }
+            ExportFormat::Json => {
+                let items: Vec<String> = sets
+                    .iter()
+                    .map(|set| {
+                        let files: Vec<String> = set
+                            .files
+                            .iter()
+                            .map(|file| export::json_string(&file.path.to_string_lossy()))
+                            .collect();
+                        format!(
+                            "    {{\"size\": {}, \"hash\": {}, \"wasted\": {}, \"files\": [{}]}}",
+                            set.size,
+                            export::json_string(&set.hash),
+                            set.wasted(),
+                            files.join(", ")
+                        )
+                    })
+                    .collect();
+                let wasted: u64 = sets.iter().map(DupeSet::wasted).sum();
+                format!(
+                    "{{\n  \"total_wasted\": {},\n  \"sets\": [\n{}\n  ]\n}}\n",
+                    wasted,
+                    items.join(",\n")
+                )
+            }
+        };
+
+        fs::write(path, content)
+            .map_err(|e| HekitError::FileOperation(format!("导出报告失败: {}", e)))
+    }
+
+    /// 确认是否执行操作
+    fn confirm(&self, prompt: &str) -> HekitResult<bool> {
+        let answer = utils::get_user_input(prompt)
+            .map_err(|e| HekitError::UserInput(format!("读取输入失败: {}", e)))?;
+        if !answer.eq_ignore_ascii_case("y") {
+            utils::print_info("已取消操作");
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// 删除每组中除保留文件外的副本
+    fn delete_duplicates(&self, sets: &[DupeSet]) -> HekitResult<()> {
+        if self.config.preview {
+            for set in sets {
+                for target in &set.files[1..] {
+                    println!("预览删除: {}", target.path.display());
+                }
+            }
+            return Ok(());
+        }
+        let count: usize = sets.iter().map(|set| set.files.len() - 1).sum();
+        if !self.confirm(&format!("确认删除 {} 个重复文件? (y/N): ", count))? {
+            return Ok(());
+        }
+
+        let mut error_count = 0;
+        let mut skipped = 0;
+        for set in sets {
+            // 保留文件被改动后副本不再是它的备份，整组跳过
+            if !set.files[0].unchanged() {
+                eprintln!(
+                    "✗ {} 在扫描后被修改或移除，跳过该组",
+                    set.files[0].path.display()
+                );
+                skipped += set.files.len() - 1;
+                continue;
+            }
+            for target in &set.files[1..] {
+                if !target.unchanged() {
+                    eprintln!("✗ {} 在扫描后被修改或移除，已跳过", target.path.display());
+                    skipped += 1;
+                    continue;
+                }
+                match fs::remove_file(&target.path) {
+                    Ok(_) => println!("✓ 已删除 {}", target.path.display()),
+                    Err(e) => {
+                        eprintln!("✗ 删除 {} 失败: {}", target.path.display(), e);
+                        error_count += 1;
+                    }
+                }
+            }
+        }
+        if skipped > 0 {
+            utils::print_warning(&format!("{} 个文件在扫描后发生变化，未删除", skipped));
+        }
+
+        if error_count > 0 {
+            hekit_error!(FileOperation, "部分重复文件删除失败")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 将每组中的副本替换为指向保留文件的硬链接
+    fn hardlink_duplicates(&self, sets: &[DupeSet]) -> HekitResult<()> {
+        if self.config.preview {
+            for set in sets {
+                for target in &set.files[1..] {
+                    println!(
+                        "预览链接: {} → {}",
+                        target.path.display(),
+                        set.files[0].path.display()
+                    );
+                }
+            }
+            return Ok(());
+        }
+        let count: usize = sets.iter().map(|set| set.files.len() - 1).sum();
+        if !self.confirm(&format!("确认将 {} 个重复文件替换为硬链接? (y/N): ", count))?
+        {
+            return Ok(());
+        }
+
+        let mut error_count = 0;
+        let mut skipped = 0;
+        for set in sets {
+            let original = &set.files[0];
+            if !original.unchanged() {
+                eprintln!(
+                    "✗ {} 在扫描后被修改或移除，跳过该组",
+                    original.path.display()
+                );
+                skipped += set.files.len() - 1;
+                continue;
+            }
+            for target in &set.files[1..] {
+                if !target.unchanged() {
+                    eprintln!("✗ {} 在扫描后被修改或移除，已跳过", target.path.display());
+                    skipped += 1;
+                    continue;
+                }
+                match replace_with_hardlink(&original.path, &target.path) {
+                    Ok(_) => println!("✓ {} → {}", target.path.display(), original.path.display()),
+                    Err(e) => {
+                        eprintln!("✗ 链接 {} 失败: {}", target.path.display(), e);
+                        error_count += 1;
+                    }
+                }
+            }
+        }
+        if skipped > 0 {
+            utils::print_warning(&format!("{} 个文件在扫描后发生变化，未替换", skipped));
+        }
+
+        if error_count > 0 {
+            hekit_error!(FileOperation, "部分重复文件替换为硬链接失败")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// 先在同目录创建临时硬链接，再通过重命名原子地替换目标文件，失败时不影响原文件
+fn replace_with_hardlink(original: &Path, target: &Path) -> std::io::Result<()> {
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    let mut attempt = 0;
+    let temp_path = loop {
+        let temp_path = target.with_file_name(format!(
+            ".{}.hekit-link-{}-{}",
+            name,
+            std::process::id(),
+            attempt
+        ));
+        // hard_link 不会覆盖已存在的文件，名称被占用时换下一个
+        match fs::hard_link(original, &temp_path) {
+            Ok(_) => break temp_path,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && attempt < 100 => {
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    };
+    fs::rename(&temp_path, target).inspect_err(|_| {
+        let _ = fs::remove_file(&temp_path);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hekit_dupes_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn scanned(path: &Path) -> ScannedFile {
+        let metadata = fs::metadata(path).unwrap();
+        ScannedFile {
+            path: path.to_path_buf(),
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        }
+    }
+
+    #[test]
+    fn unchanged_detects_modification_and_removal() {
+        let dir = temp_dir("unchanged");
+        let path = dir.join("a.txt");
+        fs::write(&path, "same").unwrap();
+        let file = scanned(&path);
+        assert!(file.unchanged());
+
+        fs::write(&path, "longer content").unwrap();
+        assert!(!file.unchanged());
+
+        fs::remove_file(&path).unwrap();
+        assert!(!file.unchanged());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn unchanged_detects_same_size_rewrite_by_mtime() {
+        let dir = temp_dir("mtime");
+        let path = dir.join("a.txt");
+        fs::write(&path, "abcd").unwrap();
+        let mut file = scanned(&path);
+        file.modified = file
+            .modified
+            .map(|time| time - std::time::Duration::from_secs(60));
+        assert!(!file.unchanged());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn replace_with_hardlink_links_target_and_cleans_up() {
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = temp_dir("hardlink");
+        let original = dir.join("a.txt");
+        let target = dir.join("b.txt");
+        fs::write(&original, "same").unwrap();
+        fs::write(&target, "same").unwrap();
+        // 占用第一个临时名称，应自动换用下一个
+        let taken = dir.join(format!(".b.txt.hekit-link-{}-0", std::process::id()));
+        fs::write(&taken, "busy").unwrap();
+
+        replace_with_hardlink(&original, &target).unwrap();
+
+        let ino = |path: &Path| fs::metadata(path).unwrap().ino();
+        assert_eq!(ino(&original), ino(&target));
+        assert_eq!(fs::read_to_string(&taken).unwrap(), "busy");
+        let mut names: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names.len(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn replace_with_hardlink_keeps_target_when_original_missing() {
+        let dir = temp_dir("missing");
+        let target = dir.join("b.txt");
+        fs::write(&target, "keep").unwrap();
+
+        assert!(replace_with_hardlink(&dir.join("gone.txt"), &target).is_err());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "keep");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,84 @@
+use crate::error::HekitResult;
+use crate::features::common::ToolInterface;
+use crate::features::dupes::config::BatchDupesConfig;
+use crate::features::dupes::core::BatchDupesCore;
+
+/// 重复文件查找工具接口
+pub struct DupesTool;
+
+impl ToolInterface for DupesTool {
+    /// 工具名称
+    fn tool_name() -> &'static str {
+        "重复文件"
+    }
+
+    /// 显示使用说明
+    fn show_usage() {
+        use crate::utils;
+
+        utils::print_compact_tool_title("重复文件查找");
+        println!();
+
+        println!("参数说明:");
+        println!("  -d, --path       目标文件夹（默认当前目录，递归扫描）");
+        println!("  -n, --name       只比较匹配的文件（支持通配符 *）");
+        println!("  --no-recursive   不扫描子目录");
+        println!("  --min-size       忽略小于此大小的文件（如 4K、1MB，默认1字节）");
+        println!("  --algo           哈希算法（blake3, sha256）");
+        println!("  -j, --threads    线程数（默认按CPU核心数）");
+        println!(
+            "  -a, --action     操作: report(只报告), delete(删除多余副本), hardlink(替换为硬链接)"
+        );
+        println!("  -k, --keep       每组保留: oldest(最早), newest(最新), shortest(路径最短)");
+        println!("  -o, --export     导出报告（.csv, .json, .txt）");
+        println!("  -v, --preview    预览操作（不实际删除或链接）");
+        println!("  --similar        查找相似图片（缩放、重新压缩的副本），算法: dhash(默认), ahash, phash");
+        println!("  --threshold      相似图片的最大汉明距离（0-32，默认8，越小越严格）");
+        println!("                   相似图片只报告，每组按分辨率和文件大小标出最佳的一张");
+        println!();
+
+        println!("实用示例:");
+        println!("  查找重复文件: -d D:\\照片");
+        println!("  只比较大文件: --min-size 1MB");
+        println!("  保留最早的副本并删除其余: -a delete -k oldest -v");
+        println!("  替换为硬链接节省空间: -a hardlink");
+        println!("  导出报告: -o dupes.csv");
+        println!("  查找相似照片: -d D:\\照片 --similar");
+        println!("  更严格的相似判断: --similar phash --threshold 4 -o similar.csv");
+
+        utils::print_compact_separator();
+    }
+
+    /// 执行命令
+    fn execute_command(input: &str) -> HekitResult<()> {
+        if input.trim().is_empty() {
+            Self::show_usage();
+            return Ok(());
+        }
+
+        let matches = crate::features::common::execute_common_command(
+            input,
+            "dupes",
+            BatchDupesConfig::build_clap_command,
+            Self::show_usage,
+        )?;
+
+        if input.trim() == "help" {
+            return Ok(());
+        }
+
+        let config = BatchDupesConfig::from_matches(&matches)
+            .map_err(|e| crate::error::HekitError::UserInput(format!("配置错误: {}", e)))?;
+        let core = BatchDupesCore::new(config);
+        core.execute()
+    }
+}
+
+/// 运行交互式界面
+pub fn run_interactive() -> HekitResult<()> {
+    crate::features::common::run_interactive(
+        DupesTool::tool_name(),
+        DupesTool::execute_command,
+        DupesTool::show_usage,
+    )
+}
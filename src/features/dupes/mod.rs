@@ -0,0 +1,8 @@
+pub mod config;
+pub mod core;
+pub mod interface;
+pub mod similar;
+
+pub use config::BatchDupesConfig;
+pub use core::BatchDupesCore;
+pub use interface::{run_interactive, DupesTool};
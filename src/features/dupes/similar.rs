@@ -0,0 +1,426 @@
+use crate::features::dupes::core::ScannedFile;
+use crate::progress::ProgressManager;
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use std::f64::consts::PI;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// 感知哈希算法（哈希均存为64位整数，pHash 只使用其中63位）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageHashKind {
+    /// 均值哈希：缩小到 8×8 灰度图，与平均亮度比较
+    Average,
+    /// 差异哈希：缩小到 9×8 灰度图，比较相邻像素
+    Difference,
+    /// 感知哈希：缩小到 32×32 后做 DCT，取低频 8×8（去掉直流分量后的63个系数）与中位数比较
+    Perceptual,
+}
+
+impl ImageHashKind {
+    /// 解析算法名称
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "ahash" | "average" => Some(Self::Average),
+            "dhash" | "difference" => Some(Self::Difference),
+            "phash" | "perceptual" => Some(Self::Perceptual),
+            _ => None,
+        }
+    }
+
+    /// 算法名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Average => "aHash",
+            Self::Difference => "dHash",
+            Self::Perceptual => "pHash",
+        }
+    }
+
+    /// 计算图片的感知哈希
+    pub fn compute(&self, image: &DynamicImage) -> u64 {
+        match self {
+            Self::Average => average_hash(image),
+            Self::Difference => difference_hash(image),
+            Self::Perceptual => perceptual_hash(image),
+        }
+    }
+}
+
+/// 一张图片的信息和感知哈希
+#[derive(Debug, Clone)]
+pub struct ImageInfo {
+    pub path: PathBuf,
+    pub width: u32,
+    pub height: u32,
+    pub size: u64,
+    pub hash: u64,
+}
+
+impl ImageInfo {
+    /// 像素总数
+    pub fn pixels(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+}
+
+/// 一组相似图片（第一张为分辨率最高、文件最大的图片）
+#[derive(Debug, Clone)]
+pub struct SimilarSet {
+    pub images: Vec<ImageInfo>,
+}
+
+impl SimilarSet {
+    /// 与第一张图片的汉明距离
+    pub fn distance(&self, index: usize) -> u32 {
+        hamming_distance(self.images[0].hash, self.images[index].hash)
+    }
+}
+
+/// 两个哈希的汉明距离（不同的位数）
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// 是否为可解码的图片格式（按扩展名判断）
+pub fn is_supported_image(path: &Path) -> bool {
+    ImageFormat::from_path(path).is_ok_and(|format| format.can_read())
+}
+
+/// 多线程解码图片并计算哈希，返回 (成功的图片, 解码失败的数量)
+pub fn hash_images(
+    files: &[ScannedFile],
+    kind: ImageHashKind,
+    threads: usize,
+) -> (Vec<ImageInfo>, usize) {
+    let progress = ProgressManager::new(files.len() as u64, "计算图片哈希...");
+    let next = AtomicUsize::new(0);
+    let failed = AtomicUsize::new(0);
+    let images = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..threads.min(files.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(ScannedFile { path, size, .. }) = files.get(index) else {
+                    break;
+                };
+                match image::open(path) {
+                    Ok(image) => {
+                        let (width, height) = image.dimensions();
+                        let hash = kind.compute(&image);
+                        images.lock().unwrap().push(ImageInfo {
+                            path: path.clone(),
+                            width,
+                            height,
+                            size: *size,
+                            hash,
+                        });
+                    }
+                    Err(_) => {
+                        failed.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                progress.inc(1);
+            });
+        }
+    });
+
+    progress.finish_with_message("图片哈希计算完成");
+    let mut images = images.into_inner().unwrap();
+    images.sort_by(|a, b| a.path.cmp(&b.path));
+    (images, failed.into_inner())
+}
+
+/// 把相似图片归为一组，组内按分辨率、文件大小从大到小排序，组间按图片数量排序
+///
+/// 按排序后的顺序处理图片，每组第一张作为代表，图片只与各组代表比较，
+/// 加入距离最近且不超过 threshold 的组，否则自成一组。相似关系不传递，
+/// 组内每张图片与代表的距离都不超过 threshold。
+pub fn group_similar(mut images: Vec<ImageInfo>, threshold: u32) -> Vec<SimilarSet> {
+    images.sort_by(compare_priority);
+
+    let mut groups: Vec<Vec<ImageInfo>> = Vec::new();
+    let mut representatives = BkTree::default();
+    for image in images {
+        match representatives.nearest(image.hash, threshold) {
+            Some(group) => groups[group].push(image),
+            None => {
+                representatives.insert(image.hash, groups.len());
+                groups.push(vec![image]);
+            }
+        }
+    }
+
+    let mut sets: Vec<SimilarSet> = groups
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .map(|images| SimilarSet { images })
+        .collect();
+    sets.sort_by(|a, b| {
+        b.images
+            .len()
+            .cmp(&a.images.len())
+            .then_with(|| a.images[0].path.cmp(&b.images[0].path))
+    });
+    sets
+}
+
+/// 保留优先级：分辨率高、文件大的在前
+fn compare_priority(a: &ImageInfo, b: &ImageInfo) -> std::cmp::Ordering {
+    b.pixels()
+        .cmp(&a.pixels())
+        .then_with(|| b.size.cmp(&a.size))
+        .then_with(|| a.path.cmp(&b.path))
+}
+
+/// 按汉明距离索引各组代表哈希的 BK 树，查询时只需访问距离可能满足条件的分支
+#[derive(Default)]
+struct BkTree {
+    nodes: Vec<BkNode>,
+}
+
+/// BK 树节点
+struct BkNode {
+    hash: u64,
+    group: usize,
+    /// 子节点 (与本节点的距离, 节点序号)
+    children: Vec<(u32, usize)>,
+}
+
+impl BkTree {
+    fn insert(&mut self, hash: u64, group: usize) {
+        let new_node = self.nodes.len();
+        self.nodes.push(BkNode {
+            hash,
+            group,
+            children: Vec::new(),
+        });
+        if new_node == 0 {
+            return;
+        }
+
+        let mut node = 0;
+        loop {
+            let distance = hamming_distance(self.nodes[node].hash, hash);
+            match self.nodes[node]
+                .children
+                .iter()
+                .find(|(d, _)| *d == distance)
+            {
+                Some(&(_, child)) => node = child,
+                None => {
+                    self.nodes[node].children.push((distance, new_node));
+                    return;
+                }
+            }
+        }
+    }
+
+    /// 距离不超过 threshold 的最近代表所在的组（距离相同时取先建立的组）
+    fn nearest(&self, hash: u64, threshold: u32) -> Option<usize> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut best: Option<(u32, usize)> = None;
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            let distance = hamming_distance(node.hash, hash);
+            if distance <= threshold && best.is_none_or(|best| (distance, node.group) < best) {
+                best = Some((distance, node.group));
+            }
+            // 三角不等式：子树中的哈希与本节点的距离为 d，与查询的距离至少为 |d - distance|
+            stack.extend(
+                node.children
+                    .iter()
+                    .filter(|(d, _)| d.abs_diff(distance) <= threshold)
+                    .map(|(_, child)| *child),
+            );
+        }
+        best.map(|(_, group)| group)
+    }
+}
+
+/// 缩放为指定大小的灰度像素（按行排列）
+fn grayscale_pixels(image: &DynamicImage, width: u32, height: u32) -> Vec<f64> {
+    image
+        .resize_exact(width, height, FilterType::Triangle)
+        .to_luma8()
+        .pixels()
+        .map(|pixel| pixel.0[0] as f64)
+        .collect()
+}
+
+/// 按顺序把 64 个比较结果组成哈希
+fn bits_to_hash(bits: impl Iterator<Item = bool>) -> u64 {
+    bits.take(64)
+        .fold(0u64, |hash, bit| (hash << 1) | u64::from(bit))
+}
+
+fn average_hash(image: &DynamicImage) -> u64 {
+    let pixels = grayscale_pixels(image, 8, 8);
+    let mean = pixels.iter().sum::<f64>() / pixels.len() as f64;
+    bits_to_hash(pixels.iter().map(|&value| value > mean))
+}
+
+fn difference_hash(image: &DynamicImage) -> u64 {
+    let pixels = grayscale_pixels(image, 9, 8);
+    bits_to_hash(
+        pixels
+            .chunks(9)
+            .flat_map(|row| row.windows(2).map(|pair| pair[0] > pair[1])),
+    )
+}
+
+fn perceptual_hash(image: &DynamicImage) -> u64 {
+    const SIZE: usize = 32;
+    const LOW: usize = 8;
+    let pixels = grayscale_pixels(image, SIZE as u32, SIZE as u32);
+
+    // 二维 DCT-II 可分解为先按行再按列，只需计算低频部分
+    let cosines: Vec<Vec<f64>> = (0..LOW)
+        .map(|u| {
+            (0..SIZE)
+                .map(|x| ((2 * x + 1) as f64 * u as f64 * PI / (2 * SIZE) as f64).cos())
+                .collect()
+        })
+        .collect();
+    let rows: Vec<[f64; LOW]> = pixels
+        .chunks(SIZE)
+        .map(|row| {
+            let mut coefficients = [0.0; LOW];
+            for (u, coefficient) in coefficients.iter_mut().enumerate() {
+                *coefficient = row.iter().zip(&cosines[u]).map(|(p, c)| p * c).sum();
+            }
+            coefficients
+        })
+        .collect();
+    let mut low = Vec::with_capacity(LOW * LOW);
+    for column_cosines in &cosines {
+        for u in 0..LOW {
+            low.push(
+                rows.iter()
+                    .zip(column_cosines)
+                    .map(|(row, c)| row[u] * c)
+                    .sum::<f64>(),
+            );
+        }
+    }
+
+    // 直流分量只反映整体亮度，数值远大于其他系数，不参与中位数和哈希
+    let ac = &low[1..];
+    let mut sorted = ac.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = sorted[sorted.len() / 2];
+    bits_to_hash(ac.iter().map(|&value| value > median))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    fn image(name: &str, pixels: u32, hash: u64) -> ImageInfo {
+        ImageInfo {
+            path: PathBuf::from(name),
+            width: pixels,
+            height: 1,
+            size: 0,
+            hash,
+        }
+    }
+
+    fn names(sets: &[SimilarSet]) -> Vec<Vec<&str>> {
+        sets.iter()
+            .map(|set| {
+                set.images
+                    .iter()
+                    .map(|image| image.path.to_str().unwrap())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn group_similar_does_not_chain_through_intermediate_images() {
+        // a-b 距离4，b-c 距离4，a-c 距离8
+        let images = vec![
+            image("c", 100, 0xFF),
+            image("b", 200, 0x0F),
+            image("a", 300, 0x00),
+        ];
+        let sets = group_similar(images, 4);
+        assert_eq!(names(&sets), vec![vec!["a", "b"]]);
+        assert!(sets[0].distance(1) <= 4);
+    }
+
+    #[test]
+    fn group_similar_joins_nearest_representative() {
+        let images = vec![
+            image("a", 300, 0x00),
+            image("b", 200, 0xFF),
+            // 与 a 距离3，与 b 距离5
+            image("x", 100, 0x07),
+            // 与 a 距离5，与 b 距离3
+            image("y", 100, 0x1F),
+        ];
+        let sets = group_similar(images, 5);
+        assert_eq!(names(&sets), vec![vec!["a", "x"], vec!["b", "y"]]);
+    }
+
+    #[test]
+    fn bk_tree_matches_linear_search() {
+        let mut seed = 0x2545_F491_4F6C_DD1Du64;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        let representatives: Vec<u64> = (0..300).map(|_| next()).collect();
+        let mut tree = BkTree::default();
+        for (group, &hash) in representatives.iter().enumerate() {
+            tree.insert(hash, group);
+        }
+
+        for _ in 0..300 {
+            // 在某个代表附近翻转几位，保证有命中也有未命中
+            let hash = representatives[(next() % 300) as usize] ^ (next() & next() & next());
+            for threshold in [0, 6, 12] {
+                let expected = representatives
+                    .iter()
+                    .enumerate()
+                    .map(|(group, &other)| (hamming_distance(other, hash), group))
+                    .filter(|&(distance, _)| distance <= threshold)
+                    .min()
+                    .map(|(_, group)| group);
+                assert_eq!(tree.nearest(hash, threshold), expected);
+            }
+        }
+    }
+
+    fn gradient(offset: u8) -> DynamicImage {
+        DynamicImage::ImageLuma8(GrayImage::from_fn(64, 64, |x, y| {
+            Luma([offset + ((x * 3 + y * 2) % 100) as u8])
+        }))
+    }
+
+    #[test]
+    fn perceptual_hash_uses_63_bits_and_ignores_brightness() {
+        let hash = perceptual_hash(&gradient(20));
+        assert_eq!(hash >> 63, 0);
+        assert_eq!(hash, perceptual_hash(&gradient(120)));
+    }
+
+    #[test]
+    fn perceptual_hash_separates_different_images() {
+        let stripes = DynamicImage::ImageLuma8(GrayImage::from_fn(64, 64, |x, _| {
+            Luma([if (x / 8) % 2 == 0 { 30 } else { 220 }])
+        }));
+        assert!(hamming_distance(perceptual_hash(&gradient(20)), perceptual_hash(&stripes)) > 10);
+    }
+}
@@ -0,0 +1,175 @@
+use encoding_rs::{GBK, UTF_16BE, UTF_16LE};
+
+/// 文本文件编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    /// 带 BOM 的 UTF-8
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Gbk,
+}
+
+impl TextEncoding {
+    /// 编码名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf8Bom => "UTF-8 BOM",
+            TextEncoding::Utf16Le => "UTF-16LE",
+            TextEncoding::Utf16Be => "UTF-16BE",
+            TextEncoding::Gbk => "GBK",
+        }
+    }
+}
+
+/// 识别编码时检查的字节数
+const DETECT_LEN: usize = 8192;
+
+/// 识别编码并解码为字符串，判定为二进制文件时返回 None
+///
+/// 依次检查 BOM、无 BOM 的 UTF-16（零字节分布）、NUL 字节（二进制），
+/// 然后尝试 UTF-8，失败时按 GBK 解码。
+pub fn decode(bytes: &[u8]) -> Option<(String, TextEncoding)> {
+    if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return Some((
+            String::from_utf8_lossy(rest).into_owned(),
+            TextEncoding::Utf8Bom,
+        ));
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        let (text, _) = UTF_16LE.decode_without_bom_handling(rest);
+        return Some((text.into_owned(), TextEncoding::Utf16Le));
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let (text, _) = UTF_16BE.decode_without_bom_handling(rest);
+        return Some((text.into_owned(), TextEncoding::Utf16Be));
+    }
+
+    let head = &bytes[..bytes.len().min(DETECT_LEN)];
+    if let Some(encoding) = detect_utf16_without_bom(head) {
+        let decoder = if encoding == TextEncoding::Utf16Le {
+            UTF_16LE
+        } else {
+            UTF_16BE
+        };
+        let (text, _) = decoder.decode_without_bom_handling(bytes);
+        return Some((text.into_owned(), encoding));
+    }
+    if head.contains(&0) {
+        return None;
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Some((text.to_string(), TextEncoding::Utf8)),
+        Err(_) => match GBK.decode_without_bom_handling_and_without_replacement(bytes) {
+            Some(text) => Some((text.into_owned(), TextEncoding::Gbk)),
+            None => Some((
+                String::from_utf8_lossy(bytes).into_owned(),
+                TextEncoding::Utf8,
+            )),
+        },
+    }
+}
+
+/// 按原编码把文本编码回字节（UTF-8 BOM 会写回 BOM，UTF-16 不含 BOM），
+/// 文本中有该编码无法表示的字符时返回 None
+pub fn encode(text: &str, encoding: TextEncoding) -> Option<Vec<u8>> {
+    match encoding {
+        TextEncoding::Utf8 => Some(text.as_bytes().to_vec()),
+        TextEncoding::Utf8Bom => {
+            let mut bytes = vec![0xEF, 0xBB, 0xBF];
+            bytes.extend_from_slice(text.as_bytes());
+            Some(bytes)
+        }
+        TextEncoding::Utf16Le => Some(text.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+        TextEncoding::Utf16Be => Some(text.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+        TextEncoding::Gbk => {
+            let (bytes, _, had_errors) = GBK.encode(text);
+            (!had_errors).then(|| bytes.into_owned())
+        }
+    }
+}
+
+/// 根据零字节的位置判断无 BOM 的 UTF-16（多见于 ASCII 为主的文本）
+fn detect_utf16_without_bom(head: &[u8]) -> Option<TextEncoding> {
+    let pairs = head.len() / 2;
+    if pairs < 4 {
+        return None;
+    }
+
+    let even_zeros = head.iter().step_by(2).filter(|&&b| b == 0).count();
+    let odd_zeros = head.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+    if odd_zeros * 10 >= pairs * 4 && even_zeros * 20 < pairs {
+        Some(TextEncoding::Utf16Le)
+    } else if even_zeros * 10 >= pairs * 4 && odd_zeros * 20 < pairs {
+        Some(TextEncoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 编码后再解码应得到原文本和相同的编码
+    fn round_trip(text: &str, encoding: TextEncoding) {
+        let bytes = encode(text, encoding).unwrap();
+        assert_eq!(
+            decode(&bytes),
+            Some((text.to_string(), encoding)),
+            "{:?}",
+            encoding
+        );
+    }
+
+    #[test]
+    fn utf8_and_gbk_round_trip() {
+        let text = "中文内容 mixed with ASCII\r\n第二行\n";
+        round_trip(text, TextEncoding::Utf8);
+        round_trip(text, TextEncoding::Utf8Bom);
+        round_trip(text, TextEncoding::Gbk);
+    }
+
+    #[test]
+    fn utf16_without_bom_round_trips() {
+        let text = "hello world, plain ascii text\n";
+        round_trip(text, TextEncoding::Utf16Le);
+        round_trip(text, TextEncoding::Utf16Be);
+    }
+
+    #[test]
+    fn utf16_with_bom_is_detected() {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(encode("中文", TextEncoding::Utf16Le).unwrap());
+        assert_eq!(
+            decode(&bytes),
+            Some(("中文".to_string(), TextEncoding::Utf16Le))
+        );
+
+        let mut bytes = vec![0xFE, 0xFF];
+        bytes.extend(encode("中文", TextEncoding::Utf16Be).unwrap());
+        assert_eq!(
+            decode(&bytes),
+            Some(("中文".to_string(), TextEncoding::Utf16Be))
+        );
+    }
+
+    #[test]
+    fn binary_data_is_rejected() {
+        assert_eq!(decode(&[0x89, b'P', b'N', b'G', 0, 0, 0, 0x0D]), None);
+    }
+
+    #[test]
+    fn gbk_cannot_encode_characters_outside_its_range() {
+        assert_eq!(encode("emoji 😀", TextEncoding::Gbk), None);
+        assert!(encode("emoji 😀", TextEncoding::Utf16Le).is_some());
+    }
+
+    #[test]
+    fn empty_input_is_utf8() {
+        assert_eq!(decode(b""), Some((String::new(), TextEncoding::Utf8)));
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use std::path::Path;
+use std::time::SystemTime;
+
+/// 报告导出格式（由文件扩展名决定）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Txt,
+}
+
+impl ExportFormat {
+    /// 根据输出文件扩展名确定格式
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let ext = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match ext.as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            "txt" => Ok(ExportFormat::Txt),
+            _ => Err(anyhow!(
+                "不支持的导出格式: {}（可用 .csv, .json, .txt）",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// 转义 CSV 字段（包含逗号、引号或换行时加引号）
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 转义为 JSON 字符串（含两侧引号）
+pub fn json_string(value: &str) -> String {
+    let mut output = String::with_capacity(value.len() + 2);
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+/// 格式化为本地时间（`2024-01-31 08:30:00`），时间不可用时返回空字符串
+pub fn format_time(time: Option<SystemTime>) -> String {
+    time.map(|time| {
+        DateTime::<Local>::from(time)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    })
+    .unwrap_or_default()
+}
@@ -0,0 +1,482 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// 根据文件头识别出的文件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileKind {
+    /// 标准扩展名
+    pub ext: &'static str,
+    /// MIME 类型
+    pub mime: &'static str,
+    /// 视为正确的其他扩展名
+    pub aliases: &'static [&'static str],
+}
+
+impl FileKind {
+    const fn new(ext: &'static str, mime: &'static str, aliases: &'static [&'static str]) -> Self {
+        Self { ext, mime, aliases }
+    }
+
+    /// 扩展名是否与该类型相符（不区分大小写）
+    pub fn matches_extension(&self, ext: &str) -> bool {
+        ext.eq_ignore_ascii_case(self.ext)
+            || self.aliases.iter().any(|a| ext.eq_ignore_ascii_case(a))
+    }
+}
+
+/// 识别所需读取的文件头长度（tar 的标识位于 257 字节处）
+const SNIFF_LEN: usize = 512;
+
+/// 文件大类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileCategory {
+    Image,
+    Video,
+    Audio,
+    Archive,
+    Document,
+    Code,
+}
+
+impl FileCategory {
+    /// 解析大类名称（不区分大小写）
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.to_lowercase().as_str() {
+            "image" | "images" => Some(Self::Image),
+            "video" | "videos" => Some(Self::Video),
+            "audio" | "music" => Some(Self::Audio),
+            "archive" | "archives" => Some(Self::Archive),
+            "document" | "documents" | "doc" => Some(Self::Document),
+            "code" | "source" => Some(Self::Code),
+            _ => None,
+        }
+    }
+
+    /// 大类的中文名称
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Image => "图片",
+            Self::Video => "视频",
+            Self::Audio => "音频",
+            Self::Archive => "压缩包",
+            Self::Document => "文档",
+            Self::Code => "代码",
+        }
+    }
+
+    /// 根据 MIME 类型判断大类
+    pub fn from_mime(mime: &str) -> Option<Self> {
+        let category = if mime.starts_with("image/") {
+            Self::Image
+        } else if mime.starts_with("video/") {
+            Self::Video
+        } else if mime.starts_with("audio/") {
+            Self::Audio
+        } else if matches!(
+            mime,
+            "application/zip"
+                | "application/gzip"
+                | "application/x-bzip2"
+                | "application/x-xz"
+                | "application/x-7z-compressed"
+                | "application/vnd.rar"
+                | "application/x-tar"
+                | "application/zstd"
+                | "application/java-archive"
+                | "application/vnd.android.package-archive"
+        ) {
+            Self::Archive
+        } else if matches!(
+            mime,
+            "application/pdf"
+                | "application/rtf"
+                | "application/msword"
+                | "application/vnd.ms-excel"
+                | "application/vnd.ms-powerpoint"
+                | "application/x-ole-storage"
+                | "application/epub+zip"
+                | "text/plain"
+                | "text/markdown"
+                | "text/csv"
+        ) || mime.starts_with("application/vnd.openxmlformats-officedocument.")
+            || mime.starts_with("application/vnd.oasis.opendocument.")
+        {
+            Self::Document
+        } else if mime.starts_with("text/x-")
+            || matches!(
+                mime,
+                "text/html"
+                    | "text/css"
+                    | "text/javascript"
+                    | "application/json"
+                    | "application/xml"
+                    | "application/yaml"
+                    | "application/toml"
+            )
+        {
+            Self::Code
+        } else {
+            return None;
+        };
+        Some(category)
+    }
+}
+
+/// 读取文件头并识别文件类型，无法识别时返回 None
+pub fn detect_file(path: &Path) -> Option<FileKind> {
+    let mut file = File::open(path).ok()?;
+    let header = read_header(&mut file).ok()?;
+    detect(&header)
+}
+
+/// 读取识别所需长度的文件头（不足时返回实际读到的内容）
+pub fn read_header(reader: &mut dyn Read) -> io::Result<Vec<u8>> {
+    let mut buffer = vec![0u8; SNIFF_LEN];
+    let mut len = 0;
+    while len < SNIFF_LEN {
+        match reader.read(&mut buffer[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    buffer.truncate(len);
+    Ok(buffer)
+}
+
+/// 读取文件头并判断 MIME 类型（文件头无法识别时按扩展名判断）
+pub fn classify_file(path: &Path) -> Option<&'static str> {
+    let header = File::open(path)
+        .and_then(|mut file| read_header(&mut file))
+        .unwrap_or_default();
+    classify(&header, path)
+}
+
+/// 综合文件头和扩展名判断 MIME 类型
+///
+/// 文件头可识别时以内容为准，扩展名与内容相符时使用扩展名对应的更具体类型
+/// （如 zip 容器中的 docx）；文件头无法识别（文本文件等）时按扩展名判断。
+pub fn classify(header: &[u8], path: &Path) -> Option<&'static str> {
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let by_extension = extension_mime(&ext);
+
+    match detect(header) {
+        Some(kind) if kind.matches_extension(&ext) => Some(by_extension.unwrap_or(kind.mime)),
+        Some(kind) => Some(kind.mime),
+        None => by_extension,
+    }
+}
+
+/// 常见扩展名对应的 MIME 类型（扩展名须为小写）
+fn extension_mime(ext: &str) -> Option<&'static str> {
+    let mime = match ext {
+        "jpg" | "jpeg" | "jpe" | "jfif" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" | "dib" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        "ico" => "image/x-icon",
+        "svg" => "image/svg+xml",
+        "heic" | "heif" => "image/heic",
+        "avif" => "image/avif",
+        "psd" => "image/vnd.adobe.photoshop",
+        "dng" | "nef" | "cr2" | "arw" => "image/x-raw",
+        "mp4" | "m4v" => "video/mp4",
+        "mov" => "video/quicktime",
+        "avi" => "video/x-msvideo",
+        "mkv" => "video/x-matroska",
+        "webm" => "video/webm",
+        "flv" => "video/x-flv",
+        "wmv" => "video/x-ms-wmv",
+        "mpg" | "mpeg" => "video/mpeg",
+        "3gp" | "3g2" => "video/3gpp",
+        "ogv" => "video/ogg",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "ogg" | "oga" | "opus" | "spx" => "audio/ogg",
+        "m4a" | "m4b" => "audio/mp4",
+        "aac" => "audio/aac",
+        "wma" => "audio/x-ms-wma",
+        "mid" | "midi" => "audio/midi",
+        "aiff" | "aif" | "aifc" => "audio/aiff",
+        "zip" => "application/zip",
+        "gz" | "tgz" => "application/gzip",
+        "bz2" | "tbz" | "tbz2" => "application/x-bzip2",
+        "xz" | "txz" => "application/x-xz",
+        "7z" => "application/x-7z-compressed",
+        "rar" => "application/vnd.rar",
+        "tar" => "application/x-tar",
+        "zst" => "application/zstd",
+        "jar" => "application/java-archive",
+        "apk" => "application/vnd.android.package-archive",
+        "pdf" => "application/pdf",
+        "rtf" => "application/rtf",
+        "doc" => "application/msword",
+        "xls" => "application/vnd.ms-excel",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "odt" => "application/vnd.oasis.opendocument.text",
+        "ods" => "application/vnd.oasis.opendocument.spreadsheet",
+        "odp" => "application/vnd.oasis.opendocument.presentation",
+        "epub" => "application/epub+zip",
+        "txt" | "text" | "log" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "csv" => "text/csv",
+        "rs" => "text/x-rust",
+        "py" | "pyw" => "text/x-python",
+        "js" | "mjs" | "cjs" | "jsx" => "text/javascript",
+        "ts" | "tsx" => "text/x-typescript",
+        "c" | "h" => "text/x-c",
+        "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" => "text/x-c++",
+        "cs" => "text/x-csharp",
+        "java" => "text/x-java",
+        "kt" | "kts" => "text/x-kotlin",
+        "go" => "text/x-go",
+        "rb" => "text/x-ruby",
+        "php" => "text/x-php",
+        "swift" => "text/x-swift",
+        "lua" => "text/x-lua",
+        "sql" => "text/x-sql",
+        "vue" => "text/x-vue",
+        "sh" | "bash" | "zsh" => "text/x-shellscript",
+        "ps1" => "text/x-powershell",
+        "bat" | "cmd" => "text/x-msdos-batch",
+        "html" | "htm" => "text/html",
+        "css" | "scss" | "less" => "text/css",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "toml" => "application/toml",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// 根据文件头字节识别文件类型
+pub fn detect(header: &[u8]) -> Option<FileKind> {
+    let starts = |magic: &[u8]| header.starts_with(magic);
+    let at = |offset: usize, magic: &[u8]| {
+        header
+            .get(offset..offset + magic.len())
+            .is_some_and(|bytes| bytes == magic)
+    };
+
+    // 带 BOM 的文本没有特征签名，且 FF FE 会被误认成 MPEG 帧头
+    if has_text_bom(header) {
+        return None;
+    }
+
+    let kind = if starts(&[0xFF, 0xD8, 0xFF]) {
+        FileKind::new("jpg", "image/jpeg", &["jpeg", "jpe", "jfif"])
+    } else if starts(b"\x89PNG\r\n\x1a\n") {
+        FileKind::new("png", "image/png", &[])
+    } else if starts(b"GIF87a") || starts(b"GIF89a") {
+        FileKind::new("gif", "image/gif", &[])
+    } else if starts(b"RIFF") && at(8, b"WEBP") {
+        FileKind::new("webp", "image/webp", &[])
+    } else if starts(b"RIFF") && at(8, b"WAVE") {
+        FileKind::new("wav", "audio/wav", &[])
+    } else if starts(b"RIFF") && at(8, b"AVI ") {
+        FileKind::new("avi", "video/x-msvideo", &[])
+    } else if starts(b"BM") && header.len() >= 14 && at(6, &[0, 0, 0, 0]) {
+        FileKind::new("bmp", "image/bmp", &["dib"])
+    } else if starts(b"II*\0") || starts(b"MM\0*") {
+        FileKind::new("tif", "image/tiff", &["tiff", "dng", "nef", "cr2", "arw"])
+    } else if starts(&[0, 0, 1, 0]) {
+        FileKind::new("ico", "image/x-icon", &[])
+    } else if starts(b"8BPS") {
+        FileKind::new("psd", "image/vnd.adobe.photoshop", &[])
+    } else if at(4, b"ftyp") {
+        detect_ftyp(header)
+    } else if starts(b"%PDF-") {
+        FileKind::new("pdf", "application/pdf", &[])
+    } else if starts(b"{\\rtf") {
+        FileKind::new("rtf", "application/rtf", &[])
+    } else if starts(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1]) {
+        FileKind::new(
+            "doc",
+            "application/x-ole-storage",
+            &["xls", "ppt", "msi", "msg"],
+        )
+    } else if starts(b"PK\x03\x04") || starts(b"PK\x05\x06") {
+        FileKind::new(
+            "zip",
+            "application/zip",
+            &[
+                "docx", "xlsx", "pptx", "odt", "ods", "odp", "epub", "jar", "apk", "xpi", "ipa",
+                "whl", "nupkg", "vsix", "kmz",
+            ],
+        )
+    } else if starts(&[0x1F, 0x8B]) {
+        FileKind::new("gz", "application/gzip", &["tgz"])
+    } else if starts(b"BZh") {
+        FileKind::new("bz2", "application/x-bzip2", &["tbz", "tbz2"])
+    } else if starts(&[0xFD, b'7', b'z', b'X', b'Z', 0]) {
+        FileKind::new("xz", "application/x-xz", &["txz"])
+    } else if starts(b"7z\xBC\xAF\x27\x1C") {
+        FileKind::new("7z", "application/x-7z-compressed", &[])
+    } else if starts(b"Rar!\x1A\x07") {
+        FileKind::new("rar", "application/vnd.rar", &[])
+    } else if at(257, b"ustar") {
+        FileKind::new("tar", "application/x-tar", &[])
+    } else if starts(b"fLaC") {
+        FileKind::new("flac", "audio/flac", &[])
+    } else if starts(b"OggS") {
+        FileKind::new("ogg", "audio/ogg", &["oga", "ogv", "opus", "spx"])
+    } else if starts(b"ID3") || is_mpeg_audio_frame(header) {
+        FileKind::new("mp3", "audio/mpeg", &[])
+    } else if starts(b"MThd") {
+        FileKind::new("mid", "audio/midi", &["midi"])
+    } else if starts(b"FORM") && (at(8, b"AIFF") || at(8, b"AIFC")) {
+        FileKind::new("aiff", "audio/aiff", &["aif", "aifc"])
+    } else if starts(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        if header.windows(4).any(|w| w == b"webm") {
+            FileKind::new("webm", "video/webm", &[])
+        } else {
+            FileKind::new("mkv", "video/x-matroska", &["mka", "mks"])
+        }
+    } else if starts(b"FLV\x01") {
+        FileKind::new("flv", "video/x-flv", &[])
+    } else if starts(b"SQLite format 3\0") {
+        FileKind::new("sqlite", "application/vnd.sqlite3", &["db", "sqlite3"])
+    } else if starts(&[0x7F, b'E', b'L', b'F']) {
+        FileKind::new("elf", "application/x-elf", &["so", "o", "bin", ""])
+    } else if starts(b"MZ") {
+        FileKind::new(
+            "exe",
+            "application/vnd.microsoft.portable-executable",
+            &["dll", "sys", "scr", "com"],
+        )
+    } else if starts(&[0xCA, 0xFE, 0xBA, 0xBE]) {
+        FileKind::new("class", "application/java-vm", &[])
+    } else if starts(b"\0asm") {
+        FileKind::new("wasm", "application/wasm", &[])
+    } else if starts(b"wOFF") {
+        FileKind::new("woff", "font/woff", &[])
+    } else if starts(b"wOF2") {
+        FileKind::new("woff2", "font/woff2", &[])
+    } else {
+        return None;
+    };
+
+    Some(kind)
+}
+
+/// 区分 ISO 媒体容器（MP4/MOV/M4A/HEIC/AVIF 等）
+fn detect_ftyp(header: &[u8]) -> FileKind {
+    let brand = header.get(8..12).unwrap_or_default();
+    match brand {
+        b"M4A " | b"M4B " => FileKind::new("m4a", "audio/mp4", &["m4b"]),
+        b"qt  " => FileKind::new("mov", "video/quicktime", &[]),
+        b"heic" | b"heix" | b"hevc" | b"mif1" | b"msf1" => {
+            FileKind::new("heic", "image/heic", &["heif"])
+        }
+        b"avif" | b"avis" => FileKind::new("avif", "image/avif", &[]),
+        b"3gp4" | b"3gp5" | b"3gp6" | b"3g2a" => FileKind::new("3gp", "video/3gpp", &["3g2"]),
+        _ => FileKind::new("mp4", "video/mp4", &["m4v", "m4a", "mov"]),
+    }
+}
+
+/// 检查是否以 UTF-8 / UTF-16 / UTF-32 的 BOM 开头
+fn has_text_bom(header: &[u8]) -> bool {
+    header.starts_with(&[0xEF, 0xBB, 0xBF])
+        || header.starts_with(&[0xFF, 0xFE])
+        || header.starts_with(&[0xFE, 0xFF])
+        || header.starts_with(&[0x00, 0x00, 0xFE, 0xFF])
+}
+
+/// 检查是否为无 ID3 标签的 MPEG 音频帧头
+/// （11 位同步位 + 合法的版本、层、比特率索引和采样率索引）
+fn is_mpeg_audio_frame(header: &[u8]) -> bool {
+    match header {
+        [0xFF, second, third, ..] => {
+            let sync = second & 0xE0 == 0xE0;
+            let version = (second >> 3) & 0x03;
+            let layer = (second >> 1) & 0x03;
+            let bitrate = third >> 4;
+            let sample_rate = (third >> 2) & 0x03;
+            sync && version != 0x01 && layer != 0x00 && bitrate != 0x0F && sample_rate != 0x03
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+        bytes
+    }
+
+    #[test]
+    fn utf16le_text_with_bom_is_not_mp3() {
+        assert!(detect(&utf16le("hello world")).is_none());
+        assert!(classify(&utf16le("hello"), Path::new("a.txt")).is_some_and(|m| m == "text/plain"));
+    }
+
+    #[test]
+    fn other_text_boms_are_not_detected() {
+        assert!(detect(&[0xEF, 0xBB, 0xBF, b'h', b'i']).is_none());
+        assert!(detect(&[0xFE, 0xFF, 0x00, b'h']).is_none());
+    }
+
+    #[test]
+    fn mpeg_frame_without_id3_is_mp3() {
+        // MPEG-1 Layer III, 128 kbps, 44.1 kHz
+        let kind = detect(&[0xFF, 0xFB, 0x90, 0x64, 0x00]).unwrap();
+        assert_eq!(kind.mime, "audio/mpeg");
+        assert_eq!(kind.ext, "mp3");
+    }
+
+    #[test]
+    fn mpeg_frame_with_invalid_bitrate_or_sample_rate_is_rejected() {
+        assert!(detect(&[0xFF, 0xFB, 0xF0, 0x00]).is_none());
+        assert!(detect(&[0xFF, 0xFB, 0x9C, 0x00]).is_none());
+        assert!(detect(&[0xFF, 0xFB]).is_none());
+    }
+
+    #[test]
+    fn id3_tag_is_mp3() {
+        assert_eq!(detect(b"ID3\x04\x00\x00").unwrap().mime, "audio/mpeg");
+    }
+
+    #[test]
+    fn common_signatures() {
+        assert_eq!(detect(b"\x89PNG\r\n\x1a\n").unwrap().ext, "png");
+        assert_eq!(detect(&[0xFF, 0xD8, 0xFF, 0xE0]).unwrap().ext, "jpg");
+        assert_eq!(detect(b"PK\x03\x04").unwrap().mime, "application/zip");
+        assert_eq!(detect(b"%PDF-1.7").unwrap().ext, "pdf");
+        let mut m4a = b"\0\0\0\x20ftypM4A ".to_vec();
+        m4a.resize(32, 0);
+        assert_eq!(detect(&m4a).unwrap().ext, "m4a");
+        assert!(detect(b"plain text").is_none());
+    }
+
+    #[test]
+    fn classify_prefers_content_over_extension() {
+        assert_eq!(
+            classify(b"\x89PNG\r\n\x1a\n", Path::new("photo.txt")),
+            Some("image/png")
+        );
+        assert_eq!(
+            classify(b"fn main() {}", Path::new("main.rs")),
+            Some("text/x-rust")
+        );
+        assert_eq!(
+            FileCategory::from_mime("audio/mpeg"),
+            Some(FileCategory::Audio)
+        );
+    }
+}
@@ -2,7 +2,15 @@ pub mod clean;
 pub mod common;
 pub mod compress;
 pub mod convert;
+pub mod diskusage;
+pub mod dupes;
+pub mod encoding;
+pub mod export;
+pub mod hashing;
+pub mod magic;
+pub mod parse;
 pub mod rename;
+pub mod replace;
 pub mod search;
 pub mod sysinfo;
 
@@ -10,6 +18,9 @@ pub use clean::interface::run_interactive as run_clean;
 pub use common::*; // 这行确保common模块中的所有公共项都能被其他模块访问
 pub use compress::interface::run_interactive as run_compress;
 pub use convert::interface::run_interactive as run_convert;
+pub use diskusage::interface::run_interactive as run_diskusage;
+pub use dupes::interface::run_interactive as run_dupes;
 pub use rename::interface::run_interactive as run_rename;
+pub use replace::interface::run_interactive as run_replace;
 pub use search::interface::run_interactive as run_search;
 pub use sysinfo::interface::run_interactive as run_sysinfo;
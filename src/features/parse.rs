@@ -0,0 +1,266 @@
+use anyhow::{anyhow, Result};
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+use std::time::{Duration, SystemTime};
+
+/// 文件大小单位及对应字节数（统一按1024进制，与显示的 KB/MB 一致）
+const SIZE_UNITS: [(&str, u64); 14] = [
+    ("", 1),
+    ("b", 1),
+    ("k", 1 << 10),
+    ("kb", 1 << 10),
+    ("kib", 1 << 10),
+    ("m", 1 << 20),
+    ("mb", 1 << 20),
+    ("mib", 1 << 20),
+    ("g", 1 << 30),
+    ("gb", 1 << 30),
+    ("gib", 1 << 30),
+    ("t", 1 << 40),
+    ("tb", 1 << 40),
+    ("tib", 1 << 40),
+];
+
+/// 相对时间单位及对应秒数（分钟只接受 `min`，`m` 在文件大小中表示 MiB，容易混淆）
+const TIME_UNITS: [(&str, u64); 7] = [
+    ("s", 1),
+    ("min", 60),
+    ("h", 3600),
+    ("d", 86400),
+    ("w", 7 * 86400),
+    ("mo", 30 * 86400),
+    ("y", 365 * 86400),
+];
+
+/// 解析时间点
+///
+/// 支持相对时间（`30min`、`12h`、`3d`、`2w`、`6mo`、`1y`，表示距今多久之前）
+/// 和绝对日期（`2024-01-31`、`2024-01-31 08:30`、`2024-01-31T08:30:00`，也可用 `/` 分隔），
+/// 绝对日期按本地时区解释。
+pub fn parse_time_point(text: &str) -> Result<SystemTime> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(anyhow!("时间不能为空"));
+    }
+
+    if let Some(number) = text.strip_suffix(['m', 'M']) {
+        if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(anyhow!(
+                "时间单位 m 有歧义: {}（分钟请写 {}min，月请写 {}mo）",
+                text,
+                number,
+                number
+            ));
+        }
+    }
+
+    if let Some(duration) = parse_duration(text) {
+        return SystemTime::now()
+            .checked_sub(duration)
+            .ok_or_else(|| anyhow!("时间超出范围: {}", text));
+    }
+
+    let normalized = text.replace('/', "-").replace('T', " ");
+    let datetime = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(&normalized, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(&normalized, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "无法识别的时间: {}（可用 3d、2w、1y 等相对时间或 2024-01-31 格式的日期）",
+                text
+            )
+        })?;
+
+    Local
+        .from_local_datetime(&datetime)
+        .earliest()
+        .map(SystemTime::from)
+        .ok_or_else(|| anyhow!("无效的本地时间: {}", text))
+}
+
+/// 解析相对时长，如 `3d`、`2w`，不是相对时间格式时返回 None
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let (number, unit) = text.split_at(split);
+    let number: u64 = number.parse().ok()?;
+    let unit = unit.to_lowercase();
+    let seconds = TIME_UNITS
+        .iter()
+        .find(|(name, _)| *name == unit)
+        .map(|(_, seconds)| *seconds)?;
+    Some(Duration::from_secs(number.checked_mul(seconds)?))
+}
+
+/// 解析文件大小，如 `1024`、`10K`、`5MB`、`1.5GiB`（单位不区分大小写，按1024进制）
+pub fn parse_size(text: &str) -> Result<u64> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let invalid = || {
+        anyhow!(
+            "无效的文件大小: {}（可用 1024、10K、5MB、1.5GiB 等格式）",
+            text
+        )
+    };
+
+    if number.is_empty() {
+        return Err(invalid());
+    }
+    let unit = unit.trim().to_lowercase();
+    let multiplier = SIZE_UNITS
+        .iter()
+        .find(|(name, _)| *name == unit)
+        .map(|(_, bytes)| *bytes)
+        .ok_or_else(invalid)?;
+
+    if let Ok(value) = number.parse::<u64>() {
+        return value
+            .checked_mul(multiplier)
+            .ok_or_else(|| anyhow!("文件大小超出范围: {}", text));
+    }
+    let value: f64 = number.parse().map_err(|_| invalid())?;
+    let bytes = value * multiplier as f64;
+    if !bytes.is_finite() || bytes >= u64::MAX as f64 {
+        return Err(anyhow!("文件大小超出范围: {}", text));
+    }
+    Ok(bytes.round() as u64)
+}
+
+/// 解析逗号分隔的扩展名列表，如 `jpg,png,webp`（允许 `.jpg` 或 `*.jpg` 写法）
+pub fn parse_extensions(text: &str) -> Result<Vec<String>> {
+    let mut extensions: Vec<String> = Vec::new();
+    for part in text.split(',') {
+        let ext = part.trim();
+        let ext = ext
+            .strip_prefix("*.")
+            .or_else(|| ext.strip_prefix('.'))
+            .unwrap_or(ext);
+        if ext.is_empty() {
+            return Err(anyhow!("文件类型列表中有空项: {}", text));
+        }
+        if ext.contains(['/', '\\', '*', '?']) {
+            return Err(anyhow!(
+                "无效的文件类型: {}（只需填写扩展名，如 jpg）",
+                part.trim()
+            ));
+        }
+        if !extensions.iter().any(|existing| existing == ext) {
+            extensions.push(ext.to_string());
+        }
+    }
+    Ok(extensions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_size_accepts_units_and_fractions() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("10K").unwrap(), 10 * 1024);
+        assert_eq!(parse_size("5mb").unwrap(), 5 << 20);
+        assert_eq!(parse_size("5m").unwrap(), 5 << 20);
+        assert_eq!(parse_size("1.5GiB").unwrap(), 3 << 29);
+        assert_eq!(parse_size(" 2 t ").unwrap(), 2 << 40);
+        assert_eq!(parse_size("0.5k").unwrap(), 512);
+    }
+
+    #[test]
+    fn parse_size_rejects_invalid_input() {
+        for text in ["", "K", "10x", "1.2.3M", "-5", "99999999999T"] {
+            assert!(parse_size(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_duration_reads_relative_units() {
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5min"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_duration("12H"), Some(Duration::from_secs(12 * 3600)));
+        assert_eq!(parse_duration("2w"), Some(Duration::from_secs(14 * 86400)));
+        assert_eq!(
+            parse_duration("6mo"),
+            Some(Duration::from_secs(180 * 86400))
+        );
+        assert_eq!(parse_duration("1y"), Some(Duration::from_secs(365 * 86400)));
+    }
+
+    #[test]
+    fn parse_duration_rejects_ambiguous_and_non_durations() {
+        for text in [
+            "5m",
+            "3",
+            "d",
+            "2024-01-31",
+            "3days",
+            "18446744073709551615y",
+        ] {
+            assert_eq!(parse_duration(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_time_point_handles_relative_times() {
+        let before = SystemTime::now();
+        let time = parse_time_point("3d").unwrap();
+        let elapsed = before.duration_since(time).unwrap();
+        assert!(elapsed >= Duration::from_secs(3 * 86400 - 1));
+        assert!(elapsed <= Duration::from_secs(3 * 86400 + 60));
+    }
+
+    #[test]
+    fn parse_time_point_handles_dates_in_local_time() {
+        let expected = |y, m, d, h, min| {
+            SystemTime::from(
+                Local
+                    .with_ymd_and_hms(y, m, d, h, min, 0)
+                    .earliest()
+                    .unwrap(),
+            )
+        };
+        assert_eq!(
+            parse_time_point("2024-01-31").unwrap(),
+            expected(2024, 1, 31, 0, 0)
+        );
+        assert_eq!(
+            parse_time_point("2024/01/31").unwrap(),
+            expected(2024, 1, 31, 0, 0)
+        );
+        assert_eq!(
+            parse_time_point("2024-01-31 08:30").unwrap(),
+            expected(2024, 1, 31, 8, 30)
+        );
+        assert_eq!(
+            parse_time_point("2024-01-31T08:30:00").unwrap(),
+            expected(2024, 1, 31, 8, 30)
+        );
+    }
+
+    #[test]
+    fn parse_time_point_rejects_bare_m_with_hint() {
+        let message = parse_time_point("30m").unwrap_err().to_string();
+        assert!(message.contains("30min"), "{}", message);
+        assert!(message.contains("30mo"), "{}", message);
+        assert!(parse_time_point("").is_err());
+        assert!(parse_time_point("2024-13-01").is_err());
+        assert!(parse_time_point("yesterday").is_err());
+    }
+
+    #[test]
+    fn parse_extensions_normalizes_and_deduplicates() {
+        assert_eq!(
+            parse_extensions("jpg, .png,*.webp,jpg").unwrap(),
+            vec!["jpg", "png", "webp"]
+        );
+        assert!(parse_extensions("jpg,,png").is_err());
+        assert!(parse_extensions("*.j?g").is_err());
+        assert!(parse_extensions("dir/jpg").is_err());
+    }
+}
@@ -0,0 +1,809 @@
+use crate::features::rename::metadata::MetadataSource;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// 音频标签来源：MP3 的 ID3v1/ID3v2，FLAC/OGG 的 Vorbis 注释
+pub struct AudioTagSource;
+
+const AUDIO_TOKENS: [&str; 8] = [
+    "artist",
+    "album",
+    "title",
+    "track",
+    "year",
+    "genre",
+    "albumartist",
+    "disc",
+];
+
+/// 标签数据读取上限，避免异常文件占用过多内存
+const MAX_TAG_SIZE: usize = 16 * 1024 * 1024;
+
+impl MetadataSource for AudioTagSource {
+    fn name(&self) -> &'static str {
+        "音频标签"
+    }
+
+    fn tokens(&self) -> &'static [&'static str] {
+        &AUDIO_TOKENS
+    }
+
+    fn supports(&self, path: &Path) -> bool {
+        matches!(
+            path.extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .as_deref(),
+            Some("mp3" | "flac" | "ogg" | "oga" | "opus")
+        )
+    }
+
+    fn read(&self, path: &Path) -> HashMap<String, String> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(_) => return HashMap::new(),
+        };
+
+        let mut magic = [0u8; 4];
+        if file.read_exact(&mut magic).is_err() || file.seek(SeekFrom::Start(0)).is_err() {
+            return HashMap::new();
+        }
+
+        let tags = match &magic {
+            b"fLaC" => read_flac(&mut file),
+            b"OggS" => read_ogg(&mut file),
+            _ => {
+                // ID3v2 优先，缺少的字段用 ID3v1 补充
+                let mut tags = read_id3v2(&mut file).unwrap_or_default();
+                for (key, value) in read_id3v1(&mut file).unwrap_or_default() {
+                    tags.entry(key).or_insert(value);
+                }
+                Some(tags)
+            }
+        };
+
+        tags.unwrap_or_default()
+    }
+}
+
+/// 统一字段值：去除空白和结尾的空字符，序号只保留 "3/12" 中的 3
+fn insert_tag(tags: &mut HashMap<String, String>, key: &str, value: &str) {
+    let value = value.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    if value.is_empty() {
+        return;
+    }
+    let value = match key {
+        "track" | "disc" => value.split('/').next().unwrap_or(value).trim(),
+        "year" => value.get(..4).unwrap_or(value),
+        _ => value,
+    };
+    tags.entry(key.to_string())
+        .or_insert_with(|| value.to_string());
+}
+
+/// 读取 ID3v2（2.2/2.3/2.4）标签
+fn read_id3v2<R: Read + Seek>(file: &mut R) -> Option<HashMap<String, String>> {
+    let mut header = [0u8; 10];
+    file.read_exact(&mut header).ok()?;
+    if &header[0..3] != b"ID3" {
+        return None;
+    }
+    let version = header[3];
+    let flags = header[5];
+    let size = syncsafe(&header[6..10]) as usize;
+    if size > MAX_TAG_SIZE {
+        return None;
+    }
+
+    let mut data = vec![0u8; size];
+    file.read_exact(&mut data).ok()?;
+
+    // 2.2/2.3 的反同步标志作用于整个标签
+    if flags & 0x80 != 0 && version < 4 {
+        data = remove_unsynchronisation(&data);
+    }
+
+    let mut pos = 0;
+    if flags & 0x40 != 0 && version >= 3 {
+        let ext_size = if version == 3 {
+            u32::from_be_bytes(data.get(0..4)?.try_into().ok()?) as usize + 4
+        } else {
+            syncsafe(data.get(0..4)?) as usize
+        };
+        pos = ext_size;
+    }
+
+    let mut tags = HashMap::new();
+    let (id_len, header_len) = if version == 2 { (3, 6) } else { (4, 10) };
+
+    while pos + header_len <= data.len() {
+        let id = &data[pos..pos + id_len];
+        if id[0] == 0 {
+            break;
+        }
+        let frame_size = match version {
+            2 => u32::from_be_bytes([0, data[pos + 3], data[pos + 4], data[pos + 5]]) as usize,
+            3 => u32::from_be_bytes(data[pos + 4..pos + 8].try_into().ok()?) as usize,
+            _ => syncsafe(&data[pos + 4..pos + 8]) as usize,
+        };
+        let body_start = pos + header_len;
+        let body_end = body_start.checked_add(frame_size)?;
+        if body_end > data.len() {
+            break;
+        }
+
+        let key = match id {
+            b"TPE1" | b"TP1" => Some("artist"),
+            b"TALB" | b"TAL" => Some("album"),
+            b"TIT2" | b"TT2" => Some("title"),
+            b"TRCK" | b"TRK" => Some("track"),
+            b"TYER" | b"TYE" | b"TDRC" => Some("year"),
+            b"TCON" | b"TCO" => Some("genre"),
+            b"TPE2" | b"TP2" => Some("albumartist"),
+            b"TPOS" | b"TPA" => Some("disc"),
+            _ => None,
+        };
+        if let Some(key) = key {
+            let value = decode_text_frame(&data[body_start..body_end]);
+            let value = if key == "genre" {
+                normalize_genre(&value)
+            } else {
+                value
+            };
+            insert_tag(&mut tags, key, &value);
+        }
+
+        pos = body_end;
+    }
+
+    Some(tags)
+}
+
+/// 读取文件末尾 128 字节的 ID3v1 标签
+fn read_id3v1<R: Read + Seek>(file: &mut R) -> Option<HashMap<String, String>> {
+    let len = file.seek(SeekFrom::End(0)).ok()?;
+    if len < 128 {
+        return None;
+    }
+    file.seek(SeekFrom::Start(len - 128)).ok()?;
+    let mut tag = [0u8; 128];
+    file.read_exact(&mut tag).ok()?;
+    if &tag[0..3] != b"TAG" {
+        return None;
+    }
+
+    let mut tags = HashMap::new();
+    insert_tag(&mut tags, "title", &latin1(&tag[3..33]));
+    insert_tag(&mut tags, "artist", &latin1(&tag[33..63]));
+    insert_tag(&mut tags, "album", &latin1(&tag[63..93]));
+    insert_tag(&mut tags, "year", &latin1(&tag[93..97]));
+    // ID3v1.1：注释第29字节为0时第30字节为音轨号
+    if tag[125] == 0 && tag[126] != 0 {
+        insert_tag(&mut tags, "track", &tag[126].to_string());
+    }
+    if let Some(genre) = ID3V1_GENRES.get(tag[127] as usize) {
+        insert_tag(&mut tags, "genre", genre);
+    }
+
+    Some(tags)
+}
+
+/// 读取 FLAC 文件中的 VORBIS_COMMENT 块
+fn read_flac<R: Read + Seek>(file: &mut R) -> Option<HashMap<String, String>> {
+    file.seek(SeekFrom::Start(4)).ok()?;
+    loop {
+        let mut header = [0u8; 4];
+        file.read_exact(&mut header).ok()?;
+        let is_last = header[0] & 0x80 != 0;
+        let block_type = header[0] & 0x7F;
+        let length = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+
+        if block_type == 4 {
+            if length > MAX_TAG_SIZE {
+                return None;
+            }
+            let mut block = vec![0u8; length];
+            file.read_exact(&mut block).ok()?;
+            return parse_vorbis_comments(&block);
+        }
+
+        if is_last {
+            return None;
+        }
+        file.seek(SeekFrom::Current(length as i64)).ok()?;
+    }
+}
+
+/// 读取 OGG（Vorbis/Opus）文件中的注释包（第二个逻辑包）
+fn read_ogg<R: Read>(file: &mut R) -> Option<HashMap<String, String>> {
+    let mut packets: Vec<Vec<u8>> = vec![Vec::new()];
+    let mut total = 0usize;
+
+    while packets.len() <= 2 {
+        let mut header = [0u8; 27];
+        file.read_exact(&mut header).ok()?;
+        if &header[0..4] != b"OggS" {
+            return None;
+        }
+        let segment_count = header[26] as usize;
+        let mut lacing = vec![0u8; segment_count];
+        file.read_exact(&mut lacing).ok()?;
+
+        for segment_len in lacing {
+            let mut segment = vec![0u8; segment_len as usize];
+            file.read_exact(&mut segment).ok()?;
+            total += segment.len();
+            if total > MAX_TAG_SIZE {
+                return None;
+            }
+            packets.last_mut()?.extend_from_slice(&segment);
+            // 长度小于255的段表示当前包结束
+            if segment_len < 255 {
+                packets.push(Vec::new());
+            }
+        }
+    }
+
+    let comment = packets.get(1)?;
+    let body = if comment.starts_with(b"\x03vorbis") {
+        &comment[7..]
+    } else if comment.starts_with(b"OpusTags") {
+        &comment[8..]
+    } else {
+        return None;
+    };
+    parse_vorbis_comments(body)
+}
+
+/// 解析 Vorbis 注释结构（小端长度 + "KEY=value"）
+fn parse_vorbis_comments(data: &[u8]) -> Option<HashMap<String, String>> {
+    let read_u32 = |pos: usize| -> Option<usize> {
+        Some(u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok()?) as usize)
+    };
+
+    let vendor_len = read_u32(0)?;
+    let mut pos = 4usize.checked_add(vendor_len)?;
+    let count = read_u32(pos)?;
+    pos += 4;
+
+    let mut tags = HashMap::new();
+    for _ in 0..count {
+        let len = read_u32(pos)?;
+        pos += 4;
+        let entry = data.get(pos..pos.checked_add(len)?)?;
+        pos += len;
+
+        let entry = String::from_utf8_lossy(entry);
+        let Some((key, value)) = entry.split_once('=') else {
+            continue;
+        };
+        let key = match key.to_uppercase().as_str() {
+            "ARTIST" => "artist",
+            "ALBUM" => "album",
+            "TITLE" => "title",
+            "TRACKNUMBER" => "track",
+            "DATE" | "YEAR" => "year",
+            "GENRE" => "genre",
+            "ALBUMARTIST" | "ALBUM ARTIST" => "albumartist",
+            "DISCNUMBER" => "disc",
+            _ => continue,
+        };
+        insert_tag(&mut tags, key, value);
+    }
+
+    Some(tags)
+}
+
+/// 解码 ID3v2 文本帧（首字节为编码方式）
+fn decode_text_frame(body: &[u8]) -> String {
+    let Some((&encoding, text)) = body.split_first() else {
+        return String::new();
+    };
+    let value = match encoding {
+        1 => decode_utf16(text, None),
+        2 => decode_utf16(text, Some(false)),
+        3 => String::from_utf8_lossy(text).to_string(),
+        _ => latin1(text),
+    };
+    // 多值帧以空字符分隔，只取第一个值
+    value.split('\0').next().unwrap_or_default().to_string()
+}
+
+/// 解码 UTF-16 文本，little_endian 为 None 时根据 BOM 判断
+fn decode_utf16(bytes: &[u8], little_endian: Option<bool>) -> String {
+    let (little_endian, bytes) = match (little_endian, bytes) {
+        (None, [0xFF, 0xFE, rest @ ..]) => (true, rest),
+        (None, [0xFE, 0xFF, rest @ ..]) => (false, rest),
+        (None, rest) => (true, rest),
+        (Some(le), rest) => (le, rest),
+    };
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| {
+            if little_endian {
+                u16::from_le_bytes([pair[0], pair[1]])
+            } else {
+                u16::from_be_bytes([pair[0], pair[1]])
+            }
+        })
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn latin1(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .take_while(|&&b| b != 0)
+        .map(|&b| b as char)
+        .collect()
+}
+
+fn syncsafe(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0u32, |acc, &b| (acc << 7) | (b & 0x7F) as u32)
+}
+
+fn remove_unsynchronisation(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len());
+    let mut previous = 0u8;
+    for &byte in data {
+        if !(previous == 0xFF && byte == 0x00) {
+            output.push(byte);
+        }
+        previous = byte;
+    }
+    output
+}
+
+/// 处理 ID3v2 中 "(17)" 或 "17" 形式的流派编号
+fn normalize_genre(value: &str) -> String {
+    let trimmed = value.trim();
+    let number = trimmed
+        .strip_prefix('(')
+        .and_then(|rest| rest.split(')').next())
+        .unwrap_or(trimmed);
+    match number.parse::<usize>() {
+        Ok(index) => ID3V1_GENRES
+            .get(index)
+            .map(|genre| genre.to_string())
+            .unwrap_or_else(|| trimmed.to_string()),
+        Err(_) => trimmed.to_string(),
+    }
+}
+
+/// ID3v1 标准流派列表
+const ID3V1_GENRES: [&str; 80] = [
+    "Blues",
+    "Classic Rock",
+    "Country",
+    "Dance",
+    "Disco",
+    "Funk",
+    "Grunge",
+    "Hip-Hop",
+    "Jazz",
+    "Metal",
+    "New Age",
+    "Oldies",
+    "Other",
+    "Pop",
+    "R&B",
+    "Rap",
+    "Reggae",
+    "Rock",
+    "Techno",
+    "Industrial",
+    "Alternative",
+    "Ska",
+    "Death Metal",
+    "Pranks",
+    "Soundtrack",
+    "Euro-Techno",
+    "Ambient",
+    "Trip-Hop",
+    "Vocal",
+    "Jazz+Funk",
+    "Fusion",
+    "Trance",
+    "Classical",
+    "Instrumental",
+    "Acid",
+    "House",
+    "Game",
+    "Sound Clip",
+    "Gospel",
+    "Noise",
+    "AlternRock",
+    "Bass",
+    "Soul",
+    "Punk",
+    "Space",
+    "Meditative",
+    "Instrumental Pop",
+    "Instrumental Rock",
+    "Ethnic",
+    "Gothic",
+    "Darkwave",
+    "Techno-Industrial",
+    "Electronic",
+    "Pop-Folk",
+    "Eurodance",
+    "Dream",
+    "Southern Rock",
+    "Comedy",
+    "Cult",
+    "Gangsta",
+    "Top 40",
+    "Christian Rap",
+    "Pop/Funk",
+    "Jungle",
+    "Native American",
+    "Cabaret",
+    "New Wave",
+    "Psychadelic",
+    "Rave",
+    "Showtunes",
+    "Trailer",
+    "Lo-Fi",
+    "Tribal",
+    "Acid Punk",
+    "Acid Jazz",
+    "Polka",
+    "Retro",
+    "Musical",
+    "Rock & Roll",
+    "Hard Rock",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn syncsafe_bytes(value: usize) -> [u8; 4] {
+        [
+            (value >> 21) as u8 & 0x7F,
+            (value >> 14) as u8 & 0x7F,
+            (value >> 7) as u8 & 0x7F,
+            value as u8 & 0x7F,
+        ]
+    }
+
+    /// ID3v2.3/2.4 帧（2.4 的帧大小为 syncsafe 整数）
+    fn frame(version: u8, id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_vec();
+        if version == 4 {
+            bytes.extend(syncsafe_bytes(body.len()));
+        } else {
+            bytes.extend((body.len() as u32).to_be_bytes());
+        }
+        bytes.extend([0, 0]);
+        bytes.extend(body);
+        bytes
+    }
+
+    fn utf8_text(text: &str) -> Vec<u8> {
+        let mut body = vec![3];
+        body.extend(text.as_bytes());
+        body
+    }
+
+    fn id3v2(version: u8, flags: u8, data: &[u8]) -> Vec<u8> {
+        let mut bytes = b"ID3".to_vec();
+        bytes.extend([version, 0, flags]);
+        bytes.extend(syncsafe_bytes(data.len()));
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn id3v23_reads_frames_in_all_text_encodings() {
+        let mut utf16 = vec![1, 0xFF, 0xFE];
+        utf16.extend("标题".encode_utf16().flat_map(u16::to_le_bytes));
+        let mut data = frame(3, b"TPE1", &utf8_text("Artist"));
+        data.extend(frame(3, b"TIT2", &utf16));
+        data.extend(frame(3, b"TALB", b"\x00Alb\xE9"));
+        data.extend(frame(3, b"TRCK", &utf8_text("3/12")));
+        data.extend(frame(3, b"TCON", &utf8_text("(17)")));
+        data.extend(frame(3, b"TYER", &utf8_text("1999-05-01")));
+        data.extend(frame(3, b"TXXX", &utf8_text("ignored")));
+        data.extend([0; 16]);
+
+        let tags = read_id3v2(&mut Cursor::new(id3v2(3, 0, &data))).unwrap();
+        assert_eq!(
+            tags,
+            map(&[
+                ("artist", "Artist"),
+                ("title", "标题"),
+                ("album", "Albé"),
+                ("track", "3"),
+                ("genre", "Rock"),
+                ("year", "1999"),
+            ])
+        );
+    }
+
+    #[test]
+    fn id3v24_uses_syncsafe_frame_sizes() {
+        // 200 字节的帧：syncsafe 编码为 01 48，按普通整数读会错位
+        let title = "T".repeat(199);
+        let mut data = frame(4, b"TIT2", &utf8_text(&title));
+        data.extend(frame(4, b"TPE1", &utf8_text("Artist")));
+
+        let tags = read_id3v2(&mut Cursor::new(id3v2(4, 0, &data))).unwrap();
+        assert_eq!(tags["title"], title);
+        assert_eq!(tags["artist"], "Artist");
+    }
+
+    #[test]
+    fn id3v22_uses_three_byte_ids() {
+        let mut data = b"TT2\x00\x00\x06\x03Title".to_vec();
+        data.extend(b"TP1\x00\x00\x03\x03Me");
+
+        let tags = read_id3v2(&mut Cursor::new(id3v2(2, 0, &data))).unwrap();
+        assert_eq!(tags, map(&[("title", "Title"), ("artist", "Me")]));
+    }
+
+    #[test]
+    fn id3v23_removes_unsynchronisation_and_skips_extended_header() {
+        let mut data = vec![0, 0, 0, 6, 0, 0, 0, 0, 0, 0];
+        data.extend(frame(3, b"TIT2", b"\x00a\xFFb"));
+        // 反同步：0xFF 后插入 0x00
+        let mut unsynchronised = Vec::new();
+        for &byte in &data {
+            unsynchronised.push(byte);
+            if byte == 0xFF {
+                unsynchronised.push(0);
+            }
+        }
+
+        let tags = read_id3v2(&mut Cursor::new(id3v2(3, 0xC0, &unsynchronised))).unwrap();
+        assert_eq!(tags, map(&[("title", "a\u{FF}b")]));
+    }
+
+    #[test]
+    fn id3v2_malformed_input_does_not_panic() {
+        // 不是 ID3 标签、头部不完整、标签数据不足
+        assert_eq!(read_id3v2(&mut Cursor::new(b"RIFF....".to_vec())), None);
+        assert_eq!(read_id3v2(&mut Cursor::new(b"ID3\x03".to_vec())), None);
+        let mut truncated = id3v2(3, 0, &frame(3, b"TIT2", &utf8_text("Title")));
+        truncated.truncate(15);
+        assert_eq!(read_id3v2(&mut Cursor::new(truncated)), None);
+
+        // 帧大小超出标签范围时停止解析，已读取的帧保留
+        let mut data = frame(3, b"TPE1", &utf8_text("Artist"));
+        data.extend(b"TIT2\xFF\xFF\xFF\xFF\x00\x00abc");
+        let tags = read_id3v2(&mut Cursor::new(id3v2(3, 0, &data))).unwrap();
+        assert_eq!(tags, map(&[("artist", "Artist")]));
+
+        // 扩展头长度超出数据、空帧体、不完整的帧头
+        for data in [
+            vec![0xFF, 0xFF, 0xFF, 0xFF],
+            b"TIT2\x00\x00\x00\x00\x00\x00".to_vec(),
+            b"TIT2\x00\x00".to_vec(),
+        ] {
+            for version in [2, 3, 4] {
+                let tags = read_id3v2(&mut Cursor::new(id3v2(version, 0x40, &data)));
+                assert!(tags.is_none_or(|tags| tags.is_empty()));
+            }
+        }
+    }
+
+    fn id3v1(title: &str, track: u8, genre: u8) -> Vec<u8> {
+        let mut tag = vec![0u8; 128];
+        tag[..3].copy_from_slice(b"TAG");
+        tag[3..3 + title.len()].copy_from_slice(title.as_bytes());
+        tag[33..39].copy_from_slice(b"Artist");
+        tag[63..68].copy_from_slice(b"Album");
+        tag[93..97].copy_from_slice(b"2001");
+        tag[126] = track;
+        tag[127] = genre;
+        tag
+    }
+
+    #[test]
+    fn id3v1_reads_fixed_fields_and_track() {
+        let mut bytes = b"audio data".to_vec();
+        bytes.extend(id3v1("Title", 7, 17));
+        let tags = read_id3v1(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(
+            tags,
+            map(&[
+                ("title", "Title"),
+                ("artist", "Artist"),
+                ("album", "Album"),
+                ("year", "2001"),
+                ("track", "7"),
+                ("genre", "Rock"),
+            ])
+        );
+
+        // 无音轨号、未知流派
+        let tags = read_id3v1(&mut Cursor::new(id3v1("T", 0, 255))).unwrap();
+        assert!(!tags.contains_key("track") && !tags.contains_key("genre"));
+    }
+
+    #[test]
+    fn id3v1_requires_full_tag() {
+        assert_eq!(read_id3v1(&mut Cursor::new(b"TAG short".to_vec())), None);
+        assert_eq!(read_id3v1(&mut Cursor::new(vec![0u8; 200])), None);
+    }
+
+    fn vorbis_comments(entries: &[&str]) -> Vec<u8> {
+        let mut data = 6u32.to_le_bytes().to_vec();
+        data.extend(b"vendor");
+        data.extend((entries.len() as u32).to_le_bytes());
+        for entry in entries {
+            data.extend((entry.len() as u32).to_le_bytes());
+            data.extend(entry.as_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn vorbis_comments_map_keys_case_insensitively() {
+        let data = vorbis_comments(&[
+            "artist=Artist",
+            "TITLE=Title",
+            "TrackNumber=04/10",
+            "ALBUM ARTIST=Various",
+            "DATE=2020-01-01",
+            "no separator",
+            "COMMENT=ignored",
+        ]);
+        assert_eq!(
+            parse_vorbis_comments(&data).unwrap(),
+            map(&[
+                ("artist", "Artist"),
+                ("title", "Title"),
+                ("track", "04"),
+                ("albumartist", "Various"),
+                ("year", "2020"),
+            ])
+        );
+    }
+
+    #[test]
+    fn vorbis_comments_reject_truncated_data() {
+        let data = vorbis_comments(&["ARTIST=Artist", "TITLE=Title"]);
+        for len in [0, 3, 10, 15, data.len() - 1] {
+            assert_eq!(parse_vorbis_comments(&data[..len]), None, "{}", len);
+        }
+        // 厂商字符串长度溢出
+        let mut huge = u32::MAX.to_le_bytes().to_vec();
+        huge.extend([0; 8]);
+        assert_eq!(parse_vorbis_comments(&huge), None);
+    }
+
+    fn flac_block(block_type: u8, last: bool, body: &[u8]) -> Vec<u8> {
+        let len = (body.len() as u32).to_be_bytes();
+        let mut bytes = vec![
+            block_type | if last { 0x80 } else { 0 },
+            len[1],
+            len[2],
+            len[3],
+        ];
+        bytes.extend(body);
+        bytes
+    }
+
+    #[test]
+    fn flac_finds_vorbis_comment_block() {
+        let mut bytes = b"fLaC".to_vec();
+        bytes.extend(flac_block(0, false, &[0; 34]));
+        bytes.extend(flac_block(1, false, &[0; 10]));
+        bytes.extend(flac_block(4, true, &vorbis_comments(&["TITLE=Song"])));
+
+        assert_eq!(
+            read_flac(&mut Cursor::new(bytes)),
+            Some(map(&[("title", "Song")]))
+        );
+    }
+
+    #[test]
+    fn flac_without_comments_or_truncated_returns_none() {
+        let mut bytes = b"fLaC".to_vec();
+        bytes.extend(flac_block(0, true, &[0; 34]));
+        assert_eq!(read_flac(&mut Cursor::new(bytes)), None);
+
+        let mut bytes = b"fLaC".to_vec();
+        bytes.extend(flac_block(4, true, &vorbis_comments(&["TITLE=Song"])));
+        bytes.truncate(bytes.len() - 2);
+        assert_eq!(read_flac(&mut Cursor::new(bytes)), None);
+
+        assert_eq!(read_flac(&mut Cursor::new(b"fLaC\x00".to_vec())), None);
+    }
+
+    /// OGG 页：27 字节页头 + 段表 + 数据
+    fn ogg_page(segments: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = b"OggS".to_vec();
+        bytes.extend([0; 22]);
+        bytes.push(segments.len() as u8);
+        bytes.extend(segments.iter().map(|segment| segment.len() as u8));
+        for segment in segments {
+            bytes.extend(*segment);
+        }
+        bytes
+    }
+
+    #[test]
+    fn ogg_reads_comment_packet_across_pages() {
+        let mut comment = b"\x03vorbis".to_vec();
+        comment.extend(vorbis_comments(&[&format!("TITLE={}", "x".repeat(300))]));
+        // 注释包跨两页：长度为255的段表示包未结束
+        let (first, rest) = comment.split_at(255);
+        let mut bytes = ogg_page(&[b"\x01vorbis identification", first]);
+        bytes.extend(ogg_page(&[rest]));
+
+        let tags = read_ogg(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(tags["title"], "x".repeat(300));
+    }
+
+    #[test]
+    fn ogg_reads_opus_tags() {
+        let mut comment = b"OpusTags".to_vec();
+        comment.extend(vorbis_comments(&["ARTIST=Opus"]));
+        let bytes = ogg_page(&[b"OpusHead", &comment]);
+        assert_eq!(
+            read_ogg(&mut Cursor::new(bytes)),
+            Some(map(&[("artist", "Opus")]))
+        );
+    }
+
+    #[test]
+    fn ogg_malformed_input_returns_none() {
+        let mut comment = b"\x03vorbis".to_vec();
+        comment.extend(vorbis_comments(&["TITLE=Song"]));
+        let bytes = ogg_page(&[b"\x01vorbis", &comment]);
+
+        let mut truncated = bytes.clone();
+        truncated.truncate(bytes.len() - 3);
+        assert_eq!(read_ogg(&mut Cursor::new(truncated)), None);
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(read_ogg(&mut Cursor::new(bad_magic)), None);
+
+        // 第二个包不是注释包
+        let bytes = ogg_page(&[b"\x01vorbis", b"\x05vorbis setup"]);
+        assert_eq!(read_ogg(&mut Cursor::new(bytes)), None);
+    }
+
+    #[test]
+    fn decode_utf16_handles_bom_and_odd_length() {
+        assert_eq!(decode_utf16(&[0xFF, 0xFE, b'a', 0], None), "a");
+        assert_eq!(decode_utf16(&[0xFE, 0xFF, 0, b'a'], None), "a");
+        assert_eq!(decode_utf16(&[b'a', 0, b'b'], None), "a");
+        assert_eq!(decode_utf16(&[0, b'a'], Some(false)), "a");
+        assert_eq!(decode_utf16(&[], None), "");
+        // 不成对的代理项替换为 U+FFFD 而不是失败
+        assert_eq!(decode_utf16(&[0x00, 0xD8], None), "\u{FFFD}");
+    }
+
+    #[test]
+    fn audio_source_merges_id3v2_with_id3v1() {
+        let dir = std::env::temp_dir().join(format!("hekit_audio_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("song.mp3");
+        let mut bytes = id3v2(3, 0, &frame(3, b"TIT2", &utf8_text("New Title")));
+        bytes.extend([0xFF, 0xFB, 0x90, 0x00]);
+        bytes.extend(id3v1("Old Title", 5, 0));
+        std::fs::write(&path, bytes).unwrap();
+
+        let tags = AudioTagSource.read(&path);
+        assert_eq!(tags["title"], "New Title");
+        assert_eq!(tags["artist"], "Artist");
+        assert_eq!(tags["track"], "5");
+        assert_eq!(tags["genre"], "Blues");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
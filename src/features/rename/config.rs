@@ -1,3 +1,7 @@
+use crate::features::hashing::HashAlgorithm;
+use crate::features::rename::mapping::MapColumn;
+use crate::features::rename::metadata::{self, Template};
+use crate::features::rename::pipeline::{self, RenameRule};
 use anyhow::{anyhow, Result};
 use clap::{Arg, Command};
 use std::path::PathBuf;
@@ -7,14 +11,66 @@ use std::path::PathBuf;
 pub struct BatchRenameConfig {
     pub path: PathBuf,
     pub match_pattern: String,
-    pub prefix: Option<String>,
-    pub suffix: Option<String>,
-    pub replace_pattern: Option<String>,
-    pub number_start: Option<usize>,
-    pub extension: Option<String>,
+    pub template: Option<Template>,
+    pub number_sort: NumberSort,
+    pub number_width: usize,
+    pub number_step: usize,
+    pub number_separator: String,
+    pub number_position: NumberPosition,
+    pub number_reset: bool,
+    pub fix_ext: bool,
     pub preview: bool,
     pub backup: bool,
     pub case_insensitive: bool,
+    pub recursive: bool,
+    pub include_dirs: bool,
+    pub edit: bool,
+    pub map_file: Option<PathBuf>,
+    pub map_from: MapColumn,
+    pub map_to: MapColumn,
+    pub map_header: bool,
+    pub map_stem: bool,
+    pub hash: Option<HashAlgorithm>,
+    pub hash_len: usize,
+    pub dupes: DupePolicy,
+    /// 重命名规则流水线（--prefix、--replace 等旧式选项也会转换为规则）
+    pub rules: Vec<RenameRule>,
+    pub save_preset: Option<String>,
+}
+
+/// 序号分配的排序方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberSort {
+    /// 按路径字典序
+    Name,
+    /// 自然排序（file2 在 file10 之前）
+    Natural,
+    /// 按修改时间
+    Mtime,
+    /// 按文件大小
+    Size,
+    /// 按EXIF拍摄时间（缺失时使用修改时间）
+    Exif,
+}
+
+/// 序号在文件名中的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberPosition {
+    /// 放在文件名前面
+    Prefix,
+    /// 放在文件名后面
+    Suffix,
+    /// 替换整个文件名
+    Replace,
+}
+
+/// 哈希命名时内容重复文件的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DupePolicy {
+    /// 保留重复文件，不做处理
+    Skip,
+    /// 确认后删除重复文件
+    Remove,
 }
 
 impl BatchRenameConfig {
@@ -36,7 +92,14 @@ impl BatchRenameConfig {
                     .long("match")
                     .value_name("文件模式")
                     .help("选文件（通配符 *）")
-                    .required(true),
+                    .required_unless_present_any(["map", "list-presets"]),
+            )
+            .arg(
+                Arg::new("template")
+                    .short('t')
+                    .long("template")
+                    .value_name("模板")
+                    .help("按模板生成文件名（如 \"{artist} - {track:02} - {title}\"）"),
             )
             .arg(
                 Arg::new("prefix")
@@ -57,14 +120,56 @@ impl BatchRenameConfig {
                     .short('r')
                     .long("replace")
                     .value_name("替换规则")
-                    .help("替换文字（支持简单替换和正则替换）"),
+                    .help("替换文字（old=new 或 s/正则/替换/gi，可重复指定）")
+                    .action(clap::ArgAction::Append),
             )
             .arg(
                 Arg::new("number")
                     .short('n')
                     .long("number")
                     .value_name("起始序号")
-                    .help("加序号（3位补零）"),
+                    .help("加序号（补零位数见 --number-width）"),
+            )
+            .arg(
+                Arg::new("number-sort")
+                    .long("number-sort")
+                    .value_name("排序方式")
+                    .help("序号排序（name, natural, mtime, size, exif）")
+                    .default_value("name"),
+            )
+            .arg(
+                Arg::new("number-width")
+                    .long("number-width")
+                    .value_name("位数")
+                    .help("序号补零位数（默认3）")
+                    .default_value("3"),
+            )
+            .arg(
+                Arg::new("number-step")
+                    .long("number-step")
+                    .value_name("步长")
+                    .help("序号步长（默认1）")
+                    .default_value("1"),
+            )
+            .arg(
+                Arg::new("number-sep")
+                    .long("number-sep")
+                    .value_name("分隔符")
+                    .help("序号与文件名之间的分隔符（默认_）")
+                    .default_value("_"),
+            )
+            .arg(
+                Arg::new("number-pos")
+                    .long("number-pos")
+                    .value_name("位置")
+                    .help("序号位置（prefix, suffix, replace）")
+                    .default_value("suffix"),
+            )
+            .arg(
+                Arg::new("number-reset")
+                    .long("number-reset")
+                    .help("每个目录单独从起始序号计数")
+                    .action(clap::ArgAction::SetTrue),
             )
             .arg(
                 Arg::new("ext")
@@ -73,6 +178,13 @@ impl BatchRenameConfig {
                     .value_name("扩展名")
                     .help("改扩展名（空值删除）"),
             )
+            .arg(
+                Arg::new("fix-ext")
+                    .long("fix-ext")
+                    .help("按文件内容识别类型并修正扩展名")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with("ext"),
+            )
             .arg(
                 Arg::new("preview")
                     .short('v')
@@ -94,50 +206,268 @@ impl BatchRenameConfig {
                     .help("不区分大小写匹配")
                     .action(clap::ArgAction::SetTrue),
             )
+            .arg(
+                Arg::new("recursive")
+                    .long("recursive")
+                    .help("递归处理子目录")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("include-dirs")
+                    .long("include-dirs")
+                    .help("同时重命名匹配的文件夹")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("edit")
+                    .long("edit")
+                    .help("在编辑器中逐行修改文件名")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with_all([
+                        "template", "prefix", "suffix", "replace", "number", "ext", "fix-ext",
+                    ]),
+            )
+            .arg(
+                Arg::new("map")
+                    .long("map")
+                    .value_name("映射文件")
+                    .help("按CSV/TSV映射表重命名（原名→新名）")
+                    .conflicts_with_all([
+                        "template", "prefix", "suffix", "replace", "number", "ext", "fix-ext",
+                        "edit",
+                    ]),
+            )
+            .arg(
+                Arg::new("rule")
+                    .long("rule")
+                    .value_name("规则")
+                    .help("按顺序应用的规则（如 \"replace: a=b\"、\"prefix: x_\"、\"case: lower\"，可重复指定）")
+                    .action(clap::ArgAction::Append)
+                    .conflicts_with_all(["prefix", "suffix", "replace", "number", "ext", "edit", "map"]),
+            )
+            .arg(
+                Arg::new("preset")
+                    .long("preset")
+                    .value_name("预设名")
+                    .help("使用已保存的规则预设")
+                    .conflicts_with_all(["prefix", "suffix", "replace", "number", "ext", "edit", "map"]),
+            )
+            .arg(
+                Arg::new("save-preset")
+                    .long("save-preset")
+                    .value_name("预设名")
+                    .help("将本次的规则保存为预设"),
+            )
+            .arg(
+                Arg::new("list-presets")
+                    .long("list-presets")
+                    .help("列出已保存的规则预设")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("hash")
+                    .long("hash")
+                    .value_name("算法")
+                    .help("按文件内容哈希命名（sha256, blake3）")
+                    .conflicts_with_all(["template", "edit", "map", "include-dirs"]),
+            )
+            .arg(
+                Arg::new("hash-len")
+                    .long("hash-len")
+                    .value_name("长度")
+                    .help("哈希截取长度（默认8）")
+                    .default_value("8"),
+            )
+            .arg(
+                Arg::new("dupes")
+                    .long("dupes")
+                    .value_name("处理方式")
+                    .help("内容重复文件的处理（skip 保留, remove 删除）")
+                    .default_value("skip"),
+            )
+            .arg(
+                Arg::new("map-from")
+                    .long("map-from")
+                    .value_name("列")
+                    .help("原文件名所在列（序号或表头名，默认1）")
+                    .default_value("1"),
+            )
+            .arg(
+                Arg::new("map-to")
+                    .long("map-to")
+                    .value_name("列")
+                    .help("新文件名所在列（序号或表头名，默认2）")
+                    .default_value("2"),
+            )
+            .arg(
+                Arg::new("map-header")
+                    .long("map-header")
+                    .help("映射文件首行为表头")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("map-stem")
+                    .long("map-stem")
+                    .help("只按主文件名匹配（保留原扩展名）")
+                    .action(clap::ArgAction::SetTrue),
+            )
     }
 
     /// 从命令行参数解析配置
     pub fn from_matches(matches: &clap::ArgMatches) -> Result<Self> {
         let path = matches
             .get_one::<String>("path")
-            .map(|s| PathBuf::from(s))
+            .map(PathBuf::from)
             .unwrap_or_else(|| PathBuf::from("."));
 
-        let match_pattern = matches
-            .get_one::<String>("match")
-            .ok_or_else(|| anyhow!("缺少必要的 match 参数"))?
-            .to_string();
-
-        let prefix = matches.get_one::<String>("prefix").cloned();
-        let suffix = matches.get_one::<String>("suffix").cloned();
-        let replace_pattern = matches.get_one::<String>("replace").cloned();
-        let extension = matches.get_one::<String>("ext").cloned();
-
-        // 修复：序号参数解析逻辑
-        let number_start = if matches.contains_id("number") {
-            match matches.get_one::<String>("number") {
-                Some(num_str) if !num_str.is_empty() => num_str.parse::<usize>().ok(),
-                _ => Some(1), // 当用户只输入 -n 没有值时，默认使用1
-            }
-        } else {
-            None
+        let map_file = matches.get_one::<String>("map").map(PathBuf::from);
+
+        let match_pattern = match matches.get_one::<String>("match") {
+            Some(pattern) => pattern.to_string(),
+            None if map_file.is_some() => "*".to_string(),
+            None => return Err(anyhow!("缺少必要的 match 参数")),
+        };
+
+        let template = matches
+            .get_one::<String>("template")
+            .map(|text| Template::parse(text, &metadata::default_sources()))
+            .transpose()?;
+        let fix_ext = matches.get_flag("fix-ext");
+
+        let number_sort = match matches
+            .get_one::<String>("number-sort")
+            .map(|s| s.as_str())
+            .unwrap_or("name")
+        {
+            "name" => NumberSort::Name,
+            "natural" => NumberSort::Natural,
+            "mtime" => NumberSort::Mtime,
+            "size" => NumberSort::Size,
+            "exif" => NumberSort::Exif,
+            other => return Err(anyhow!("无效的序号排序方式: {}", other)),
+        };
+        let number_position = match matches
+            .get_one::<String>("number-pos")
+            .map(|s| s.as_str())
+            .unwrap_or("suffix")
+        {
+            "prefix" => NumberPosition::Prefix,
+            "suffix" => NumberPosition::Suffix,
+            "replace" => NumberPosition::Replace,
+            other => return Err(anyhow!("无效的序号位置: {}", other)),
         };
+        let number_width = matches
+            .get_one::<String>("number-width")
+            .map(|s| s.parse::<usize>())
+            .transpose()
+            .map_err(|_| anyhow!("序号位数必须是数字"))?
+            .unwrap_or(3);
+        let number_step = matches
+            .get_one::<String>("number-step")
+            .map(|s| s.parse::<usize>())
+            .transpose()
+            .map_err(|_| anyhow!("序号步长必须是数字"))?
+            .unwrap_or(1);
+        let number_separator = matches
+            .get_one::<String>("number-sep")
+            .cloned()
+            .unwrap_or_else(|| "_".to_string());
+        let number_reset = matches.get_flag("number-reset");
 
         let preview = matches.get_flag("preview");
         let backup = matches.get_flag("backup");
         let case_insensitive = matches.get_flag("case");
+        let recursive = matches.get_flag("recursive");
+        let include_dirs = matches.get_flag("include-dirs");
+        let edit = matches.get_flag("edit");
+        let map_from = MapColumn::parse(
+            matches
+                .get_one::<String>("map-from")
+                .map(|s| s.as_str())
+                .unwrap_or("1"),
+        );
+        let map_to = MapColumn::parse(
+            matches
+                .get_one::<String>("map-to")
+                .map(|s| s.as_str())
+                .unwrap_or("2"),
+        );
+        let map_header = matches.get_flag("map-header");
+        let map_stem = matches.get_flag("map-stem");
+
+        let hash = matches
+            .get_one::<String>("hash")
+            .map(|name| {
+                HashAlgorithm::parse(name).ok_or_else(|| anyhow!("无效的哈希算法: {}", name))
+            })
+            .transpose()?;
+        let hash_len = matches
+            .get_one::<String>("hash-len")
+            .map(|s| s.parse::<usize>())
+            .transpose()
+            .map_err(|_| anyhow!("哈希长度必须是数字"))?
+            .unwrap_or(8);
+        let dupes = match matches
+            .get_one::<String>("dupes")
+            .map(|s| s.as_str())
+            .unwrap_or("skip")
+        {
+            "skip" => DupePolicy::Skip,
+            "remove" => DupePolicy::Remove,
+            other => return Err(anyhow!("无效的重复文件处理方式: {}", other)),
+        };
+
+        // 显式规则（预设在前，--rule 在后）优先；否则由旧式选项按固定顺序生成
+        let mut rules = match matches.get_one::<String>("preset") {
+            Some(name) => pipeline::load_preset(name)?,
+            None => Vec::new(),
+        };
+        for text in matches.get_many::<String>("rule").unwrap_or_default() {
+            rules.push(RenameRule::parse(text)?);
+        }
+        if rules.is_empty() {
+            let replace_texts: Vec<&str> = matches
+                .get_many::<String>("replace")
+                .unwrap_or_default()
+                .map(|s| s.as_str())
+                .collect();
+            rules = RenameRule::from_legacy(
+                matches.get_one::<String>("prefix").map(|s| s.as_str()),
+                matches.get_one::<String>("suffix").map(|s| s.as_str()),
+                &replace_texts,
+                matches.get_one::<String>("number").map(|s| s.as_str()),
+                matches.get_one::<String>("ext").map(|s| s.as_str()),
+            )?;
+        }
+        let save_preset = matches.get_one::<String>("save-preset").cloned();
 
         let config = Self {
             path,
             match_pattern,
-            prefix,
-            suffix,
-            replace_pattern,
-            number_start,
-            extension,
+            template,
+            number_sort,
+            number_width,
+            number_step,
+            number_separator,
+            number_position,
+            number_reset,
+            fix_ext,
             preview,
             backup,
             case_insensitive,
+            recursive,
+            include_dirs,
+            edit,
+            map_file,
+            map_from,
+            map_to,
+            map_header,
+            map_stem,
+            hash,
+            hash_len,
+            dupes,
+            rules,
+            save_preset,
         };
 
         config.validate()?;
@@ -158,41 +488,48 @@ impl BatchRenameConfig {
             return Err(anyhow!("文件匹配模式不能为空"));
         }
 
-        if let Some(prefix) = &self.prefix {
-            if prefix.contains(std::path::MAIN_SEPARATOR) {
-                return Err(anyhow!("前缀不能包含路径分隔符: {}", prefix));
+        if let Some(map_file) = &self.map_file {
+            if !map_file.is_file() {
+                return Err(anyhow!("映射文件不存在: {}", map_file.display()));
             }
         }
 
-        if let Some(suffix) = &self.suffix {
-            if suffix.contains(std::path::MAIN_SEPARATOR) {
-                return Err(anyhow!("后缀不能包含路径分隔符: {}", suffix));
-            }
+        if self.number_width > 10 {
+            return Err(anyhow!("序号补零位数不能超过10"));
         }
 
-        if let Some(ext) = &self.extension {
-            if !ext.is_empty() && ext.contains('.') {
-                return Err(anyhow!(
-                    "扩展名不能包含点号，请直接输入扩展名（如 'txt' 而不是 '.txt'）"
-                ));
-            }
+        if self.number_step == 0 {
+            return Err(anyhow!("序号步长不能为0"));
         }
 
-        if let Some(start) = self.number_start {
-            if start == 0 {
-                return Err(anyhow!("序号起始值不能为0"));
+        if self.number_separator.contains('/') || self.number_separator.contains('\\') {
+            return Err(anyhow!(
+                "序号分隔符不能包含路径分隔符: {}",
+                self.number_separator
+            ));
+        }
+
+        if let Some(name) = &self.save_preset {
+            pipeline::check_preset_name(name)?;
+            if self.rules.is_empty() {
+                return Err(anyhow!("没有可保存的规则"));
             }
         }
 
-        let has_rename_method = self.prefix.is_some()
-            || self.suffix.is_some()
-            || self.replace_pattern.is_some()
-            || self.number_start.is_some()
-            || self.extension.is_some();
+        if self.hash_len < 4 || self.hash_len > 64 {
+            return Err(anyhow!("哈希长度必须在4到64之间"));
+        }
+
+        let has_rename_method = self.template.is_some()
+            || self.fix_ext
+            || self.edit
+            || self.map_file.is_some()
+            || self.hash.is_some()
+            || !self.rules.is_empty();
 
         if !has_rename_method {
             return Err(anyhow!(
-                "请至少指定一种重命名方式（--prefix, --suffix, --replace, --number 或 --ext）"
+                "请至少指定一种重命名方式（--template, --rule, --prefix, --suffix, --replace, --number, --ext, --fix-ext, --edit, --map 或 --hash）"
             ));
         }
 
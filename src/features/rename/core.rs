@@ -1,154 +1,702 @@
 use crate::error::{HekitError, HekitResult};
-use crate::features::rename::config::BatchRenameConfig;
+use crate::features::hashing::{self, HashAlgorithm};
+use crate::features::magic;
+use crate::features::rename::config::{BatchRenameConfig, DupePolicy};
+use crate::features::rename::metadata::{self, MetadataSource, Template};
+use crate::features::rename::{edit, mapping, numbering};
 use crate::hekit_error; // 添加宏导入
 use crate::progress::ProgressManager;
 use crate::utils;
-use glob::glob;
-use regex::Regex;
+use glob::{glob, MatchOptions, Pattern};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
 /// 批量重命名核心逻辑
 pub struct BatchRenameCore {
     pub config: BatchRenameConfig,
+    sources: Vec<Box<dyn MetadataSource>>,
 }
 
+/// 逐条规则的中间结果：(规则, 结果)
+type RuleSteps = Vec<(String, String)>;
+
 impl BatchRenameCore {
     /// 创建新的批量重命名实例
     pub fn new(config: BatchRenameConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            sources: metadata::default_sources(),
+        }
+    }
+
+    /// 添加额外的元数据来源（供模板标记使用）
+    pub fn with_source(mut self, source: Box<dyn MetadataSource>) -> Self {
+        self.sources.push(source);
+        self
     }
 
     /// 执行批量重命名
     pub fn execute(&self) -> HekitResult<()> {
         let files = self.scan_files()?;
-        let file_pairs: Vec<(PathBuf, PathBuf)> = files
-            .iter()
-            .enumerate()
-            .map(|(i, file_path)| {
-                let new_path = self.generate_new_filename(file_path, i + 1)?;
-                Ok((file_path.clone(), new_path))
-            })
-            .collect::<HekitResult<Vec<_>>>()?;
+        if self.config.edit {
+            return self.execute_edit(&files);
+        }
+        if let Some(map_file) = &self.config.map_file {
+            return self.execute_map(map_file, &files);
+        }
+
+        if let Some(algorithm) = self.config.hash {
+            return self.execute_hash(algorithm, &files);
+        }
+
+        let indices = numbering::assign_indices(&files, self.config.number_reset);
+        let mut file_pairs = Vec::new();
+        let mut steps = HashMap::new();
+        for (file_path, index) in files.iter().zip(indices) {
+            if let Some((new_path, rule_steps)) =
+                self.generate_new_filename(file_path, index, None)?
+            {
+                // 名称不变的文件无需重命名
+                if &new_path != file_path {
+                    // 多条规则时在预览中显示中间结果，便于调试
+                    if rule_steps.len() > 1 {
+                        steps.insert(file_path.clone(), rule_steps);
+                    }
+                    file_pairs.push((file_path.clone(), new_path));
+                }
+            }
+        }
+
+        if self.config.fix_ext {
+            self.report_unknown_types(&files);
+        }
 
         if self.config.preview {
-            return self.execute_preview(&file_pairs);
+            return self.execute_preview(&file_pairs, &steps);
         }
 
         // 删除备份选项，直接使用批量重命名
         self.execute_batch(&file_pairs)
     }
 
-    /// 扫描匹配的文件
-    fn scan_files(&self) -> HekitResult<Vec<PathBuf>> {
-        let pattern = self.config.path.join(&self.config.match_pattern);
-        let pattern = pattern.to_string_lossy().to_string();
+    /// 执行编辑器重命名模式
+    fn execute_edit(&self, files: &[PathBuf]) -> HekitResult<()> {
+        if files.is_empty() {
+            utils::print_warning("没有找到匹配的文件");
+            return Ok(());
+        }
 
-        let mut files = Vec::new();
+        let file_pairs: Vec<(PathBuf, PathBuf)> = edit::edit_in_editor(files)?
+            .into_iter()
+            .filter(|(old_path, new_path)| old_path != new_path)
+            .collect();
 
-        match glob(&pattern) {
-            Ok(paths) => {
-                for entry in paths.flatten() {
-                    if entry.is_file() {
-                        files.push(entry);
-                    }
+        if file_pairs.is_empty() {
+            utils::print_info("文件名未修改，无需重命名");
+            return Ok(());
+        }
+
+        self.check_conflicts(&file_pairs)?;
+        self.execute_preview(&file_pairs, &HashMap::new())?;
+        if self.config.preview {
+            return Ok(());
+        }
+
+        let answer = utils::get_user_input("确认执行以上重命名? (y/N): ")
+            .map_err(|e| HekitError::UserInput(format!("读取输入失败: {}", e)))?;
+        if !answer.eq_ignore_ascii_case("y") {
+            utils::print_info("已取消重命名");
+            return Ok(());
+        }
+
+        self.execute_batch(&file_pairs)
+    }
+
+    /// 执行映射表重命名模式
+    fn execute_map(&self, map_file: &Path, files: &[PathBuf]) -> HekitResult<()> {
+        let rows = mapping::load_mapping(
+            map_file,
+            &self.config.map_from,
+            &self.config.map_to,
+            self.config.map_header,
+        )?;
+        let result = mapping::apply_mapping(
+            files,
+            &rows,
+            self.config.map_stem,
+            self.config.case_insensitive,
+        )?;
+
+        if !result.unmatched_rows.is_empty() {
+            utils::print_warning(&format!(
+                "映射表中有 {} 行未找到对应文件:",
+                result.unmatched_rows.len()
+            ));
+            for row in &result.unmatched_rows {
+                println!("  第 {} 行: {}", row.line, row.old_name);
+            }
+        }
+        if !result.unmapped_files.is_empty() {
+            utils::print_warning(&format!(
+                "有 {} 个文件不在映射表中，将保持不变:",
+                result.unmapped_files.len()
+            ));
+            for file in &result.unmapped_files {
+                println!("  {}", file.display());
+            }
+        }
+
+        let file_pairs: Vec<(PathBuf, PathBuf)> = result
+            .pairs
+            .into_iter()
+            .filter(|(old_path, new_path)| old_path != new_path)
+            .collect();
+
+        if file_pairs.is_empty() {
+            utils::print_info("没有需要重命名的文件");
+            return Ok(());
+        }
+
+        self.check_conflicts(&file_pairs)?;
+        self.execute_preview(&file_pairs, &HashMap::new())?;
+        if self.config.preview {
+            return Ok(());
+        }
+
+        let answer = utils::get_user_input("确认执行以上重命名? (y/N): ")
+            .map_err(|e| HekitError::UserInput(format!("读取输入失败: {}", e)))?;
+        if !answer.eq_ignore_ascii_case("y") {
+            utils::print_info("已取消重命名");
+            return Ok(());
+        }
+
+        self.execute_batch(&file_pairs)
+    }
+
+    /// 执行内容哈希命名模式（内容相同的文件只重命名一个，其余按策略处理）
+    fn execute_hash(&self, algorithm: HashAlgorithm, files: &[PathBuf]) -> HekitResult<()> {
+        let files: Vec<PathBuf> = files.iter().filter(|f| f.is_file()).cloned().collect();
+        if files.is_empty() {
+            utils::print_warning("没有找到匹配的文件");
+            return Ok(());
+        }
+
+        let progress = ProgressManager::new(files.len() as u64, "计算文件哈希中...");
+        let mut hashes: HashMap<PathBuf, String> = HashMap::new();
+        let mut failed = Vec::new();
+        for file_path in &files {
+            progress.set_message(&format!("哈希: {}", file_path.display()));
+            match hashing::hash_file(file_path, algorithm) {
+                Ok(hash) => {
+                    hashes.insert(file_path.clone(), hash);
+                }
+                Err(e) => failed.push(format!("{} - {}", file_path.display(), e)),
+            }
+            progress.inc(1);
+        }
+        progress.finish_with_message(&format!("已计算 {} 个文件的哈希", hashes.len()));
+
+        if !failed.is_empty() {
+            utils::print_warning(&format!("有 {} 个文件读取失败，将保持不变:", failed.len()));
+            for message in &failed {
+                println!("  {}", message);
+            }
+        }
+
+        // 按完整哈希分组：每组保留一个文件（优先保留已按哈希命名的），其余为重复文件
+        let mut groups: HashMap<&str, Vec<&PathBuf>> = HashMap::new();
+        for file_path in &files {
+            if let Some(hash) = hashes.get(file_path) {
+                groups.entry(hash.as_str()).or_default().push(file_path);
+            }
+        }
+
+        let mut keep: HashSet<&PathBuf> = HashSet::new();
+        let mut duplicates: Vec<(PathBuf, PathBuf)> = Vec::new();
+        for group in groups.values() {
+            let kept = group
+                .iter()
+                .find(|path| {
+                    let hash = &hashes[**path];
+                    path.file_stem()
+                        .is_some_and(|stem| stem.to_string_lossy() == hash[..self.config.hash_len])
+                })
+                .unwrap_or(&group[0]);
+            keep.insert(kept);
+            for path in group.iter().filter(|path| *path != kept) {
+                duplicates.push(((*path).clone(), (*kept).clone()));
+            }
+        }
+
+        let kept_files: Vec<PathBuf> = files.iter().filter(|f| keep.contains(f)).cloned().collect();
+        let indices = numbering::assign_indices(&kept_files, self.config.number_reset);
+        let mut file_pairs = Vec::new();
+        for (file_path, index) in kept_files.iter().zip(indices) {
+            let short_hash = &hashes[file_path][..self.config.hash_len];
+            if let Some((new_path, _)) =
+                self.generate_new_filename(file_path, index, Some(short_hash))?
+            {
+                if &new_path == file_path {
+                    continue;
+                }
+                // 目标已存在且内容相同（例如之前已按哈希命名），视为重复文件
+                let same_content = new_path.is_file()
+                    && hashing::hash_file(&new_path, algorithm)
+                        .is_ok_and(|hash| hash == hashes[file_path]);
+                if same_content {
+                    duplicates.push((file_path.clone(), new_path));
+                } else {
+                    file_pairs.push((file_path.clone(), new_path));
                 }
-                files.sort();
-                Ok(files)
             }
-            Err(e) => hekit_error!(FileOperation, &format!("文件扫描失败: {}", e)),
         }
+        duplicates.sort();
+
+        if self.config.fix_ext {
+            self.report_unknown_types(&kept_files);
+        }
+
+        // 截取后的哈希相同但内容不同时，由冲突检查拦截
+        self.check_conflicts(&file_pairs)?;
+
+        if self.config.preview {
+            self.execute_preview(&file_pairs, &HashMap::new())?;
+            self.preview_duplicates(&duplicates);
+            return Ok(());
+        }
+
+        if file_pairs.is_empty() {
+            utils::print_info("没有需要重命名的文件");
+        } else {
+            self.execute_batch(&file_pairs)?;
+        }
+        self.handle_duplicates(&duplicates)
     }
 
-    // 删除 generate_new_filenames 方法（第64-72行）
+    /// 预览重复文件及其处理方式
+    fn preview_duplicates(&self, duplicates: &[(PathBuf, PathBuf)]) {
+        if duplicates.is_empty() {
+            return;
+        }
 
-    /// 为单个文件生成新文件名
-    fn generate_new_filename(&self, file_path: &Path, index: usize) -> HekitResult<PathBuf> {
-        let parent_dir = file_path.parent().unwrap_or(Path::new("."));
-        let file_stem = file_path
-            .file_stem()
-            .ok_or_else(|| HekitError::Rename("无法获取文件名".to_string()))?
-            .to_string_lossy();
-        let extension = file_path.extension().unwrap_or_default().to_string_lossy();
+        let action = match self.config.dupes {
+            DupePolicy::Skip => "保留",
+            DupePolicy::Remove => "删除",
+        };
+        utils::print_warning(&format!(
+            "发现 {} 个内容重复的文件（将{}）:",
+            duplicates.len(),
+            action
+        ));
+        for (duplicate, original) in duplicates {
+            println!(
+                "  {}  [与 {} 相同]",
+                duplicate.display(),
+                original.display()
+            );
+        }
+    }
+
+    /// 按策略处理内容重复的文件
+    fn handle_duplicates(&self, duplicates: &[(PathBuf, PathBuf)]) -> HekitResult<()> {
+        if duplicates.is_empty() {
+            return Ok(());
+        }
+
+        self.preview_duplicates(duplicates);
+        if self.config.dupes == DupePolicy::Skip {
+            return Ok(());
+        }
+
+        let answer = utils::get_user_input("确认删除以上重复文件? (y/N): ")
+            .map_err(|e| HekitError::UserInput(format!("读取输入失败: {}", e)))?;
+        if !answer.eq_ignore_ascii_case("y") {
+            utils::print_info("已保留重复文件");
+            return Ok(());
+        }
+
+        let mut error_count = 0;
+        for (duplicate, _) in duplicates {
+            match fs::remove_file(duplicate) {
+                Ok(_) => println!("✓ 已删除 {}", duplicate.display()),
+                Err(e) => {
+                    eprintln!("✗ 删除 {} 失败: {}", duplicate.display(), e);
+                    error_count += 1;
+                }
+            }
+        }
+
+        if error_count > 0 {
+            hekit_error!(Rename, "部分重复文件删除失败")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 检查重命名冲突（目标重名或目标文件已存在）
+    /// 目标是同批次中另一个将被改名的文件时不算冲突（如互换、链式改名），执行时会经临时名中转
+    fn check_conflicts(&self, file_pairs: &[(PathBuf, PathBuf)]) -> HekitResult<()> {
+        let sources: HashSet<String> = file_pairs
+            .iter()
+            .map(|(old_path, _)| self.path_key(old_path))
+            .collect();
+        let mut targets = HashSet::new();
+        let mut conflicts = Vec::new();
+
+        for (old_path, new_path) in file_pairs {
+            let key = self.path_key(new_path);
+            let case_only_change = old_path.to_string_lossy().to_lowercase()
+                == new_path.to_string_lossy().to_lowercase();
+
+            if !targets.insert(key.clone()) {
+                conflicts.push(format!("多个文件将被命名为: {}", new_path.display()));
+            } else if new_path.exists() && !case_only_change && !sources.contains(&key) {
+                conflicts.push(format!("目标已存在: {}", new_path.display()));
+            }
+        }
 
-        let mut new_name = file_stem.to_string();
+        if conflicts.is_empty() {
+            return Ok(());
+        }
 
-        if let Some(prefix) = &self.config.prefix {
-            new_name = format!("{}{}", prefix, new_name);
+        for conflict in &conflicts {
+            utils::print_error(&format!("  {}", conflict));
         }
+        hekit_error!(
+            Rename,
+            &format!("检测到 {} 处重命名冲突，已取消", conflicts.len())
+        )
+    }
 
-        if let Some(suffix) = &self.config.suffix {
-            new_name = format!("{}{}", new_name, suffix);
+    /// 比较路径用的键（不区分大小写的文件系统上统一为小写）
+    fn path_key(&self, path: &Path) -> String {
+        if self.config.case_insensitive || cfg!(windows) {
+            path.to_string_lossy().to_lowercase()
+        } else {
+            path.to_string_lossy().to_string()
         }
+    }
 
-        // 修复：改进替换功能逻辑
-        if let Some(replace_pattern) = &self.config.replace_pattern {
-            if replace_pattern.starts_with('/') && replace_pattern.contains('/') {
-                // 正则替换模式：/pattern/replacement/
-                let parts: Vec<&str> = replace_pattern.splitn(3, '/').collect();
-                if parts.len() == 3 && parts[0].is_empty() {
-                    if let Ok(regex) = Regex::new(parts[1]) {
-                        new_name = regex.replace_all(&new_name, parts[2]).to_string();
+    /// 扫描匹配的文件（文件在前，文件夹按深度从深到浅排在后面）
+    fn scan_files(&self) -> HekitResult<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let mut dirs = Vec::new();
+
+        if self.config.recursive {
+            let pattern = Pattern::new(&self.config.match_pattern)
+                .map_err(|e| HekitError::FileOperation(format!("文件扫描失败: {}", e)))?;
+            let options = MatchOptions {
+                case_sensitive: !self.config.case_insensitive,
+                ..MatchOptions::new()
+            };
+            fs::read_dir(&self.config.path).map_err(|e| {
+                HekitError::FileOperation(format!(
+                    "读取目录失败: {} - {}",
+                    self.config.path.display(),
+                    e
+                ))
+            })?;
+            let mut skipped_dirs = 0;
+            self.walk_directory(
+                &self.config.path,
+                &pattern,
+                options,
+                &mut files,
+                &mut dirs,
+                &mut skipped_dirs,
+            );
+            if skipped_dirs > 0 {
+                utils::print_warning(&format!("因无法读取跳过 {} 个目录", skipped_dirs));
+            }
+        } else {
+            let pattern = self.config.path.join(&self.config.match_pattern);
+            let pattern = pattern.to_string_lossy().to_string();
+
+            match glob(&pattern) {
+                Ok(paths) => {
+                    for entry in paths.flatten() {
+                        if entry.is_file() {
+                            files.push(entry);
+                        } else if self.config.include_dirs && entry.is_dir() {
+                            dirs.push(entry);
+                        }
                     }
                 }
-            } else if replace_pattern.contains('=') {
-                // 简单替换模式：old=new
-                let parts: Vec<&str> = replace_pattern.splitn(2, '=').collect();
-                if parts.len() == 2 {
-                    new_name = new_name.replace(parts[0], parts[1]);
+                Err(e) => return hekit_error!(FileOperation, &format!("文件扫描失败: {}", e)),
+            }
+        }
+
+        numbering::sort_files(&mut files, self.config.number_sort);
+        // 先重命名深层文件夹，保证其上级路径在重命名时仍然有效
+        dirs.sort_by(|a, b| {
+            b.components()
+                .count()
+                .cmp(&a.components().count())
+                .then_with(|| a.cmp(b))
+        });
+        files.extend(dirs);
+        Ok(files)
+    }
+
+    /// 递归遍历目录，收集名称匹配的文件和文件夹（不跟随符号链接）
+    /// 无法读取的目录给出警告并计入 skipped_dirs，不中断整个遍历
+    fn walk_directory(
+        &self,
+        dir: &Path,
+        pattern: &Pattern,
+        options: MatchOptions,
+        files: &mut Vec<PathBuf>,
+        dirs: &mut Vec<PathBuf>,
+        skipped_dirs: &mut usize,
+    ) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                utils::print_warning(&format!("跳过无法读取的目录: {} - {}", dir.display(), e));
+                *skipped_dirs += 1;
+                return;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(_) => continue,
+            };
+            let name_matches = pattern.matches_with(&entry.file_name().to_string_lossy(), options);
+
+            if file_type.is_dir() {
+                self.walk_directory(&path, pattern, options, files, dirs, skipped_dirs);
+                if self.config.include_dirs && name_matches {
+                    dirs.push(path);
                 }
-            } else {
-                // 向后兼容：只删除匹配内容
-                new_name = new_name.replace(replace_pattern, "");
+            } else if file_type.is_file() && name_matches {
+                files.push(path);
             }
         }
+    }
 
-        // 修复：序号生成逻辑
-        if self.config.number_start.is_some() {
-            let start = self.config.number_start.unwrap_or(1);
-            let current_number = start + index - 1;
-            let number_str = format!("{:03}", current_number);
-            new_name = format!("{}_{}", new_name, number_str);
+    /// 为单个文件生成新文件名（base_name 指定时代替原主文件名，如内容哈希）
+    /// 缺少模板所需的元数据时给出警告并返回 None（跳过该文件）
+    /// 同时返回逐条规则的中间结果
+    fn generate_new_filename(
+        &self,
+        file_path: &Path,
+        index: usize,
+        base_name: Option<&str>,
+    ) -> HekitResult<Option<(PathBuf, RuleSteps)>> {
+        let parent_dir = file_path.parent().unwrap_or(Path::new("."));
+        // 文件夹名称整体作为主名处理，不拆分扩展名
+        let is_dir = file_path.is_dir();
+        let file_stem = if is_dir {
+            file_path.file_name()
+        } else {
+            file_path.file_stem()
         }
+        .ok_or_else(|| HekitError::Rename("无法获取文件名".to_string()))?
+        .to_string_lossy();
+        let extension = if is_dir {
+            Default::default()
+        } else {
+            file_path.extension().unwrap_or_default().to_string_lossy()
+        };
 
-        let new_path = if let Some(new_ext) = &self.config.extension {
-            if new_ext.is_empty() {
-                parent_dir.join(new_name)
-            } else {
-                parent_dir.join(format!("{}.{}", new_name, new_ext))
+        let mut new_name = match (base_name, &self.config.template) {
+            (Some(base), _) => base.to_string(),
+            (None, Some(template)) => {
+                match template.render(&self.collect_metadata(file_path, template)) {
+                    Ok(name) => name,
+                    Err(missing) => {
+                        utils::print_warning(&format!(
+                            "跳过 {}: 缺少{} {}",
+                            file_path.display(),
+                            self.missing_source_names(&missing),
+                            missing.join(", ")
+                        ));
+                        return Ok(None);
+                    }
+                }
+            }
+            (None, None) => file_stem.to_string(),
+        };
+
+        // 按顺序应用规则流水线（文件夹不修改扩展名）
+        let mut new_ext = extension.to_string();
+        let mut steps = Vec::new();
+        for rule in &self.config.rules {
+            if is_dir && rule.is_ext() {
+                continue;
             }
-        } else if extension.is_empty() {
+            rule.apply(&mut new_name, &mut new_ext, index, &self.config);
+            let current = if new_ext.is_empty() {
+                new_name.clone()
+            } else {
+                format!("{}.{}", new_name, new_ext)
+            };
+            steps.push((rule.text().to_string(), current));
+        }
+
+        // 规则结果不能含路径分隔符，否则重命名会把文件移到其他目录
+        if new_name.chars().any(std::path::is_separator) {
+            utils::print_warning(&format!(
+                "跳过 {}: 新文件名包含路径分隔符: {}",
+                file_path.display(),
+                new_name
+            ));
+            return Ok(None);
+        }
+
+        // 按文件内容修正扩展名（无法识别或已相符时保持原扩展名）
+        let detected_ext = if self.config.fix_ext && !is_dir {
+            magic::detect_file(file_path)
+                .filter(|kind| !kind.matches_extension(&extension))
+                .map(|kind| kind.ext)
+        } else {
+            None
+        };
+
+        let new_path = if is_dir {
+            parent_dir.join(new_name)
+        } else if let Some(detected) = detected_ext {
+            parent_dir.join(format!("{}.{}", new_name, detected))
+        } else if new_ext.is_empty() {
             parent_dir.join(new_name)
         } else {
-            parent_dir.join(format!("{}.{}", new_name, extension))
+            parent_dir.join(format!("{}.{}", new_name, new_ext))
         };
 
-        Ok(new_path)
+        Ok(Some((new_path, steps)))
+    }
+
+    /// 收集模板需要的元数据（只读取模板用到的来源）
+    fn collect_metadata(&self, file_path: &Path, template: &Template) -> HashMap<String, String> {
+        let mut values = metadata::builtin_values(file_path);
+        let tokens: Vec<&str> = template.tokens().collect();
+
+        for source in &self.sources {
+            let needed = source.tokens().iter().any(|t| tokens.contains(t));
+            if needed && source.supports(file_path) {
+                for (key, value) in source.read(file_path) {
+                    values.entry(key).or_insert(value);
+                }
+            }
+        }
+
+        values
+    }
+
+    /// 缺失标记所属的来源名称（如 "音频标签"），不属于任何来源时为 "标签"
+    fn missing_source_names(&self, missing: &[String]) -> String {
+        let mut names: Vec<&str> = Vec::new();
+        for token in missing {
+            let source = self
+                .sources
+                .iter()
+                .find(|source| source.tokens().contains(&token.as_str()));
+            if let Some(source) = source {
+                if !names.contains(&source.name()) {
+                    names.push(source.name());
+                }
+            }
+        }
+        if names.is_empty() {
+            "标签".to_string()
+        } else {
+            names.join("/")
+        }
+    }
+
+    /// 报告无法根据内容识别类型的文件
+    fn report_unknown_types(&self, files: &[PathBuf]) {
+        let unknown: Vec<&PathBuf> = files
+            .iter()
+            .filter(|path| path.is_file() && magic::detect_file(path).is_none())
+            .collect();
+        if unknown.is_empty() {
+            return;
+        }
+
+        utils::print_warning(&format!(
+            "有 {} 个文件无法识别内容类型，将保持原扩展名:",
+            unknown.len()
+        ));
+        for path in unknown {
+            println!("  {}", path.display());
+        }
     }
 
     /// 执行预览模式
-    fn execute_preview(&self, file_pairs: &[(PathBuf, PathBuf)]) -> HekitResult<()> {
+    fn execute_preview(
+        &self,
+        file_pairs: &[(PathBuf, PathBuf)],
+        steps: &HashMap<PathBuf, RuleSteps>,
+    ) -> HekitResult<()> {
         utils::print_info("预览结果:");
 
+        let mut dir_count = 0;
         for (old_path, new_path) in file_pairs {
-            println!("  {} → {}", old_path.display(), new_path.display());
+            if old_path.is_dir() {
+                dir_count += 1;
+                println!("  [目录] {} → {}", old_path.display(), new_path.display());
+            } else if self.config.fix_ext && old_path.extension() != new_path.extension() {
+                let mime = magic::detect_file(old_path)
+                    .map(|kind| kind.mime)
+                    .unwrap_or("未知");
+                println!(
+                    "  {} → {}  [扩展名与内容不符: {}]",
+                    old_path.display(),
+                    new_path.display(),
+                    mime
+                );
+            } else {
+                println!("  {} → {}", old_path.display(), new_path.display());
+            }
+            if let Some(steps) = steps.get(old_path) {
+                for (rule, result) in steps {
+                    println!("      {}  ⇒ {}", rule, result);
+                }
+            }
+        }
+        if dir_count > 0 {
+            println!(
+                "总计: {} 个文件, {} 个文件夹",
+                file_pairs.len() - dir_count,
+                dir_count
+            );
+        } else {
+            println!("总计: {} 个文件", file_pairs.len());
         }
-        println!("总计: {} 个文件", file_pairs.len());
         Ok(())
     }
 
     // 删除 execute_with_backup 方法（从第147行到第180行）
 
     /// 执行批量重命名
+    /// 目标恰好是尚未处理的另一个源文件时，先把该源文件移到临时名让出位置，轮到它时再从临时名改名
     fn execute_batch(&self, file_pairs: &[(PathBuf, PathBuf)]) -> HekitResult<()> {
         let progress = ProgressManager::new(file_pairs.len() as u64, "批量重命名中...");
         let mut success_count = 0;
         let mut error_count = 0;
 
-        for (_i, (old_path, new_path)) in file_pairs.iter().enumerate() {
+        // 尚未处理的源文件，以及被临时移走的源文件的当前位置
+        let mut pending: HashMap<String, usize> = file_pairs
+            .iter()
+            .enumerate()
+            .map(|(i, (old_path, _))| (self.path_key(old_path), i))
+            .collect();
+        let mut parked: HashMap<usize, PathBuf> = HashMap::new();
+
+        for (i, (old_path, new_path)) in file_pairs.iter().enumerate() {
             progress.set_message(&format!("重命名: {}", old_path.display()));
+            pending.remove(&self.path_key(old_path));
+            let current = parked.remove(&i).unwrap_or_else(|| old_path.clone());
 
-            match self.rename_file(old_path, new_path) {
+            let result = self
+                .park_blocker(new_path, &pending, &mut parked)
+                .and_then(|_| self.rename_file(&current, new_path));
+            match result {
                 Ok(_) => {
                     println!("✓ {} → {}", old_path.display(), new_path.display());
                     success_count += 1;
@@ -156,6 +704,12 @@ impl BatchRenameCore {
                 Err(e) => {
                     eprintln!("✗ {} 失败: {}", old_path.display(), e);
                     error_count += 1;
+                    // 已被临时移走的文件尽量恢复原名
+                    if &current != old_path
+                        && (old_path.exists() || fs::rename(&current, old_path).is_err())
+                    {
+                        eprintln!("  文件暂存在: {}", current.display());
+                    }
                 }
             }
 
@@ -174,6 +728,32 @@ impl BatchRenameCore {
         }
     }
 
+    /// 目标路径被尚未处理的源文件占用时，把它移到同目录下的临时名
+    fn park_blocker(
+        &self,
+        new_path: &Path,
+        pending: &HashMap<String, usize>,
+        parked: &mut HashMap<usize, PathBuf>,
+    ) -> HekitResult<()> {
+        let Some(&index) = pending.get(&self.path_key(new_path)) else {
+            return Ok(());
+        };
+        if parked.contains_key(&index) {
+            return Ok(());
+        }
+
+        let name = new_path.file_name().unwrap_or_default().to_string_lossy();
+        let temp_path = (0..)
+            .map(|n| new_path.with_file_name(format!(".{}.hekit-rename-{}", name, n)))
+            .find(|path| !path.exists())
+            .unwrap_or_default();
+        fs::rename(new_path, &temp_path).map_err(|e| {
+            HekitError::Rename(format!("暂时移开 {} 失败: {}", new_path.display(), e))
+        })?;
+        parked.insert(index, temp_path);
+        Ok(())
+    }
+
     /// 执行单个文件重命名
     fn rename_file(&self, old_path: &Path, new_path: &Path) -> HekitResult<()> {
         if old_path == new_path {
@@ -190,13 +770,19 @@ impl BatchRenameCore {
 
             while new_path_with_counter.exists() {
                 let stem = new_path.file_stem().unwrap_or_default();
-                let ext = new_path.extension().unwrap_or_default();
-                new_path_with_counter = new_path.with_file_name(format!(
-                    "{}_{}.{}",
-                    stem.to_string_lossy(),
-                    counter,
-                    ext.to_string_lossy()
-                ));
+                new_path_with_counter = match new_path.extension() {
+                    Some(ext) if !old_path.is_dir() => new_path.with_file_name(format!(
+                        "{}_{}.{}",
+                        stem.to_string_lossy(),
+                        counter,
+                        ext.to_string_lossy()
+                    )),
+                    _ => new_path.with_file_name(format!(
+                        "{}_{}",
+                        new_path.file_name().unwrap_or_default().to_string_lossy(),
+                        counter
+                    )),
+                };
                 counter += 1;
             }
 
@@ -212,3 +798,81 @@ impl BatchRenameCore {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("hekit_rename_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn core_for(dir: &Path) -> BatchRenameCore {
+        let matches = BatchRenameConfig::build_clap_command()
+            .try_get_matches_from(["rename", "-d", dir.to_str().unwrap(), "-m", "*", "-p", "x_"])
+            .unwrap();
+        BatchRenameCore::new(BatchRenameConfig::from_matches(&matches).unwrap())
+    }
+
+    #[test]
+    fn swap_is_not_a_conflict_and_runs_through_temp_names() {
+        let dir = test_dir("swap");
+        fs::write(dir.join("a.txt"), "A").unwrap();
+        fs::write(dir.join("b.txt"), "B").unwrap();
+        let core = core_for(&dir);
+        let pairs = vec![
+            (dir.join("a.txt"), dir.join("b.txt")),
+            (dir.join("b.txt"), dir.join("a.txt")),
+        ];
+
+        core.check_conflicts(&pairs).unwrap();
+        core.execute_batch(&pairs).unwrap();
+
+        assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "B");
+        assert_eq!(fs::read_to_string(dir.join("b.txt")).unwrap(), "A");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn chain_renames_each_file_once() {
+        let dir = test_dir("chain");
+        fs::write(dir.join("a.txt"), "A").unwrap();
+        fs::write(dir.join("b.txt"), "B").unwrap();
+        let core = core_for(&dir);
+        let pairs = vec![
+            (dir.join("a.txt"), dir.join("b.txt")),
+            (dir.join("b.txt"), dir.join("c.txt")),
+        ];
+
+        core.check_conflicts(&pairs).unwrap();
+        core.execute_batch(&pairs).unwrap();
+
+        assert!(!dir.join("a.txt").exists());
+        assert_eq!(fs::read_to_string(dir.join("b.txt")).unwrap(), "A");
+        assert_eq!(fs::read_to_string(dir.join("c.txt")).unwrap(), "B");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn existing_target_outside_batch_is_a_conflict() {
+        let dir = test_dir("conflict");
+        fs::write(dir.join("a.txt"), "A").unwrap();
+        fs::write(dir.join("b.txt"), "B").unwrap();
+        let core = core_for(&dir);
+
+        let pairs = vec![(dir.join("a.txt"), dir.join("b.txt"))];
+        assert!(core.check_conflicts(&pairs).is_err());
+
+        let duplicate_targets = vec![
+            (dir.join("a.txt"), dir.join("c.txt")),
+            (dir.join("b.txt"), dir.join("c.txt")),
+        ];
+        assert!(core.check_conflicts(&duplicate_targets).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
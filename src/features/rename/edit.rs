@@ -0,0 +1,171 @@
+use crate::error::{HekitError, HekitResult};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 在外部编辑器中逐行编辑文件名，返回 (原路径, 新路径) 列表
+pub fn edit_in_editor(files: &[PathBuf]) -> HekitResult<Vec<(PathBuf, PathBuf)>> {
+    let names: Vec<String> = files
+        .iter()
+        .map(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .ok_or_else(|| HekitError::Rename(format!("无法获取文件名: {}", path.display())))
+        })
+        .collect::<HekitResult<_>>()?;
+
+    let temp_path = create_temp_file(&(names.join("\n") + "\n"))
+        .map_err(|e| HekitError::FileOperation(format!("创建临时文件失败: {}", e)))?;
+
+    let result = open_editor(&temp_path).and_then(|_| {
+        fs::read_to_string(&temp_path)
+            .map_err(|e| HekitError::FileOperation(format!("读取临时文件失败: {}", e)))
+    });
+    let _ = fs::remove_file(&temp_path);
+    let content = result?;
+
+    let new_names = parse_edited_names(&content);
+    if new_names.len() != files.len() {
+        return Err(HekitError::Rename(format!(
+            "编辑后的行数 ({}) 与文件数量 ({}) 不一致，请勿增删行",
+            new_names.len(),
+            files.len()
+        )));
+    }
+
+    files
+        .iter()
+        .zip(new_names)
+        .map(|(old_path, new_name)| {
+            if new_name.trim().is_empty() {
+                return Err(HekitError::Rename(format!(
+                    "新文件名不能为空: {}",
+                    old_path.display()
+                )));
+            }
+            if new_name.contains('/') || new_name.contains('\\') {
+                return Err(HekitError::Rename(format!(
+                    "新文件名不能包含路径分隔符: {}",
+                    new_name
+                )));
+            }
+            Ok((old_path.clone(), old_path.with_file_name(new_name)))
+        })
+        .collect()
+}
+
+/// 在临时目录中新建一个唯一命名的文件并写入内容
+/// 使用 create_new 独占创建，不会跟随已存在的同名文件或符号链接
+fn create_temp_file(content: &str) -> io::Result<PathBuf> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+
+    for attempt in 0..100 {
+        let path = env::temp_dir().join(format!(
+            "hekit_rename_{}_{}_{}.txt",
+            process::id(),
+            nanos,
+            attempt
+        ));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(content.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        "无法生成唯一的临时文件名",
+    ))
+}
+
+/// 解析编辑后的内容（忽略BOM、行尾回车和末尾空行）
+fn parse_edited_names(content: &str) -> Vec<String> {
+    let content = content.trim_start_matches('\u{feff}');
+    let mut names: Vec<String> = content
+        .lines()
+        .map(|line| line.trim_end_matches('\r').to_string())
+        .collect();
+    while names.last().is_some_and(|name| name.trim().is_empty()) {
+        names.pop();
+    }
+    names
+}
+
+/// 打开编辑器并等待其退出（优先使用 $VISUAL / $EDITOR）
+fn open_editor(path: &Path) -> HekitResult<()> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(windows) {
+                "notepad".to_string()
+            } else {
+                "vi".to_string()
+            }
+        });
+
+    let mut parts = shlex::split(&editor)
+        .filter(|parts| !parts.is_empty())
+        .ok_or_else(|| HekitError::Configuration(format!("无法解析编辑器命令: {}", editor)))?;
+    let program = parts.remove(0);
+
+    let status = Command::new(&program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| HekitError::System(format!("启动编辑器 {} 失败: {}", program, e)))?;
+
+    if !status.success() {
+        return Err(HekitError::System(format!(
+            "编辑器异常退出: {}，已取消重命名",
+            status
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_edited_names_ignores_bom_crlf_and_trailing_blank_lines() {
+        let names = parse_edited_names("\u{feff}a.txt\r\nb c.txt\r\n\r\n\n");
+        assert_eq!(names, vec!["a.txt", "b c.txt"]);
+    }
+
+    #[test]
+    fn parse_edited_names_keeps_inner_blank_lines() {
+        assert_eq!(parse_edited_names("a\n\nb\n"), vec!["a", "", "b"]);
+    }
+
+    #[test]
+    fn temp_files_are_unique_and_hold_content() {
+        let first = create_temp_file("one").unwrap();
+        let second = create_temp_file("two").unwrap();
+        assert_ne!(first, second);
+        assert_eq!(fs::read_to_string(&first).unwrap(), "one");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "two");
+        fs::remove_file(first).unwrap();
+        fs::remove_file(second).unwrap();
+    }
+}
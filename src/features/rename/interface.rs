@@ -3,6 +3,8 @@ use crate::error::HekitResult;
 use crate::features::common::ToolInterface;
 use crate::features::rename::config::BatchRenameConfig;
 use crate::features::rename::core::BatchRenameCore;
+use crate::features::rename::pipeline;
+use crate::utils;
 
 /// 批量重命名工具接口
 pub struct RenameTool;
@@ -15,21 +17,62 @@ impl ToolInterface for RenameTool {
 
     /// 显示使用说明
     fn show_usage() {
-        use crate::utils;
-
         utils::print_compact_tool_title("批量重命名");
         println!();
 
         println!("参数说明:");
-        println!("  -d, --dir <路径>        指定要重命名的目录");
-        println!("  -p, --pattern <模式>    文件名匹配模式");
-        println!("  -r, --replace <替换>    替换字符串");
-        println!("  -i, --interactive       交互式重命名");
+        println!("  -d, --path <路径>       目标文件夹（默认当前目录）");
+        println!("  -m, --match <模式>      选文件（通配符 *）");
+        println!("  -t, --template <模板>   按模板生成文件名，可用标记:");
+        println!(
+            "                          {{name}} {{ext}} {{parent}} {{artist}} {{album}} {{title}}"
+        );
+        println!("                          {{track}} {{year}} {{genre}} {{albumartist}} {{disc}}，{{track:02}} 补零");
+        println!("  --rule <规则>           按顺序应用规则，可重复: replace: old=new | prefix: x_");
+        println!("                          suffix: _y | case: lower/upper/title | trim | number: 1 | ext: txt");
+        println!("  --preset <名称>         使用已保存的规则预设");
+        println!("  --save-preset <名称>    将本次规则保存为预设");
+        println!("  --list-presets          列出已保存的预设");
+        println!("  -p, --prefix <前缀>     加前缀");
+        println!("  -s, --suffix <后缀>     加后缀（扩展名前）");
+        println!("  -r, --replace <规则>    替换文字（old=new 或 s/正则/替换/gi，可重复）");
+        println!("                          替换中可用 & \\1 $1 ${{名称}} 引用分组，\\U \\L \\E \\u \\l 转换大小写");
+        println!("  -n, --number <起始>     加序号（补零位数见 --number-width）");
+        println!("  --number-sort <方式>    序号排序（name, natural, mtime, size, exif）");
+        println!("  --number-width <位数>   序号补零位数（默认3）");
+        println!("  --number-step <步长>    序号步长（默认1）");
+        println!("  --number-sep <分隔符>   序号分隔符（默认_）");
+        println!("  --number-pos <位置>     序号位置（prefix, suffix, replace）");
+        println!("  --number-reset          每个目录单独计数");
+        println!("  -e, --ext <扩展名>      改扩展名（空值删除）");
+        println!("  --fix-ext               按文件内容识别类型并修正扩展名");
+        println!("  -v, --preview           预览效果（不真改名）");
+        println!("  -c, --case              不区分大小写匹配");
+        println!("  --recursive             递归处理子目录");
+        println!("  --include-dirs          同时重命名匹配的文件夹");
+        println!("  --edit                  在编辑器中逐行修改文件名（$EDITOR）");
+        println!("  --map <文件>            按CSV/TSV映射表重命名（原名→新名）");
+        println!("  --map-from/--map-to     映射列（序号或表头名，默认1和2）");
+        println!("  --map-header            映射文件首行为表头");
+        println!("  --map-stem              只按主文件名匹配（保留原扩展名）");
+        println!("  --hash <算法>           按文件内容哈希命名（sha256, blake3）");
+        println!("  --hash-len <长度>       哈希截取长度（默认8）");
+        println!("  --dupes <方式>          内容重复文件的处理（skip 保留, remove 删除）");
         println!();
 
         println!("实用示例:");
-        println!("  重命名当前目录文件: --dir . --pattern \"*.txt\" --replace \"new_\"");
-        println!("  交互式重命名: --dir /path/to/dir --interactive");
+        println!("  加前缀: -m \"*.txt\" -p \"new_\"");
+        println!("  规则流水线: -m \"*\" --rule \"replace: a=b\" --rule \"prefix: x_\" --rule \"case: lower\" -v");
+        println!("  保存并复用预设: -m \"*\" --rule \"case: lower\" --rule \"replace: -=_\" --save-preset tidy -v");
+        println!("  使用预设: -m \"*\" --preset tidy");
+        println!("  按音频标签改名: -m \"*.mp3\" -t \"{{artist}} - {{album}} - {{track:02}} - {{title}}\"");
+        println!("  正则改名: -m \"*.jpg\" -r \"s/(\\d+)-(\\d+)/\\2_\\1/g\"");
+        println!("  按拍摄时间编号: -m \"*.jpg\" -n 1 --number-sort exif --number-pos replace");
+        println!("  修正错误扩展名: -m \"*\" --fix-ext -v");
+        println!("  编辑器改名: -m \"*.jpg\" --edit");
+        println!("  按映射表改名: --map names.csv --map-header -v");
+        println!("  按内容哈希命名: -m \"*.jpg\" --hash blake3 --dupes remove -v");
+        println!("  递归替换文件和文件夹名: -m \"*\" -r \"old=new\" --recursive --include-dirs -v");
 
         utils::print_compact_separator();
     }
@@ -52,10 +95,42 @@ impl ToolInterface for RenameTool {
             return Ok(());
         }
 
-        let config = BatchRenameConfig::from_matches(&matches)?;
+        if matches.get_flag("list-presets") {
+            return show_presets();
+        }
+
+        let config = BatchRenameConfig::from_matches(&matches)
+            .map_err(|e| crate::error::HekitError::UserInput(format!("配置错误: {}", e)))?;
         let core = BatchRenameCore::new(config);
-        core.execute()
+        core.execute()?;
+
+        // 执行（或预览）成功后再保存预设
+        if let Some(name) = &core.config.save_preset {
+            let path = pipeline::save_preset(name, &core.config.rules)
+                .map_err(|e| crate::error::HekitError::UserInput(format!("配置错误: {}", e)))?;
+            utils::print_success(&format!("已保存预设 {}: {}", name, path.display()));
+        }
+        Ok(())
+    }
+}
+
+/// 显示已保存的规则预设
+fn show_presets() -> HekitResult<()> {
+    let presets = pipeline::list_presets()
+        .map_err(|e| crate::error::HekitError::UserInput(format!("配置错误: {}", e)))?;
+    if presets.is_empty() {
+        utils::print_info("还没有保存的预设（使用 --save-preset <名称> 保存）");
+        return Ok(());
+    }
+
+    utils::print_info("已保存的预设:");
+    for (name, rules) in presets {
+        println!("  {}", name);
+        for rule in rules {
+            println!("      {}", rule);
+        }
     }
+    Ok(())
 }
 
 /// 运行交互式界面
@@ -0,0 +1,303 @@
+use crate::error::{HekitError, HekitResult};
+use crate::features::encoding;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 映射文件中的列选择（序号从1开始，或按表头名称）
+#[derive(Debug, Clone)]
+pub enum MapColumn {
+    Index(usize),
+    Header(String),
+}
+
+impl MapColumn {
+    /// 解析列参数：纯数字视为列序号，否则视为表头名称
+    pub fn parse(value: &str) -> Self {
+        match value.trim().parse::<usize>() {
+            Ok(index) => MapColumn::Index(index),
+            Err(_) => MapColumn::Header(value.trim().to_string()),
+        }
+    }
+}
+
+/// 映射表中的一行（保留行号便于报告）
+#[derive(Debug, Clone)]
+pub struct MapRow {
+    pub line: usize,
+    pub old_name: String,
+    pub new_name: String,
+}
+
+/// 映射匹配结果
+#[derive(Debug, Default)]
+pub struct MapResult {
+    pub pairs: Vec<(PathBuf, PathBuf)>,
+    pub unmatched_rows: Vec<MapRow>,
+    pub unmapped_files: Vec<PathBuf>,
+}
+
+/// 读取CSV/TSV映射文件（.tsv 或含制表符的文件按TSV解析）
+/// 自动识别编码（UTF-8/UTF-16/GBK），兼容 Excel 导出的非 UTF-8 文件
+pub fn load_mapping(
+    path: &Path,
+    from: &MapColumn,
+    to: &MapColumn,
+    has_header: bool,
+) -> HekitResult<Vec<MapRow>> {
+    let bytes = fs::read(path)
+        .map_err(|e| HekitError::FileOperation(format!("读取映射文件失败: {}", e)))?;
+    let (content, _) = encoding::decode(&bytes).ok_or_else(|| {
+        HekitError::Configuration(format!("映射文件不是文本文件: {}", path.display()))
+    })?;
+    let content = content.trim_start_matches('\u{feff}');
+
+    let is_tsv = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("tsv"))
+        || content
+            .lines()
+            .next()
+            .is_some_and(|line| line.contains('\t'));
+    let delimiter = if is_tsv { '\t' } else { ',' };
+
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    // 按表头名称选列时必须有表头
+    let uses_header =
+        has_header || matches!(from, MapColumn::Header(_)) || matches!(to, MapColumn::Header(_));
+    let headers = if uses_header {
+        lines.next().map(|(_, line)| split_record(line, delimiter))
+    } else {
+        None
+    };
+
+    let from_index = resolve_column(from, headers.as_deref())?;
+    let to_index = resolve_column(to, headers.as_deref())?;
+
+    let mut rows = Vec::new();
+    for (line_index, line) in lines {
+        let fields = split_record(line, delimiter);
+        let (Some(old_name), Some(new_name)) = (fields.get(from_index), fields.get(to_index))
+        else {
+            return Err(HekitError::Configuration(format!(
+                "映射文件第 {} 行列数不足",
+                line_index + 1
+            )));
+        };
+        if old_name.is_empty() || new_name.is_empty() {
+            return Err(HekitError::Configuration(format!(
+                "映射文件第 {} 行存在空的文件名",
+                line_index + 1
+            )));
+        }
+        if new_name.contains('/') || new_name.contains('\\') {
+            return Err(HekitError::Configuration(format!(
+                "映射文件第 {} 行的新文件名不能包含路径分隔符: {}",
+                line_index + 1,
+                new_name
+            )));
+        }
+        rows.push(MapRow {
+            line: line_index + 1,
+            old_name: old_name.clone(),
+            new_name: new_name.clone(),
+        });
+    }
+
+    Ok(rows)
+}
+
+/// 将映射表应用到扫描到的文件上
+pub fn apply_mapping(
+    files: &[PathBuf],
+    rows: &[MapRow],
+    by_stem: bool,
+    case_insensitive: bool,
+) -> HekitResult<MapResult> {
+    let normalize = |name: &str| {
+        if case_insensitive {
+            name.to_lowercase()
+        } else {
+            name.to_string()
+        }
+    };
+
+    let mut lookup: HashMap<String, usize> = HashMap::new();
+    for (index, row) in rows.iter().enumerate() {
+        if let Some(previous) = lookup.insert(normalize(&row.old_name), index) {
+            return Err(HekitError::Configuration(format!(
+                "映射文件第 {} 行与第 {} 行的原文件名重复: {}",
+                rows[previous].line, row.line, row.old_name
+            )));
+        }
+    }
+
+    let mut result = MapResult::default();
+    let mut used = vec![false; rows.len()];
+
+    for file in files {
+        let key = if by_stem {
+            file.file_stem()
+        } else {
+            file.file_name()
+        }
+        .map(|name| normalize(&name.to_string_lossy()));
+
+        match key.and_then(|key| lookup.get(&key).copied()) {
+            Some(index) => {
+                used[index] = true;
+                let new_name = &rows[index].new_name;
+                let new_path = match (by_stem, file.extension()) {
+                    (true, Some(ext)) => {
+                        file.with_file_name(format!("{}.{}", new_name, ext.to_string_lossy()))
+                    }
+                    _ => file.with_file_name(new_name),
+                };
+                result.pairs.push((file.clone(), new_path));
+            }
+            None => result.unmapped_files.push(file.clone()),
+        }
+    }
+
+    result.unmatched_rows = rows
+        .iter()
+        .zip(used)
+        .filter(|(_, used)| !used)
+        .map(|(row, _)| row.clone())
+        .collect();
+
+    Ok(result)
+}
+
+/// 根据表头确定列序号（返回从0开始的下标）
+fn resolve_column(column: &MapColumn, headers: Option<&[String]>) -> HekitResult<usize> {
+    match column {
+        MapColumn::Index(0) => Err(HekitError::Configuration("映射列序号从1开始".to_string())),
+        MapColumn::Index(index) => Ok(index - 1),
+        MapColumn::Header(name) => headers
+            .and_then(|headers| headers.iter().position(|header| header == name))
+            .ok_or_else(|| HekitError::Configuration(format!("映射文件中找不到列: {}", name))),
+    }
+}
+
+/// 拆分一行记录，支持双引号包裹和 "" 转义
+fn split_record(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => {
+                fields.push(current.trim().to_string());
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    fields.push(current.trim().to_string());
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_mapping(name: &str, bytes: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("hekit_map_{}_{}", std::process::id(), name));
+        fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn split_record_handles_quotes_and_escaped_quotes() {
+        assert_eq!(
+            split_record("\"a, b.txt\",\"say \"\"hi\"\".txt\"\r", ','),
+            vec!["a, b.txt", "say \"hi\".txt"]
+        );
+        assert_eq!(split_record("a.txt\tb.txt", '\t'), vec!["a.txt", "b.txt"]);
+    }
+
+    #[test]
+    fn load_mapping_reads_gbk_csv_with_header() {
+        let (bytes, _, _) = encoding_rs::GBK.encode("原名,新名\r\n照片1.jpg,海边.jpg\r\n");
+        let path = write_mapping("gbk.csv", &bytes);
+
+        let rows = load_mapping(
+            &path,
+            &MapColumn::parse("原名"),
+            &MapColumn::parse("新名"),
+            false,
+        )
+        .unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(rows[0].old_name, "照片1.jpg");
+        assert_eq!(rows[0].new_name, "海边.jpg");
+    }
+
+    #[test]
+    fn load_mapping_detects_tsv_and_rejects_separators() {
+        let path = write_mapping("tabs.txt", "a.txt\tb.txt\n\nc.txt\td.txt\n".as_bytes());
+        let rows = load_mapping(&path, &MapColumn::Index(1), &MapColumn::Index(2), false).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].line, 3);
+
+        let path = write_mapping("slash.csv", b"a.txt,sub/b.txt\n");
+        let result = load_mapping(&path, &MapColumn::Index(1), &MapColumn::Index(2), false);
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn apply_mapping_by_stem_keeps_extension_and_reports_leftovers() {
+        let rows = vec![
+            MapRow {
+                line: 1,
+                old_name: "IMG_1".to_string(),
+                new_name: "beach".to_string(),
+            },
+            MapRow {
+                line: 2,
+                old_name: "missing".to_string(),
+                new_name: "x".to_string(),
+            },
+        ];
+        let files = vec![PathBuf::from("/p/img_1.JPG"), PathBuf::from("/p/other.jpg")];
+
+        let result = apply_mapping(&files, &rows, true, true).unwrap();
+
+        assert_eq!(
+            result.pairs,
+            vec![(PathBuf::from("/p/img_1.JPG"), PathBuf::from("/p/beach.JPG"))]
+        );
+        assert_eq!(result.unmapped_files, vec![PathBuf::from("/p/other.jpg")]);
+        assert_eq!(result.unmatched_rows.len(), 1);
+        assert_eq!(result.unmatched_rows[0].line, 2);
+    }
+
+    #[test]
+    fn apply_mapping_rejects_duplicate_source_names() {
+        let row = MapRow {
+            line: 1,
+            old_name: "a.txt".to_string(),
+            new_name: "b.txt".to_string(),
+        };
+        let rows = vec![row.clone(), MapRow { line: 2, ..row }];
+        assert!(apply_mapping(&[], &rows, false, false).is_err());
+    }
+}
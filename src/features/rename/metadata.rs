@@ -0,0 +1,240 @@
+use crate::features::rename::audio::AudioTagSource;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 元数据来源：为重命名模板提供标记值
+pub trait MetadataSource {
+    /// 来源名称（用于提示信息）
+    fn name(&self) -> &'static str;
+
+    /// 该来源提供的标记名称
+    fn tokens(&self) -> &'static [&'static str];
+
+    /// 是否支持读取该文件
+    fn supports(&self, path: &Path) -> bool;
+
+    /// 读取文件元数据，返回 标记 → 值（读取失败或缺失的标记不返回）
+    fn read(&self, path: &Path) -> HashMap<String, String>;
+}
+
+/// 默认启用的元数据来源
+pub fn default_sources() -> Vec<Box<dyn MetadataSource>> {
+    vec![Box::new(AudioTagSource)]
+}
+
+/// 内置标记：原文件名（不含扩展名）、扩展名、所在文件夹名
+pub const BUILTIN_TOKENS: [&str; 3] = ["name", "ext", "parent"];
+
+/// 读取内置标记的值
+pub fn builtin_values(path: &Path) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    if let Some(stem) = path.file_stem() {
+        values.insert("name".to_string(), stem.to_string_lossy().to_string());
+    }
+    if let Some(ext) = path.extension() {
+        values.insert("ext".to_string(), ext.to_string_lossy().to_string());
+    }
+    if let Some(parent) = path.parent().and_then(|p| p.file_name()) {
+        values.insert("parent".to_string(), parent.to_string_lossy().to_string());
+    }
+    values
+}
+
+/// 模板组成部分
+#[derive(Debug, Clone)]
+enum TemplatePart {
+    Text(String),
+    Token { name: String, width: Option<usize> },
+}
+
+/// 重命名模板，如 `{artist} - {album} - {track:02} - {title}`
+#[derive(Debug, Clone)]
+pub struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+impl Template {
+    /// 解析模板并检查标记是否受支持
+    pub fn parse(text: &str, sources: &[Box<dyn MetadataSource>]) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = text.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '{' {
+                if c == '}' {
+                    return Err(anyhow!("模板中存在多余的右括号: {}", text));
+                }
+                literal.push(c);
+                continue;
+            }
+
+            let mut token = String::new();
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(ch) => token.push(ch),
+                    None => return Err(anyhow!("模板中的标记缺少右括号: {}", text)),
+                }
+            }
+
+            let (name, width) = match token.split_once(':') {
+                Some((name, width)) => {
+                    let width = width
+                        .parse::<usize>()
+                        .map_err(|_| anyhow!("标记宽度必须是数字: {{{}}}", token))?;
+                    (name.trim().to_lowercase(), Some(width))
+                }
+                None => (token.trim().to_lowercase(), None),
+            };
+
+            let known = BUILTIN_TOKENS.contains(&name.as_str())
+                || sources.iter().any(|s| s.tokens().contains(&name.as_str()));
+            if !known {
+                return Err(anyhow!("不支持的模板标记: {{{}}}", name));
+            }
+
+            if !literal.is_empty() {
+                parts.push(TemplatePart::Text(std::mem::take(&mut literal)));
+            }
+            parts.push(TemplatePart::Token { name, width });
+        }
+
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Text(literal));
+        }
+        if parts.is_empty() {
+            return Err(anyhow!("模板不能为空"));
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// 模板中使用的标记
+    pub fn tokens(&self) -> impl Iterator<Item = &str> {
+        self.parts.iter().filter_map(|part| match part {
+            TemplatePart::Token { name, .. } => Some(name.as_str()),
+            TemplatePart::Text(_) => None,
+        })
+    }
+
+    /// 根据标记值生成文件名，缺少标记时返回缺失的标记列表
+    pub fn render(&self, values: &HashMap<String, String>) -> Result<String, Vec<String>> {
+        let mut output = String::new();
+        let mut missing = Vec::new();
+
+        for part in &self.parts {
+            match part {
+                TemplatePart::Text(text) => output.push_str(text),
+                TemplatePart::Token { name, width } => {
+                    match values.get(name).filter(|v| !v.trim().is_empty()) {
+                        Some(value) => {
+                            let value = sanitize_file_name(value.trim());
+                            match (width, value.parse::<u64>()) {
+                                (Some(width), Ok(number)) => {
+                                    output.push_str(&format!("{:0width$}", number, width = width))
+                                }
+                                _ => output.push_str(&value),
+                            }
+                        }
+                        None => missing.push(name.clone()),
+                    }
+                }
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(output)
+        } else {
+            Err(missing)
+        }
+    }
+}
+
+/// 替换文件名中不允许出现的字符
+fn sanitize_file_name(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Template> {
+        Template::parse(text, &default_sources())
+    }
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn template_renders_tokens_and_padding() {
+        let template = parse("{artist} - {TRACK:02} - {title}").unwrap();
+        assert_eq!(
+            template.tokens().collect::<Vec<_>>(),
+            vec!["artist", "track", "title"]
+        );
+        let values = values(&[("artist", "AC/DC"), ("track", "3"), ("title", " Song ")]);
+        assert_eq!(template.render(&values).unwrap(), "AC_DC - 03 - Song");
+    }
+
+    #[test]
+    fn template_width_only_pads_numbers() {
+        let template = parse("{track:03}_{name}").unwrap();
+        assert_eq!(
+            template
+                .render(&values(&[("track", "A1"), ("name", "x")]))
+                .unwrap(),
+            "A1_x"
+        );
+        assert_eq!(
+            template
+                .render(&values(&[("track", "1234"), ("name", "x")]))
+                .unwrap(),
+            "1234_x"
+        );
+    }
+
+    #[test]
+    fn template_reports_missing_tokens() {
+        let template = parse("{artist} - {title}.{ext}").unwrap();
+        assert_eq!(
+            template.render(&values(&[("artist", "A"), ("title", "  "), ("ext", "mp3")])),
+            Err(vec!["title".to_string()])
+        );
+    }
+
+    #[test]
+    fn template_rejects_invalid_syntax_and_unknown_tokens() {
+        for text in [
+            "",
+            "{color}",
+            "{artist",
+            "artist}",
+            "{track:xx}",
+            "{track:}",
+        ] {
+            assert!(parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn builtin_values_read_path_parts() {
+        let values = builtin_values(Path::new("music/album/song.mp3"));
+        assert_eq!(values["name"], "song");
+        assert_eq!(values["ext"], "mp3");
+        assert_eq!(values["parent"], "album");
+    }
+}
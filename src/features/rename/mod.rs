@@ -1,7 +1,15 @@
+pub mod audio;
 pub mod config;
 pub mod core;
+pub mod edit;
 pub mod interface;
+pub mod mapping;
+pub mod metadata;
+pub mod numbering;
+pub mod pipeline;
+pub mod replace;
 
 pub use config::BatchRenameConfig;
 pub use core::BatchRenameCore;
 pub use interface::{run_interactive, RenameTool};
+pub use metadata::MetadataSource;
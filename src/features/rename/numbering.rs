@@ -0,0 +1,334 @@
+use crate::features::rename::config::NumberSort;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// 按指定方式对文件排序（决定序号分配顺序）
+pub fn sort_files(files: &mut [PathBuf], sort: NumberSort) {
+    match sort {
+        NumberSort::Name => files.sort(),
+        NumberSort::Natural => files.sort_by(|a, b| natural_cmp(a, b)),
+        NumberSort::Mtime => sort_by_cached_key(files, |path| {
+            fs::metadata(path).and_then(|m| m.modified()).ok()
+        }),
+        NumberSort::Size => {
+            sort_by_cached_key(files, |path| fs::metadata(path).map(|m| m.len()).ok())
+        }
+        // 没有EXIF拍摄时间的文件退回使用修改时间
+        NumberSort::Exif => sort_by_cached_key(files, |path| {
+            read_exif_date(path).or_else(|| {
+                fs::metadata(path)
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .map(format_system_time)
+            })
+        }),
+    }
+}
+
+/// 计算每个文件的序号下标（从1开始），可按所在目录分别计数
+pub fn assign_indices(files: &[PathBuf], reset_per_dir: bool) -> Vec<usize> {
+    let mut counters: HashMap<PathBuf, usize> = HashMap::new();
+    let mut global = 0;
+
+    files
+        .iter()
+        .map(|path| {
+            if reset_per_dir {
+                let parent = path.parent().map(Path::to_path_buf).unwrap_or_default();
+                let counter = counters.entry(parent).or_insert(0);
+                *counter += 1;
+                *counter
+            } else {
+                global += 1;
+                global
+            }
+        })
+        .collect()
+}
+
+/// 先读取排序键再排序，避免重复读取元数据；同键时按路径排序保证结果稳定
+fn sort_by_cached_key<K: Ord>(files: &mut [PathBuf], key_fn: impl Fn(&Path) -> Option<K>) {
+    let mut keyed: Vec<(Option<K>, PathBuf)> = files
+        .iter()
+        .map(|path| (key_fn(path), path.clone()))
+        .collect();
+    keyed.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+    for (slot, (_, path)) in files.iter_mut().zip(keyed) {
+        *slot = path;
+    }
+}
+
+/// 自然排序比较（file2 排在 file10 之前）
+fn natural_cmp(a: &Path, b: &Path) -> Ordering {
+    let a = a.to_string_lossy().to_lowercase();
+    let b = b.to_string_lossy().to_lowercase();
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return a.cmp(&b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x_num = take_digits(&mut a_chars);
+                let y_num = take_digits(&mut b_chars);
+                let x_trimmed = x_num.trim_start_matches('0');
+                let y_trimmed = y_num.trim_start_matches('0');
+                let ordering = x_trimmed
+                    .len()
+                    .cmp(&y_trimmed.len())
+                    .then_with(|| x_trimmed.cmp(y_trimmed));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.peek().copied().filter(|c| c.is_ascii_digit()) {
+        digits.push(c);
+        chars.next();
+    }
+    digits
+}
+
+/// 将系统时间格式化为与EXIF一致的格式，便于混合比较
+fn format_system_time(time: SystemTime) -> String {
+    let time: chrono::DateTime<chrono::Local> = time.into();
+    time.format("%Y:%m:%d %H:%M:%S").to_string()
+}
+
+/// 读取JPEG文件中的EXIF拍摄时间（DateTimeOriginal，回退到DateTime）
+pub fn read_exif_date(path: &Path) -> Option<String> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    let mut marker = [0u8; 2];
+    reader.read_exact(&mut marker).ok()?;
+    if marker != [0xFF, 0xD8] {
+        return None;
+    }
+
+    // 依次查找APP1(Exif)段，遇到图像数据开始即停止
+    loop {
+        reader.read_exact(&mut marker).ok()?;
+        if marker[0] != 0xFF || marker[1] == 0xDA || marker[1] == 0xD9 {
+            return None;
+        }
+        let mut len_bytes = [0u8; 2];
+        reader.read_exact(&mut len_bytes).ok()?;
+        let len = u16::from_be_bytes(len_bytes) as usize;
+        if len < 2 {
+            return None;
+        }
+        let mut segment = vec![0u8; len - 2];
+        reader.read_exact(&mut segment).ok()?;
+
+        if marker[1] == 0xE1 && segment.starts_with(b"Exif\0\0") {
+            return parse_tiff_date(&segment[6..]);
+        }
+    }
+}
+
+/// 解析TIFF结构中的日期标签
+fn parse_tiff_date(tiff: &[u8]) -> Option<String> {
+    let little_endian = match tiff.get(0..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let read_u16 = |offset: usize| -> Option<u16> {
+        let bytes: [u8; 2] = tiff.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    };
+    let read_u32 = |offset: usize| -> Option<u32> {
+        let bytes: [u8; 4] = tiff.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+    // 在IFD中查找指定标签，返回其数据偏移或值
+    let find_tag = |ifd: usize, tag: u16| -> Option<u32> {
+        let count = read_u16(ifd)? as usize;
+        (0..count).find_map(|i| {
+            let entry = ifd + 2 + i * 12;
+            (read_u16(entry)? == tag).then(|| read_u32(entry + 8))?
+        })
+    };
+    let read_ascii = |offset: u32| -> Option<String> {
+        let bytes = tiff.get(offset as usize..offset as usize + 19)?;
+        let text = std::str::from_utf8(bytes).ok()?;
+        (text.trim() != "").then(|| text.to_string())
+    };
+
+    let ifd0 = read_u32(4)? as usize;
+    let exif_date = find_tag(ifd0, 0x8769)
+        .and_then(|exif_ifd| find_tag(exif_ifd as usize, 0x9003))
+        .and_then(read_ascii);
+
+    exif_date.or_else(|| find_tag(ifd0, 0x0132).and_then(read_ascii))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    /// 构造最小的 TIFF 结构：IFD0 可含 DateTime 和指向 Exif IFD 的指针
+    fn tiff(little_endian: bool, original: Option<&str>, modified: Option<&str>) -> Vec<u8> {
+        let u16_bytes = |value: u16| {
+            if little_endian {
+                value.to_le_bytes()
+            } else {
+                value.to_be_bytes()
+            }
+        };
+        let u32_bytes = |value: u32| {
+            if little_endian {
+                value.to_le_bytes()
+            } else {
+                value.to_be_bytes()
+            }
+        };
+        let entry = |output: &mut Vec<u8>, tag: u16, kind: u16, count: u32, value: u32| {
+            output.extend(u16_bytes(tag));
+            output.extend(u16_bytes(kind));
+            output.extend(u32_bytes(count));
+            output.extend(u32_bytes(value));
+        };
+
+        let ifd0_count = modified.is_some() as u32 + original.is_some() as u32;
+        let exif_ifd = 8 + 2 + 12 * ifd0_count + 4;
+        let data = exif_ifd + if original.is_some() { 18 } else { 0 };
+
+        let mut output = if little_endian {
+            b"II*\0".to_vec()
+        } else {
+            b"MM\0*".to_vec()
+        };
+        output.extend(u32_bytes(8));
+        output.extend(u16_bytes(ifd0_count as u16));
+        if modified.is_some() {
+            entry(&mut output, 0x0132, 2, 20, data + 20);
+        }
+        if original.is_some() {
+            entry(&mut output, 0x8769, 4, 1, exif_ifd);
+        }
+        output.extend(u32_bytes(0));
+        if original.is_some() {
+            output.extend(u16_bytes(1));
+            entry(&mut output, 0x9003, 2, 20, data);
+            output.extend(u32_bytes(0));
+        }
+        for text in [
+            original.unwrap_or("").to_string(),
+            modified.unwrap_or("").to_string(),
+        ] {
+            let mut bytes = text.into_bytes();
+            bytes.resize(20, 0);
+            output.extend(bytes);
+        }
+        output
+    }
+
+    #[test]
+    fn natural_cmp_orders_numbers_by_value() {
+        let mut files = paths(&[
+            "file10.txt",
+            "File2.txt",
+            "file1.txt",
+            "file02.txt",
+            "a.txt",
+        ]);
+        files.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            files,
+            paths(&[
+                "a.txt",
+                "file1.txt",
+                "file02.txt",
+                "File2.txt",
+                "file10.txt"
+            ])
+        );
+        assert_eq!(
+            natural_cmp(Path::new("file2"), Path::new("file10")),
+            Ordering::Less
+        );
+        assert_eq!(
+            natural_cmp(Path::new("v1.10"), Path::new("v1.9")),
+            Ordering::Greater
+        );
+        assert_eq!(natural_cmp(Path::new("a"), Path::new("a1")), Ordering::Less);
+    }
+
+    #[test]
+    fn assign_indices_counts_globally_or_per_dir() {
+        let files = paths(&["a/1.txt", "a/2.txt", "b/1.txt", "a/3.txt", "top.txt"]);
+        assert_eq!(assign_indices(&files, false), [1, 2, 3, 4, 5]);
+        assert_eq!(assign_indices(&files, true), [1, 2, 1, 3, 1]);
+    }
+
+    #[test]
+    fn parse_tiff_date_reads_both_byte_orders() {
+        for little_endian in [true, false] {
+            let data = tiff(
+                little_endian,
+                Some("2023:05:06 07:08:09"),
+                Some("2024:01:02 03:04:05"),
+            );
+            assert_eq!(
+                parse_tiff_date(&data).as_deref(),
+                Some("2023:05:06 07:08:09"),
+                "little_endian={}",
+                little_endian
+            );
+        }
+    }
+
+    #[test]
+    fn parse_tiff_date_falls_back_to_datetime() {
+        for little_endian in [true, false] {
+            let data = tiff(little_endian, None, Some("2024:01:02 03:04:05"));
+            assert_eq!(
+                parse_tiff_date(&data).as_deref(),
+                Some("2024:01:02 03:04:05")
+            );
+            assert_eq!(parse_tiff_date(&tiff(little_endian, None, None)), None);
+        }
+    }
+
+    #[test]
+    fn parse_tiff_date_rejects_malformed_data() {
+        assert_eq!(parse_tiff_date(b""), None);
+        assert_eq!(parse_tiff_date(b"XX*\0\x08\0\0\0"), None);
+        // IFD 偏移越界
+        assert_eq!(parse_tiff_date(b"II*\0\xff\0\0\0"), None);
+        let mut data = tiff(true, Some("2023:05:06 07:08:09"), None);
+        data.truncate(data.len() - 25);
+        assert_eq!(parse_tiff_date(&data), None);
+    }
+}
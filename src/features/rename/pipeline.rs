@@ -0,0 +1,409 @@
+use crate::features::rename::config::{BatchRenameConfig, NumberPosition};
+use crate::features::rename::replace::ReplaceRule;
+use crate::utils;
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::PathBuf;
+
+/// 重命名规则（按顺序组成处理流水线，可重复使用）
+#[derive(Debug, Clone)]
+pub struct RenameRule {
+    /// 规则原文（用于预览和保存预设）
+    text: String,
+    action: RuleAction,
+}
+
+/// 规则的具体操作
+#[derive(Debug, Clone)]
+enum RuleAction {
+    Replace(ReplaceRule),
+    Prefix(String),
+    Suffix(String),
+    Case(CaseMode),
+    Trim,
+    Number(usize),
+    Ext(String),
+}
+
+/// 大小写转换方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CaseMode {
+    Lower,
+    Upper,
+    /// 每个单词首字母大写
+    Title,
+}
+
+/// 可用的规则类型
+pub const RULE_KINDS: [&str; 7] = [
+    "replace", "prefix", "suffix", "case", "trim", "number", "ext",
+];
+
+impl RenameRule {
+    /// 解析规则，格式为 `类型: 参数`，如 `replace: a=b`、`prefix: x_`、`case: lower`
+    pub fn parse(text: &str) -> Result<Self> {
+        // 冒号后只去掉一个空格，保留前缀、后缀中有意义的空白
+        let (kind, arg) = match text.split_once(':') {
+            Some((kind, arg)) => (
+                kind.trim().to_lowercase(),
+                arg.strip_prefix(' ').unwrap_or(arg),
+            ),
+            None => (text.trim().to_lowercase(), ""),
+        };
+
+        let action = match kind.as_str() {
+            "replace" => {
+                if arg.is_empty() {
+                    return Err(anyhow!("replace 规则缺少替换内容: {}", text));
+                }
+                RuleAction::Replace(ReplaceRule::parse(arg)?)
+            }
+            "prefix" => RuleAction::Prefix(Self::checked_text(arg, "前缀")?),
+            "suffix" => RuleAction::Suffix(Self::checked_text(arg, "后缀")?),
+            "case" => RuleAction::Case(match arg.trim().to_lowercase().as_str() {
+                "lower" => CaseMode::Lower,
+                "upper" => CaseMode::Upper,
+                "title" => CaseMode::Title,
+                other => {
+                    return Err(anyhow!(
+                        "无效的大小写方式: {}（可用 lower, upper, title）",
+                        other
+                    ))
+                }
+            }),
+            "trim" => RuleAction::Trim,
+            "number" => RuleAction::Number(match arg.trim() {
+                "" => 1,
+                start => match start.parse::<usize>() {
+                    Ok(start) if start > 0 => start,
+                    _ => return Err(anyhow!("序号起始值必须是正整数: {}", start)),
+                },
+            }),
+            "ext" => {
+                let ext = arg.trim().trim_start_matches('.');
+                if ext.contains('.') {
+                    return Err(anyhow!("扩展名不能包含点号: {}", ext));
+                }
+                RuleAction::Ext(Self::checked_text(ext, "扩展名")?)
+            }
+            other => {
+                return Err(anyhow!(
+                    "未知的规则类型: {}（可用 {}）",
+                    other,
+                    RULE_KINDS.join(", ")
+                ))
+            }
+        };
+
+        Ok(Self {
+            text: text.to_string(),
+            action,
+        })
+    }
+
+    /// 检查规则文本中不包含路径分隔符
+    fn checked_text(text: &str, what: &str) -> Result<String> {
+        if text.contains('/') || text.contains('\\') {
+            return Err(anyhow!("{}不能包含路径分隔符: {}", what, text));
+        }
+        Ok(text.to_string())
+    }
+
+    /// 规则原文
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// 是否为修改扩展名的规则
+    pub fn is_ext(&self) -> bool {
+        matches!(self.action, RuleAction::Ext(_))
+    }
+
+    /// 由旧式选项（前缀、后缀、替换、序号、扩展名）按固定顺序生成规则，
+    /// 参数检查统一由规则解析完成
+    pub fn from_legacy(
+        prefix: Option<&str>,
+        suffix: Option<&str>,
+        replace_rules: &[&str],
+        number_start: Option<&str>,
+        extension: Option<&str>,
+    ) -> Result<Vec<Self>> {
+        let mut texts = Vec::new();
+        if let Some(prefix) = prefix {
+            texts.push(format!("prefix: {}", prefix));
+        }
+        if let Some(suffix) = suffix {
+            texts.push(format!("suffix: {}", suffix));
+        }
+        for rule in replace_rules {
+            texts.push(format!("replace: {}", rule));
+        }
+        if let Some(start) = number_start {
+            texts.push(format!("number: {}", start));
+        }
+        if let Some(ext) = extension {
+            texts.push(format!("ext: {}", ext));
+        }
+
+        texts.iter().map(|text| Self::parse(text)).collect()
+    }
+
+    /// 应用规则：name 为主文件名，ext 为扩展名（空表示无扩展名），index 为从1开始的序号
+    pub fn apply(
+        &self,
+        name: &mut String,
+        ext: &mut String,
+        index: usize,
+        config: &BatchRenameConfig,
+    ) {
+        match &self.action {
+            RuleAction::Replace(rule) => *name = rule.apply(name),
+            RuleAction::Prefix(prefix) => *name = format!("{}{}", prefix, name),
+            RuleAction::Suffix(suffix) => name.push_str(suffix),
+            RuleAction::Case(CaseMode::Lower) => *name = name.to_lowercase(),
+            RuleAction::Case(CaseMode::Upper) => *name = name.to_uppercase(),
+            RuleAction::Case(CaseMode::Title) => *name = title_case(name),
+            RuleAction::Trim => *name = name.trim().to_string(),
+            RuleAction::Number(start) => {
+                let number = format!(
+                    "{:0width$}",
+                    start + (index - 1) * config.number_step,
+                    width = config.number_width
+                );
+                let separator = &config.number_separator;
+                *name = match config.number_position {
+                    NumberPosition::Prefix => format!("{}{}{}", number, separator, name),
+                    NumberPosition::Suffix => format!("{}{}{}", name, separator, number),
+                    NumberPosition::Replace => number,
+                };
+            }
+            RuleAction::Ext(new_ext) => *ext = new_ext.clone(),
+        }
+    }
+}
+
+/// 每个单词首字母大写，其余小写（空格、下划线、连字符和点号视为单词分隔）
+fn title_case(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut word_start = true;
+    for c in text.chars() {
+        if word_start {
+            output.extend(c.to_uppercase());
+        } else {
+            output.extend(c.to_lowercase());
+        }
+        word_start = c.is_whitespace() || matches!(c, '_' | '-' | '.');
+    }
+    output
+}
+
+/// 预设保存目录
+fn preset_dir() -> Result<PathBuf> {
+    utils::app_data_dir()
+        .map(|dir| dir.join("rename_presets"))
+        .ok_or_else(|| anyhow!("无法确定预设保存目录"))
+}
+
+/// 检查预设名称是否合法（可作为文件名）
+pub fn check_preset_name(name: &str) -> Result<()> {
+    let invalid = name.is_empty()
+        || name.starts_with('.')
+        || name.chars().any(|c| {
+            matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || c.is_control()
+        });
+    if invalid {
+        return Err(anyhow!("无效的预设名称: {}", name));
+    }
+    Ok(())
+}
+
+/// 预设文件路径
+fn preset_path(name: &str) -> Result<PathBuf> {
+    check_preset_name(name)?;
+    Ok(preset_dir()?.join(format!("{}.rules", name)))
+}
+
+/// 保存预设（每行一条规则）
+pub fn save_preset(name: &str, rules: &[RenameRule]) -> Result<PathBuf> {
+    if rules.is_empty() {
+        return Err(anyhow!("没有可保存的规则"));
+    }
+    let path = preset_path(name)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| anyhow!("创建预设目录失败: {}", e))?;
+    }
+    let content: String = rules
+        .iter()
+        .map(|rule| format!("{}\n", rule.text()))
+        .collect();
+    fs::write(&path, content).map_err(|e| anyhow!("保存预设失败: {}", e))?;
+    Ok(path)
+}
+
+/// 读取预设中的规则
+pub fn load_preset(name: &str) -> Result<Vec<RenameRule>> {
+    let path = preset_path(name)?;
+    let content = fs::read_to_string(&path).map_err(|_| anyhow!("预设不存在: {}", name))?;
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            RenameRule::parse(line).map_err(|e| anyhow!("预设 {} 中的规则无效: {}", name, e))
+        })
+        .collect()
+}
+
+/// 列出已保存的预设：(名称, 规则原文)
+pub fn list_presets() -> Result<Vec<(String, Vec<String>)>> {
+    let dir = preset_dir()?;
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut presets = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "rules") {
+            let name = path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let rules = fs::read_to_string(&path)
+                .unwrap_or_default()
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from)
+                .collect();
+            presets.push((name, rules));
+        }
+    }
+    presets.sort();
+    Ok(presets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(extra: &[&str]) -> Result<BatchRenameConfig> {
+        let mut args = vec!["rename", "-d", ".", "-m", "*"];
+        args.extend_from_slice(extra);
+        let matches = BatchRenameConfig::build_clap_command().try_get_matches_from(args)?;
+        BatchRenameConfig::from_matches(&matches)
+    }
+
+    fn run(rules: &[&str], name: &str, ext: &str, index: usize) -> String {
+        let config = config(&["--rule", "trim"]).unwrap();
+        let (mut name, mut ext) = (name.to_string(), ext.to_string());
+        for text in rules {
+            RenameRule::parse(text)
+                .unwrap()
+                .apply(&mut name, &mut ext, index, &config);
+        }
+        format!("{}.{}", name, ext)
+    }
+
+    #[test]
+    fn rules_apply_in_order() {
+        assert_eq!(
+            run(
+                &["replace: -=_", "prefix: x_", "case: upper", "ext: .TXT"],
+                "a-b",
+                "md",
+                1
+            ),
+            "X_A_B.TXT"
+        );
+        assert_eq!(run(&["case: upper", "prefix: x_"], "a", "md", 1), "x_A.md");
+    }
+
+    #[test]
+    fn number_rule_uses_index_and_config() {
+        assert_eq!(run(&["number: 5"], "a", "jpg", 3), "a_007.jpg");
+        let config = config(&[
+            "--rule",
+            "number",
+            "--number-pos",
+            "prefix",
+            "--number-sep",
+            "-",
+        ])
+        .unwrap();
+        let (mut name, mut ext) = ("a".to_string(), "jpg".to_string());
+        config.rules[0].apply(&mut name, &mut ext, 2, &config);
+        assert_eq!(name, "002-a");
+    }
+
+    #[test]
+    fn prefix_and_suffix_keep_spaces_after_colon() {
+        assert_eq!(run(&["prefix:  x ", "trim"], "a", "t", 1), "x a.t");
+        assert_eq!(run(&["suffix: _v2"], "a", "t", 1), "a_v2.t");
+    }
+
+    #[test]
+    fn title_case_splits_on_separators() {
+        assert_eq!(
+            title_case("hello wORLD_foo-bar.baz"),
+            "Hello World_Foo-Bar.Baz"
+        );
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        for text in [
+            "unknown: x",
+            "replace:",
+            "prefix: a/b",
+            "suffix: a\\b",
+            "case: sideways",
+            "number: 0",
+            "number: abc",
+            "ext: tar.gz",
+        ] {
+            assert!(RenameRule::parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn legacy_options_become_rules_in_fixed_order() {
+        let config = config(&["-e", "md", "-r", "a=b", "-s", "_s", "-p", "p_", "-n", "3"]).unwrap();
+        let texts: Vec<&str> = config.rules.iter().map(|rule| rule.text()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "prefix: p_",
+                "suffix: _s",
+                "replace: a=b",
+                "number: 3",
+                "ext: md"
+            ]
+        );
+    }
+
+    #[test]
+    fn legacy_options_are_validated_by_rule_parsing() {
+        assert!(config(&["-p", "a/b"]).is_err());
+        assert!(config(&["-n", "abc"]).is_err());
+        assert!(config(&["-n", "0"]).is_err());
+        assert!(config(&["-e", "tar.gz"]).is_err());
+        assert!(config(&["-r", "s/(/x/"]).is_err());
+    }
+
+    #[test]
+    fn explicit_rules_cannot_be_mixed_with_legacy_options() {
+        assert!(config(&["-p", "x_", "--rule", "case: lower"]).is_err());
+    }
+
+    #[test]
+    fn preset_names_must_be_valid_file_names() {
+        assert!(check_preset_name("tidy").is_ok());
+        for name in ["", ".hidden", "a/b", "a\\b", "a:b", "a*b"] {
+            assert!(check_preset_name(name).is_err(), "{:?}", name);
+        }
+        assert!(config(&["--rule", "trim", "--save-preset", "../x"]).is_err());
+    }
+}
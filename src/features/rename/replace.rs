@@ -0,0 +1,412 @@
+use anyhow::{anyhow, Result};
+use regex::{Captures, Regex, RegexBuilder};
+
+/// 替换规则：简单文本替换或 sed 风格的正则替换
+#[derive(Debug, Clone)]
+pub enum ReplaceRule {
+    /// 文本替换 old=new（没有 = 时删除匹配内容）
+    Literal { from: String, to: String },
+    /// 正则替换 s/pattern/replacement/flags
+    Regex {
+        regex: Regex,
+        replacement: Vec<ReplacementPart>,
+        global: bool,
+    },
+}
+
+/// 替换文本的组成部分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplacementPart {
+    /// 普通文本
+    Text(String),
+    /// 按序号引用捕获组（0 表示整个匹配）
+    Group(usize),
+    /// 按名称引用捕获组
+    Named(String),
+    /// \U 之后全部转大写
+    Upper,
+    /// \L 之后全部转小写
+    Lower,
+    /// \E 结束大小写转换
+    End,
+    /// \u 下一个字符转大写
+    UpperNext,
+    /// \l 下一个字符转小写
+    LowerNext,
+}
+
+/// sed 风格规则可用的分隔符
+const SED_DELIMITERS: [char; 3] = ['/', '#', '|'];
+
+impl ReplaceRule {
+    /// 解析替换规则
+    ///
+    /// 支持 `s/pattern/replacement/gi`（分隔符可用 / # |，用 \ 转义）、
+    /// 兼容旧写法 `/pattern/replacement/`（默认全局替换）、`old=new` 和直接删除文本。
+    pub fn parse(rule: &str) -> Result<Self> {
+        let mut chars = rule.chars();
+        match (chars.next(), chars.next()) {
+            (Some('s'), Some(delimiter)) if SED_DELIMITERS.contains(&delimiter) => {
+                Self::parse_sed(&rule[2..], delimiter, rule, false)
+            }
+            (Some('/'), _) => Self::parse_sed(&rule[1..], '/', rule, true),
+            _ => Ok(match rule.split_once('=') {
+                Some((from, to)) => ReplaceRule::Literal {
+                    from: from.to_string(),
+                    to: checked_text(to, rule)?.to_string(),
+                },
+                None => ReplaceRule::Literal {
+                    from: rule.to_string(),
+                    to: String::new(),
+                },
+            }),
+        }
+    }
+
+    /// 对文件名应用规则
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            ReplaceRule::Literal { from, to } => {
+                if from.is_empty() {
+                    name.to_string()
+                } else {
+                    name.replace(from.as_str(), to)
+                }
+            }
+            ReplaceRule::Regex {
+                regex,
+                replacement,
+                global,
+            } => {
+                let limit = if *global { 0 } else { 1 };
+                regex
+                    .replacen(name, limit, |caps: &Captures| {
+                        expand_replacement(replacement, caps)
+                    })
+                    .to_string()
+            }
+        }
+    }
+
+    /// 解析 sed 风格规则主体（不含开头的 s 和分隔符）
+    fn parse_sed(body: &str, delimiter: char, rule: &str, legacy: bool) -> Result<Self> {
+        let parts = split_unescaped(body, delimiter);
+        let (pattern, replacement, flags) = match parts.as_slice() {
+            [pattern, replacement, flags] => (pattern, replacement, flags.as_str()),
+            // 旧写法允许省略末尾分隔符
+            [pattern, replacement] if legacy => (pattern, replacement, ""),
+            _ => {
+                return Err(anyhow!(
+                    "替换规则格式错误: {}（应为 s/模式/替换/标志）",
+                    rule
+                ))
+            }
+        };
+
+        let mut global = legacy;
+        let mut case_insensitive = false;
+        for flag in flags.chars() {
+            match flag {
+                'g' => global = true,
+                'i' => case_insensitive = true,
+                other => return Err(anyhow!("不支持的替换标志 '{}': {}", other, rule)),
+            }
+        }
+
+        if pattern.is_empty() {
+            return Err(anyhow!("替换规则的匹配模式不能为空: {}", rule));
+        }
+
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|e| anyhow!("正则表达式无效 '{}': {}", pattern, e))?;
+        let replacement = parse_replacement(replacement)?;
+        validate_groups(&regex, &replacement)?;
+        for part in &replacement {
+            if let ReplacementPart::Text(text) = part {
+                checked_text(text, rule)?;
+            }
+        }
+
+        Ok(ReplaceRule::Regex {
+            regex,
+            replacement,
+            global,
+        })
+    }
+}
+
+/// 检查替换文本不会引入路径分隔符，避免把文件移到其他目录
+fn checked_text<'a>(text: &'a str, rule: &str) -> Result<&'a str> {
+    if text.contains('/') || text.contains('\\') {
+        return Err(anyhow!("替换内容不能包含路径分隔符: {}", rule));
+    }
+    Ok(text)
+}
+
+/// 按未转义的分隔符拆分，\分隔符 还原为分隔符本身，其余转义原样保留
+fn split_unescaped(text: &str, delimiter: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        let current = parts.last_mut().expect("parts 至少包含一个元素");
+        match c {
+            '\\' => match chars.next() {
+                Some(next) if next == delimiter => current.push(next),
+                Some(next) => {
+                    current.push('\\');
+                    current.push(next);
+                }
+                None => current.push('\\'),
+            },
+            c if c == delimiter => parts.push(String::new()),
+            c => current.push(c),
+        }
+    }
+
+    parts
+}
+
+/// 解析替换文本中的捕获组引用（& \1 $1 ${name} $name，\& 表示 & 本身）
+/// 和大小写修饰符（\U \L \E \u \l）
+fn parse_replacement(text: &str) -> Result<Vec<ReplacementPart>> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('U') => push(&mut parts, &mut literal, ReplacementPart::Upper),
+                Some('L') => push(&mut parts, &mut literal, ReplacementPart::Lower),
+                Some('E') => push(&mut parts, &mut literal, ReplacementPart::End),
+                Some('u') => push(&mut parts, &mut literal, ReplacementPart::UpperNext),
+                Some('l') => push(&mut parts, &mut literal, ReplacementPart::LowerNext),
+                Some(d) if d.is_ascii_digit() => {
+                    let index = d.to_digit(10).unwrap_or(0) as usize;
+                    push(&mut parts, &mut literal, ReplacementPart::Group(index));
+                }
+                Some(other) => literal.push(other),
+                None => literal.push('\\'),
+            },
+            '&' => push(&mut parts, &mut literal, ReplacementPart::Group(0)),
+            '$' => match chars.peek().copied() {
+                Some('$') => {
+                    chars.next();
+                    literal.push('$');
+                }
+                Some('{') => {
+                    chars.next();
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(ch) => name.push(ch),
+                            None => return Err(anyhow!("替换文本中的 ${{ 缺少右括号: {}", text)),
+                        }
+                    }
+                    push(&mut parts, &mut literal, group_reference(name));
+                }
+                Some(ch) if ch.is_ascii_alphanumeric() || ch == '_' => {
+                    let mut name = String::new();
+                    while let Some(ch) = chars
+                        .peek()
+                        .copied()
+                        .filter(|ch| ch.is_ascii_alphanumeric() || *ch == '_')
+                    {
+                        name.push(ch);
+                        chars.next();
+                    }
+                    push(&mut parts, &mut literal, group_reference(name));
+                }
+                _ => literal.push('$'),
+            },
+            c => literal.push(c),
+        }
+    }
+
+    if !literal.is_empty() {
+        parts.push(ReplacementPart::Text(literal));
+    }
+    Ok(parts)
+}
+
+/// 先写入已累积的普通文本，再写入新的部分
+fn push(parts: &mut Vec<ReplacementPart>, literal: &mut String, part: ReplacementPart) {
+    if !literal.is_empty() {
+        parts.push(ReplacementPart::Text(std::mem::take(literal)));
+    }
+    parts.push(part);
+}
+
+fn group_reference(name: String) -> ReplacementPart {
+    match name.parse::<usize>() {
+        Ok(index) => ReplacementPart::Group(index),
+        Err(_) => ReplacementPart::Named(name),
+    }
+}
+
+/// 检查替换文本引用的捕获组是否存在
+fn validate_groups(regex: &Regex, replacement: &[ReplacementPart]) -> Result<()> {
+    for part in replacement {
+        match part {
+            ReplacementPart::Group(index) if *index >= regex.captures_len() => {
+                return Err(anyhow!(
+                    "替换文本引用了不存在的捕获组 {}（共 {} 个）",
+                    index,
+                    regex.captures_len() - 1
+                ));
+            }
+            ReplacementPart::Named(name) if !regex.capture_names().flatten().any(|n| n == name) => {
+                return Err(anyhow!("替换文本引用了不存在的命名捕获组: {}", name));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// 根据一次匹配结果生成替换文本
+fn expand_replacement(replacement: &[ReplacementPart], caps: &Captures) -> String {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Case {
+        None,
+        Upper,
+        Lower,
+    }
+
+    let mut output = String::new();
+    let mut case = Case::None;
+    let mut next_case = Case::None;
+
+    let mut emit = |text: &str, case: Case, next_case: &mut Case| {
+        for ch in text.chars() {
+            let applied = if *next_case != Case::None {
+                std::mem::replace(next_case, Case::None)
+            } else {
+                case
+            };
+            match applied {
+                Case::Upper => output.extend(ch.to_uppercase()),
+                Case::Lower => output.extend(ch.to_lowercase()),
+                Case::None => output.push(ch),
+            }
+        }
+    };
+
+    for part in replacement {
+        match part {
+            ReplacementPart::Text(text) => emit(text, case, &mut next_case),
+            ReplacementPart::Group(index) => {
+                let text = caps.get(*index).map(|m| m.as_str()).unwrap_or("");
+                emit(text, case, &mut next_case);
+            }
+            ReplacementPart::Named(name) => {
+                let text = caps.name(name).map(|m| m.as_str()).unwrap_or("");
+                emit(text, case, &mut next_case);
+            }
+            ReplacementPart::Upper => case = Case::Upper,
+            ReplacementPart::Lower => case = Case::Lower,
+            ReplacementPart::End => case = Case::None,
+            ReplacementPart::UpperNext => next_case = Case::Upper,
+            ReplacementPart::LowerNext => next_case = Case::Lower,
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(rule: &str, name: &str) -> String {
+        ReplaceRule::parse(rule).unwrap().apply(name)
+    }
+
+    #[test]
+    fn literal_rules_replace_or_delete() {
+        assert_eq!(apply("old=new", "old_old.txt"), "new_new.txt");
+        assert_eq!(apply("_copy", "a_copy.txt"), "a.txt");
+        assert_eq!(apply("a=", "banana"), "bnn");
+    }
+
+    #[test]
+    fn sed_rules_respect_global_and_case_flags() {
+        assert_eq!(apply("s/a/o/", "banana"), "bonana");
+        assert_eq!(apply("s/a/o/g", "banana"), "bonono");
+        assert_eq!(apply("s/IMG/photo/i", "img_1.jpg"), "photo_1.jpg");
+        // 旧写法默认全局替换
+        assert_eq!(apply("/a/o/", "banana"), "bonono");
+    }
+
+    #[test]
+    fn capture_groups_by_number_and_name() {
+        assert_eq!(apply(r"s/(\d+)-(\d+)/\2_\1/", "12-34.txt"), "34_12.txt");
+        assert_eq!(apply(r"s/(\d+)/[$1]/g", "a1b22"), "a[1]b[22]");
+        assert_eq!(
+            apply(r"s/(?P<y>\d{4})(?P<m>\d{2})/${m}-${y}/", "202401.jpg"),
+            "01-2024.jpg"
+        );
+        assert_eq!(apply(r"s/x/$$/", "x"), "$");
+    }
+
+    #[test]
+    fn case_modifiers() {
+        assert_eq!(
+            apply(r"s/(\w+) (\w+)/\U\1\E \u\2/", "hello world"),
+            "HELLO World"
+        );
+        assert_eq!(apply(r"s/.*/\L&/", "ABC"), "abc");
+        assert_eq!(apply(r"s/(.*)/\L\1/", "ABC.TXT"), "abc.txt");
+        assert_eq!(apply(r"s/(\w)(\w*)/\l\1\U\2/", "Name"), "nAME");
+    }
+
+    #[test]
+    fn ampersand_expands_to_whole_match() {
+        assert_eq!(apply("s/b+/[&]/", "abbc"), "a[bb]c");
+        assert_eq!(apply(r"s/b/\&/g", "abcb"), "a&c&");
+        assert_eq!(apply(r"s/(b)/&\1/", "ab"), "abb");
+        assert_eq!(apply("a=&", "abc"), "&bc");
+    }
+
+    #[test]
+    fn replacements_cannot_introduce_path_separators() {
+        for rule in [
+            "a=x/y",
+            r"a=x\y",
+            "s/a/x/y/",
+            r"s/a/x\/y/",
+            r"s/a/x\\y/",
+            "s#a#/#",
+        ] {
+            assert!(ReplaceRule::parse(rule).is_err(), "{}", rule);
+        }
+        // 删除或替换掉已有的分隔符仍然允许
+        assert_eq!(apply("s#/#_#g", "a/b"), "a_b");
+    }
+
+    #[test]
+    fn alternative_and_escaped_delimiters() {
+        assert_eq!(apply("s#/#_#g", "a/b/c"), "a_b_c");
+        assert_eq!(apply(r"s/\//-/g", "a/b"), "a-b");
+        assert_eq!(apply("s|a|b|", "aa"), "ba");
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        for rule in [
+            "s/a/b",
+            "s/a/b/x",
+            "s//b/",
+            "s/(a/b/",
+            r"s/(a)/\2/",
+            "s/(a)/${name}/",
+            "s/a/${1/",
+        ] {
+            assert!(ReplaceRule::parse(rule).is_err(), "{}", rule);
+        }
+    }
+}
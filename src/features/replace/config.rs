@@ -0,0 +1,234 @@
+use crate::features::parse;
+use crate::features::search::walker;
+use anyhow::{anyhow, Result};
+use clap::{Arg, Command};
+use std::path::PathBuf;
+
+/// 默认跳过的最大文件大小（10 MiB）
+const DEFAULT_MAX_SIZE: u64 = 10 * 1024 * 1024;
+
+/// 批量文本替换配置
+#[derive(Debug, Clone)]
+pub struct BatchReplaceConfig {
+    pub path: PathBuf,
+    pub name_pattern: String,
+    /// 扩展名列表（为空时不限制）
+    pub file_types: Vec<String>,
+    pub recursive: bool,
+    /// 要查找的文本（正则模式下为正则表达式）
+    pub find: String,
+    /// 替换为的文本（正则模式下可用 $1、${name} 引用分组）
+    pub replacement: String,
+    pub regex: bool,
+    pub case_insensitive: bool,
+    /// 大于此大小的文件不处理
+    pub max_size: u64,
+    pub threads: usize,
+    pub preview: bool,
+    pub backup: bool,
+}
+
+impl BatchReplaceConfig {
+    /// 构建CLAP命令
+    pub fn build_clap_command() -> Command {
+        Command::new("replace")
+            .about("批量文本替换工具")
+            .arg(
+                Arg::new("path")
+                    .short('d')
+                    .long("path")
+                    .value_name("目标文件夹")
+                    .help("目标文件夹（默认当前目录）")
+                    .default_value("."),
+            )
+            .arg(
+                Arg::new("name")
+                    .short('n')
+                    .long("name")
+                    .value_name("文件名模式")
+                    .help("只处理匹配的文件（支持通配符 *）")
+                    .default_value("*"),
+            )
+            .arg(
+                Arg::new("type")
+                    .short('t')
+                    .long("type")
+                    .value_name("文件类型")
+                    .help("只处理这些扩展名，多个用逗号分隔（如 txt,md,html）"),
+            )
+            .arg(
+                Arg::new("recursive")
+                    .short('r')
+                    .long("recursive")
+                    .help("包含子目录")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("find")
+                    .short('f')
+                    .long("find")
+                    .value_name("查找内容")
+                    .help("要查找的文本（可用 \\n、\\t 表示换行和制表符）")
+                    .required(true),
+            )
+            .arg(
+                Arg::new("with")
+                    .short('w')
+                    .long("with")
+                    .value_name("替换内容")
+                    .help("替换为的文本（默认为空，即删除匹配内容）")
+                    .default_value(""),
+            )
+            .arg(
+                Arg::new("regex")
+                    .short('e')
+                    .long("regex")
+                    .help("把查找内容作为正则表达式（替换内容中可用 $1、${name} 引用分组）")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("case")
+                    .short('c')
+                    .long("case")
+                    .help("不区分大小写匹配")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("max-size")
+                    .long("max-size")
+                    .value_name("最大大小")
+                    .help("跳过大于此大小的文件（如 512K、50MB，默认10MB）"),
+            )
+            .arg(
+                Arg::new("threads")
+                    .short('j')
+                    .long("threads")
+                    .value_name("线程数")
+                    .help("扫描线程数（默认按CPU核心数）"),
+            )
+            .arg(
+                Arg::new("preview")
+                    .short('v')
+                    .long("preview")
+                    .help("预览效果（不修改文件）")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("backup")
+                    .short('b')
+                    .long("backup")
+                    .help("备份原文件（加.bak）")
+                    .action(clap::ArgAction::SetTrue),
+            )
+    }
+
+    /// 从命令行参数解析配置
+    pub fn from_matches(matches: &clap::ArgMatches) -> Result<Self> {
+        let path = matches
+            .get_one::<String>("path")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("."));
+        let name_pattern = matches
+            .get_one::<String>("name")
+            .cloned()
+            .unwrap_or_else(|| "*".to_string());
+        let file_types = matches
+            .get_one::<String>("type")
+            .map(|text| parse::parse_extensions(text))
+            .transpose()?
+            .unwrap_or_default();
+
+        let regex = matches.get_flag("regex");
+        let find = matches
+            .get_one::<String>("find")
+            .map(|text| if regex { text.clone() } else { unescape(text) })
+            .unwrap_or_default();
+        let replacement = matches
+            .get_one::<String>("with")
+            .map(|text| unescape(text))
+            .unwrap_or_default();
+
+        let max_size = matches
+            .get_one::<String>("max-size")
+            .map(|text| parse::parse_size(text))
+            .transpose()?
+            .unwrap_or(DEFAULT_MAX_SIZE);
+        let threads = matches
+            .get_one::<String>("threads")
+            .map(|s| s.parse::<usize>())
+            .transpose()
+            .map_err(|_| anyhow!("线程数必须是数字"))?
+            .unwrap_or_else(walker::default_threads);
+
+        let config = Self {
+            path,
+            name_pattern,
+            file_types,
+            recursive: matches.get_flag("recursive"),
+            find,
+            replacement,
+            regex,
+            case_insensitive: matches.get_flag("case"),
+            max_size,
+            threads,
+            preview: matches.get_flag("preview"),
+            backup: matches.get_flag("backup"),
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 验证配置参数
+    pub fn validate(&self) -> Result<()> {
+        if !self.path.exists() {
+            return Err(anyhow!("目录不存在: {}", self.path.display()));
+        }
+
+        if !self.path.is_dir() {
+            return Err(anyhow!("路径不是目录: {}", self.path.display()));
+        }
+
+        if self.name_pattern.trim().is_empty() {
+            return Err(anyhow!("文件名匹配模式不能为空"));
+        }
+
+        if self.find.is_empty() {
+            return Err(anyhow!("查找内容不能为空"));
+        }
+
+        if self.threads == 0 || self.threads > 64 {
+            return Err(anyhow!("线程数必须在1到64之间"));
+        }
+
+        if self.max_size == 0 {
+            return Err(anyhow!("最大文件大小必须大于0"));
+        }
+
+        Ok(())
+    }
+}
+
+/// 处理转义序列 `\n`、`\r`、`\t` 和 `\\`（其他反斜杠原样保留，便于输入 Windows 路径）
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some('\\') => result.push('\\'),
+            _ => {
+                result.push('\\');
+                continue;
+            }
+        }
+        chars.next();
+    }
+    result
+}
@@ -0,0 +1,720 @@
+use crate::error::{HekitError, HekitResult};
+use crate::features::encoding::{self, TextEncoding};
+use crate::features::replace::config::BatchReplaceConfig;
+use crate::features::search::walker::{self, WalkOptions};
+use crate::hekit_error;
+use crate::progress::ProgressManager;
+use crate::utils;
+use glob::{MatchOptions, Pattern};
+use regex::{Regex, RegexBuilder};
+use std::fs::{self, File};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// 每个文件预览的最多修改处数
+const MAX_HUNKS_SHOWN: usize = 5;
+
+/// 每处修改前后各显示的最多行数
+const MAX_HUNK_LINES: usize = 6;
+
+/// 删除行和新增行的终端颜色
+const REMOVED_COLOR: &str = "\x1b[31m";
+const ADDED_COLOR: &str = "\x1b[32m";
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// 单处替换（位置为解码后文本中的字节偏移）
+struct Edit {
+    start: usize,
+    end: usize,
+    replacement: String,
+}
+
+/// 预览中的一处修改：原内容和新内容所在的行
+struct Hunk {
+    old_line: usize,
+    new_line: usize,
+    old: String,
+    new: String,
+}
+
+/// 单个文件的替换计划
+struct FilePlan {
+    path: PathBuf,
+    encoding: TextEncoding,
+    crlf: bool,
+    match_count: usize,
+    /// 按原编码编码后的新内容
+    new_bytes: Vec<u8>,
+    /// 扫描时的大小和修改时间，写入前用于检测文件是否被改动
+    original_len: u64,
+    modified: Option<SystemTime>,
+    /// 预览的修改（最多 MAX_HUNKS_SHOWN 处）
+    hunks: Vec<Hunk>,
+    hunk_count: usize,
+}
+
+/// 扫描时跳过的文件
+#[derive(Default)]
+struct SkipStats {
+    binary: AtomicUsize,
+    large: AtomicUsize,
+    unreadable: AtomicUsize,
+    /// 编码无法无损还原，或替换内容无法用原编码表示
+    encoding: Mutex<Vec<PathBuf>>,
+}
+
+/// 批量文本替换核心逻辑
+pub struct BatchReplaceCore {
+    pub config: BatchReplaceConfig,
+    regex: Regex,
+    color: bool,
+}
+
+impl BatchReplaceCore {
+    /// 创建新的文本替换实例（编译查找模式）
+    pub fn new(config: BatchReplaceConfig) -> HekitResult<Self> {
+        let pattern = if config.regex {
+            config.find.clone()
+        } else {
+            regex::escape(&config.find)
+        };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(config.case_insensitive)
+            .multi_line(true)
+            .crlf(true)
+            .build()
+            .map_err(|e| HekitError::ArgumentParse(format!("查找正则表达式无效: {}", e)))?;
+
+        Ok(Self {
+            config,
+            regex,
+            color: std::io::stdout().is_terminal() && utils::supports_color(),
+        })
+    }
+
+    /// 执行替换：扫描、预览，确认后写入
+    pub fn execute(&self) -> HekitResult<()> {
+        let skipped = SkipStats::default();
+        let plans = self.scan(&skipped)?;
+
+        for plan in &plans {
+            self.print_plan(plan);
+        }
+        self.print_skipped(&skipped);
+
+        if plans.is_empty() {
+            utils::print_info("没有文件需要替换");
+            return Ok(());
+        }
+        let total: usize = plans.iter().map(|plan| plan.match_count).sum();
+        println!("总计: {} 个文件，{} 处匹配", plans.len(), total);
+
+        if self.config.preview {
+            utils::print_info("预览模式，未修改任何文件");
+            return Ok(());
+        }
+        if !self.confirm(&format!(
+            "确认替换 {} 个文件中的 {} 处匹配? (y/N): ",
+            plans.len(),
+            total
+        ))? {
+            return Ok(());
+        }
+
+        self.apply(&plans)
+    }
+
+    /// 遍历目录并为包含匹配内容的文件生成替换计划（按路径排序）
+    fn scan(&self, skipped: &SkipStats) -> HekitResult<Vec<FilePlan>> {
+        let pattern = Pattern::new(&self.config.name_pattern)
+            .map_err(|e| HekitError::ArgumentParse(format!("文件名模式解析失败: {}", e)))?;
+        let options = WalkOptions {
+            threads: self.config.threads,
+            recursive: self.config.recursive,
+            ..WalkOptions::default()
+        };
+        let cancel = AtomicBool::new(false);
+        let plans = Mutex::new(Vec::new());
+
+        let progress = ProgressManager::create_indeterminate("查找匹配内容中...");
+        let stats = walker::walk(&self.config.path, &options, &cancel, |path| {
+            if !self.matches_selection(path, &pattern) {
+                return;
+            }
+            if let Some(plan) = self.plan_file(path, skipped) {
+                plans.lock().unwrap().push(plan);
+            }
+        });
+        drop(progress);
+
+        if stats.skipped > 0 {
+            utils::print_warning(&format!("因权限问题跳过 {} 个目录", stats.skipped));
+        }
+
+        let mut plans = plans.into_inner().unwrap();
+        plans.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(plans)
+    }
+
+    /// 检查文件名和扩展名
+    fn matches_selection(&self, path: &Path, pattern: &Pattern) -> bool {
+        let options = MatchOptions {
+            case_sensitive: !self.config.case_insensitive,
+            ..MatchOptions::new()
+        };
+        let name_matches = path
+            .file_name()
+            .is_some_and(|name| pattern.matches_with(&name.to_string_lossy(), options));
+        if !name_matches {
+            return false;
+        }
+
+        self.config.file_types.is_empty()
+            || path.extension().is_some_and(|ext| {
+                let ext = ext.to_string_lossy();
+                self.config
+                    .file_types
+                    .iter()
+                    .any(|expected| expected.eq_ignore_ascii_case(&ext))
+            })
+    }
+
+    /// 读取并解码文件，计算替换结果（无匹配或跳过时返回 None）
+    fn plan_file(&self, path: &Path, skipped: &SkipStats) -> Option<FilePlan> {
+        let Ok(metadata) = fs::metadata(path) else {
+            skipped.unreadable.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        if metadata.len() > self.config.max_size {
+            skipped.large.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        let Ok(bytes) = fs::read(path) else {
+            skipped.unreadable.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        let Some((text, encoding)) = encoding::decode(&bytes) else {
+            skipped.binary.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        if !self.regex.is_match(&text) {
+            return None;
+        }
+
+        // 原内容必须能按原编码无损还原，否则写回会损坏文件
+        let bom = utf16_bom(&bytes, encoding);
+        if encode_with_bom(&text, encoding, bom).as_deref() != Some(bytes.as_slice()) {
+            skipped.encoding.lock().unwrap().push(path.to_path_buf());
+            return None;
+        }
+
+        // 替换内容中的换行与文件原有的换行符保持一致
+        let crlf = text.contains("\r\n");
+        let template = if crlf {
+            self.config
+                .replacement
+                .replace("\r\n", "\n")
+                .replace('\n', "\r\n")
+        } else {
+            self.config.replacement.clone()
+        };
+
+        let edits = self.edits(&text, &template);
+        let new_text = apply_edits(&text, &edits);
+        if new_text == text {
+            return None;
+        }
+        let Some(new_bytes) = encode_with_bom(&new_text, encoding, bom) else {
+            skipped.encoding.lock().unwrap().push(path.to_path_buf());
+            return None;
+        };
+
+        let (hunks, hunk_count) = build_hunks(&text, &new_text, &edits);
+        Some(FilePlan {
+            path: path.to_path_buf(),
+            encoding,
+            crlf,
+            match_count: edits.len(),
+            new_bytes,
+            original_len: metadata.len(),
+            modified: metadata.modified().ok(),
+            hunks,
+            hunk_count,
+        })
+    }
+
+    /// 找出所有匹配及其替换内容（正则模式下展开分组引用）
+    fn edits(&self, text: &str, template: &str) -> Vec<Edit> {
+        if self.config.regex {
+            self.regex
+                .captures_iter(text)
+                .filter_map(|caps| {
+                    let matched = caps.get(0)?;
+                    let mut replacement = String::new();
+                    caps.expand(template, &mut replacement);
+                    Some(Edit {
+                        start: matched.start(),
+                        end: matched.end(),
+                        replacement,
+                    })
+                })
+                .collect()
+        } else {
+            self.regex
+                .find_iter(text)
+                .map(|matched| Edit {
+                    start: matched.start(),
+                    end: matched.end(),
+                    replacement: template.to_string(),
+                })
+                .collect()
+        }
+    }
+
+    /// 显示单个文件的修改预览
+    fn print_plan(&self, plan: &FilePlan) {
+        println!(
+            "{}  ({} 处匹配, {}{})",
+            plan.path.display(),
+            plan.match_count,
+            plan.encoding.name(),
+            if plan.crlf { ", CRLF" } else { "" }
+        );
+        for hunk in &plan.hunks {
+            self.print_lines(&hunk.old, hunk.old_line, '-', REMOVED_COLOR);
+            self.print_lines(&hunk.new, hunk.new_line, '+', ADDED_COLOR);
+        }
+        if plan.hunk_count > plan.hunks.len() {
+            println!(
+                "       ... 另有 {} 处修改",
+                plan.hunk_count - plan.hunks.len()
+            );
+        }
+        println!();
+    }
+
+    /// 显示修改前或修改后的行（带行号）
+    fn print_lines(&self, text: &str, first_line: usize, sign: char, color: &str) {
+        let lines: Vec<&str> = text.split('\n').collect();
+        for (offset, line) in lines.iter().take(MAX_HUNK_LINES).enumerate() {
+            let line = line.trim_end_matches('\r');
+            if self.color {
+                println!(
+                    "  {:>5} {}{} {}{}",
+                    first_line + offset,
+                    color,
+                    sign,
+                    line,
+                    RESET_COLOR
+                );
+            } else {
+                println!("  {:>5} {} {}", first_line + offset, sign, line);
+            }
+        }
+        if lines.len() > MAX_HUNK_LINES {
+            println!("        {} ... 共 {} 行", sign, lines.len());
+        }
+    }
+
+    /// 显示扫描时跳过的文件
+    fn print_skipped(&self, skipped: &SkipStats) {
+        let binary = skipped.binary.load(Ordering::Relaxed);
+        if binary > 0 {
+            utils::print_info(&format!("跳过 {} 个二进制文件", binary));
+        }
+        let large = skipped.large.load(Ordering::Relaxed);
+        if large > 0 {
+            utils::print_info(&format!(
+                "跳过 {} 个大于 {} 的文件（可用 --max-size 调整）",
+                large,
+                utils::format_size(self.config.max_size)
+            ));
+        }
+        let unreadable = skipped.unreadable.load(Ordering::Relaxed);
+        if unreadable > 0 {
+            utils::print_warning(&format!("{} 个文件无法读取", unreadable));
+        }
+
+        let mut encoding = skipped.encoding.lock().unwrap();
+        if !encoding.is_empty() {
+            encoding.sort();
+            utils::print_warning(&format!(
+                "{} 个文件的编码无法无损保留（或替换内容无法用原编码表示），已跳过:",
+                encoding.len()
+            ));
+            for path in encoding.iter() {
+                println!("  {}", path.display());
+            }
+        }
+    }
+
+    /// 确认是否执行操作
+    fn confirm(&self, prompt: &str) -> HekitResult<bool> {
+        let answer = utils::get_user_input(prompt)
+            .map_err(|e| HekitError::UserInput(format!("读取输入失败: {}", e)))?;
+        if !answer.eq_ignore_ascii_case("y") {
+            utils::print_info("已取消操作");
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// 写入所有替换结果
+    fn apply(&self, plans: &[FilePlan]) -> HekitResult<()> {
+        let progress = ProgressManager::new(plans.len() as u64, "替换中...");
+        let mut success_count = 0;
+        let mut error_count = 0;
+
+        for plan in plans {
+            progress.set_message(&format!("替换: {}", plan.path.display()));
+            match self.write_plan(plan) {
+                Ok(Some(backup)) => {
+                    println!(
+                        "✓ {} ({} 处，备份: {})",
+                        plan.path.display(),
+                        plan.match_count,
+                        backup.display()
+                    );
+                    success_count += 1;
+                }
+                Ok(None) => {
+                    println!("✓ {} ({} 处)", plan.path.display(), plan.match_count);
+                    success_count += 1;
+                }
+                Err(e) => {
+                    eprintln!("✗ {} 失败: {}", plan.path.display(), e);
+                    error_count += 1;
+                }
+            }
+            progress.inc(1);
+        }
+
+        progress.finish_with_message(&format!(
+            "完成: 成功 {} 个, 失败 {} 个",
+            success_count, error_count
+        ));
+
+        if error_count > 0 {
+            hekit_error!(FileOperation, "部分文件替换失败")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 写入单个文件：先写临时文件再替换原文件，返回备份路径
+    fn write_plan(&self, plan: &FilePlan) -> HekitResult<Option<PathBuf>> {
+        // 符号链接写入其指向的文件
+        let target = fs::canonicalize(&plan.path)
+            .map_err(|e| HekitError::FileOperation(format!("无法访问文件: {}", e)))?;
+        let metadata = fs::metadata(&target)
+            .map_err(|e| HekitError::FileOperation(format!("无法读取文件信息: {}", e)))?;
+        if metadata.len() != plan.original_len || metadata.modified().ok() != plan.modified {
+            return hekit_error!(FileOperation, "文件在预览后被修改，已跳过");
+        }
+
+        let backup = if self.config.backup {
+            let backup = backup_path(&plan.path);
+            fs::copy(&target, &backup)
+                .map_err(|e| HekitError::FileOperation(format!("备份失败: {}", e)))?;
+            Some(backup)
+        } else {
+            None
+        };
+
+        let (file, temp) = create_temp_file(&target)
+            .map_err(|e| HekitError::FileOperation(format!("创建临时文件失败: {}", e)))?;
+        let write = || -> std::io::Result<()> {
+            let mut file = file;
+            file.write_all(&plan.new_bytes)?;
+            file.set_permissions(metadata.permissions())?;
+            // 先关闭文件再重命名（Windows 不能重命名已打开的文件）
+            drop(file);
+            fs::rename(&temp, &target)
+        };
+        write().map_err(|e| {
+            let _ = fs::remove_file(&temp);
+            HekitError::FileOperation(format!("写入失败: {}", e))
+        })?;
+
+        Ok(backup)
+    }
+}
+
+/// 在目标文件所在目录创建不与现有文件冲突的临时文件，返回 (文件, 路径)
+fn create_temp_file(target: &Path) -> std::io::Result<(File, PathBuf)> {
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    let mut attempt = 0;
+    loop {
+        let temp = target.with_file_name(format!(
+            ".{}.hekit-tmp-{}-{}",
+            name,
+            std::process::id(),
+            attempt
+        ));
+        // create_new 不会打开已存在的文件或符号链接，名称被占用时换下一个
+        match File::options().write(true).create_new(true).open(&temp) {
+            Ok(file) => return Ok((file, temp)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && attempt < 100 => {
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// 原文件为带 BOM 的 UTF-16 时返回其 BOM
+fn utf16_bom(bytes: &[u8], encoding: TextEncoding) -> &'static [u8] {
+    match encoding {
+        TextEncoding::Utf16Le if bytes.starts_with(&[0xFF, 0xFE]) => &[0xFF, 0xFE],
+        TextEncoding::Utf16Be if bytes.starts_with(&[0xFE, 0xFF]) => &[0xFE, 0xFF],
+        _ => &[],
+    }
+}
+
+/// 按原编码编码文本，并补回 UTF-16 的 BOM
+fn encode_with_bom(text: &str, encoding: TextEncoding, bom: &[u8]) -> Option<Vec<u8>> {
+    let encoded = encoding::encode(text, encoding)?;
+    let mut bytes = Vec::with_capacity(bom.len() + encoded.len());
+    bytes.extend_from_slice(bom);
+    bytes.extend_from_slice(&encoded);
+    Some(bytes)
+}
+
+/// 依次应用所有替换
+fn apply_edits(text: &str, edits: &[Edit]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for edit in edits {
+        result.push_str(&text[last..edit.start]);
+        result.push_str(&edit.replacement);
+        last = edit.end;
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+/// 把相邻行上的替换合并为预览块，返回 (前 MAX_HUNKS_SHOWN 块, 总块数)
+fn build_hunks(old: &str, new: &str, edits: &[Edit]) -> (Vec<Hunk>, usize) {
+    let bytes = old.as_bytes();
+    // 每块在原文本中的范围 [start, end) 及块之前的长度变化
+    let mut ranges: Vec<(usize, usize, isize, isize)> = Vec::new();
+    let mut delta: isize = 0;
+    for edit in edits {
+        let line_start = bytes[..edit.start]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        let last = if edit.end > edit.start {
+            edit.end - 1
+        } else {
+            edit.end
+        };
+        let line_end = bytes[last..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(bytes.len(), |i| last + i)
+            .max(edit.end);
+        let change = edit.replacement.len() as isize - (edit.end - edit.start) as isize;
+
+        match ranges.last_mut() {
+            Some(range) if line_start <= range.1 + 1 => {
+                range.1 = range.1.max(line_end);
+                range.3 += change;
+            }
+            _ => ranges.push((line_start, line_end, delta, change)),
+        }
+        delta += change;
+    }
+
+    let hunk_count = ranges.len();
+    let mut hunks = Vec::new();
+    let (mut old_line, mut new_line) = (1, 1);
+    let (mut old_pos, mut new_pos) = (0, 0);
+    for (start, end, before, change) in ranges.into_iter().take(MAX_HUNKS_SHOWN) {
+        let new_start = (start as isize + before) as usize;
+        let new_end = (end as isize + before + change) as usize;
+        old_line += old[old_pos..start].matches('\n').count();
+        new_line += new[new_pos..new_start].matches('\n').count();
+        old_pos = start;
+        new_pos = new_start;
+        hunks.push(Hunk {
+            old_line,
+            new_line,
+            old: old[start..end].to_string(),
+            new: new[new_start..new_end].to_string(),
+        });
+    }
+    (hunks, hunk_count)
+}
+
+/// 备份文件路径：原文件名加 .bak，已存在时依次尝试 .bak2、.bak3 ...
+fn backup_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut backup = path.with_file_name(format!("{}.bak", name));
+    let mut counter = 2;
+    while backup.exists() {
+        backup = path.with_file_name(format!("{}.bak{}", name, counter));
+        counter += 1;
+    }
+    backup
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("hekit_replace_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn core(dir: &Path, args: &[&str]) -> BatchReplaceCore {
+        let dir = dir.to_string_lossy().into_owned();
+        let mut argv = vec!["replace", "-d", &dir];
+        argv.extend_from_slice(args);
+        let matches = BatchReplaceConfig::build_clap_command()
+            .try_get_matches_from(argv)
+            .unwrap();
+        BatchReplaceCore::new(BatchReplaceConfig::from_matches(&matches).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn write_plan_replaces_content_in_original_encoding() {
+        let dir = temp_dir("gbk");
+        let path = dir.join("a.txt");
+        let (gbk, _, _) = encoding_rs::GBK.encode("旧名称\r\n旧名称\r\n");
+        fs::write(&path, &gbk).unwrap();
+
+        let core = core(&dir, &["-f", "旧", "-w", "新\n"]);
+        let skipped = SkipStats::default();
+        let plan = core.plan_file(&path, &skipped).unwrap();
+        assert_eq!(plan.encoding, TextEncoding::Gbk);
+        assert!(plan.crlf);
+        assert_eq!(plan.match_count, 2);
+
+        assert_eq!(core.write_plan(&plan).unwrap(), None);
+        let (expected, _, _) = encoding_rs::GBK.encode("新\r\n名称\r\n新\r\n名称\r\n");
+        assert_eq!(fs::read(&path).unwrap(), expected.into_owned());
+        // 临时文件已被重命名，目录中只剩原文件
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn write_plan_keeps_utf16_bom_and_writes_backup() {
+        let dir = temp_dir("utf16");
+        let path = dir.join("a.txt");
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(encoding::encode("foo bar", TextEncoding::Utf16Le).unwrap());
+        fs::write(&path, &bytes).unwrap();
+
+        let core = core(&dir, &["-f", "foo", "-w", "baz", "-b"]);
+        let plan = core.plan_file(&path, &SkipStats::default()).unwrap();
+        let backup = core.write_plan(&plan).unwrap().unwrap();
+
+        assert_eq!(fs::read(&backup).unwrap(), bytes);
+        let mut expected = vec![0xFF, 0xFE];
+        expected.extend(encoding::encode("baz bar", TextEncoding::Utf16Le).unwrap());
+        assert_eq!(fs::read(&path).unwrap(), expected);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn write_plan_skips_files_changed_after_preview() {
+        let dir = temp_dir("changed");
+        let path = dir.join("a.txt");
+        fs::write(&path, "foo").unwrap();
+        let core = core(&dir, &["-f", "foo", "-w", "bar"]);
+        let plan = core.plan_file(&path, &SkipStats::default()).unwrap();
+
+        // 大小不变但修改时间变化
+        fs::write(&path, "fox").unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1))
+            .unwrap();
+        assert!(core.write_plan(&plan).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fox");
+
+        // 大小变化
+        fs::write(&path, "foo").unwrap();
+        let plan = core.plan_file(&path, &SkipStats::default()).unwrap();
+        fs::write(&path, "foo foo").unwrap();
+        assert!(core.write_plan(&plan).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "foo foo");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn plan_file_skips_replacement_unrepresentable_in_encoding() {
+        let dir = temp_dir("unrepresentable");
+        let path = dir.join("a.txt");
+        let (gbk, _, _) = encoding_rs::GBK.encode("中文 foo");
+        fs::write(&path, &gbk).unwrap();
+
+        let core = core(&dir, &["-f", "foo", "-w", "😀"]);
+        let skipped = SkipStats::default();
+        assert!(core.plan_file(&path, &skipped).is_none());
+        assert_eq!(skipped.encoding.lock().unwrap().len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn build_hunks_merges_edits_on_adjacent_lines() {
+        let old = "a1\nb1\nc\n\nd1\n";
+        let edits: Vec<Edit> = old
+            .match_indices('1')
+            .map(|(start, _)| Edit {
+                start,
+                end: start + 1,
+                replacement: "22".to_string(),
+            })
+            .collect();
+        let new = apply_edits(old, &edits);
+        assert_eq!(new, "a22\nb22\nc\n\nd22\n");
+
+        let (hunks, count) = build_hunks(old, &new, &edits);
+        assert_eq!(count, 2);
+        assert_eq!(
+            hunks
+                .iter()
+                .map(|hunk| (
+                    hunk.old_line,
+                    hunk.new_line,
+                    hunk.old.as_str(),
+                    hunk.new.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            vec![(1, 1, "a1\nb1", "a22\nb22"), (5, 5, "d1", "d22")]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_plan_does_not_touch_existing_temp_names() {
+        let dir = temp_dir("temp_names");
+        let path = dir.join("a.txt");
+        fs::write(&path, "foo").unwrap();
+        // 旧的固定临时名称是指向其他文件的符号链接，第一个计数名称被普通文件占用
+        let victim = dir.join("victim.txt");
+        fs::write(&victim, "keep").unwrap();
+        std::os::unix::fs::symlink(&victim, dir.join(".a.txt.hekit-tmp")).unwrap();
+        let taken = dir.join(format!(".a.txt.hekit-tmp-{}-0", std::process::id()));
+        fs::write(&taken, "busy").unwrap();
+
+        let core = core(&dir, &["-f", "foo", "-w", "bar"]);
+        let plan = core.plan_file(&path, &SkipStats::default()).unwrap();
+        core.write_plan(&plan).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "bar");
+        assert_eq!(fs::read_to_string(&victim).unwrap(), "keep");
+        assert_eq!(fs::read_to_string(&taken).unwrap(), "busy");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 4);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
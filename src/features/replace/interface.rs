@@ -0,0 +1,83 @@
+use crate::error::HekitResult;
+use crate::features::common::ToolInterface;
+use crate::features::replace::config::BatchReplaceConfig;
+use crate::features::replace::core::BatchReplaceCore;
+
+/// 文本替换工具接口
+pub struct ReplaceTool;
+
+impl ToolInterface for ReplaceTool {
+    /// 工具名称
+    fn tool_name() -> &'static str {
+        "文本替换"
+    }
+
+    /// 显示使用说明
+    fn show_usage() {
+        use crate::utils;
+
+        utils::print_compact_tool_title("批量文本替换");
+        println!();
+
+        println!("参数说明:");
+        println!("  -d, --path       目标文件夹（默认当前目录）");
+        println!("  -n, --name       只处理匹配的文件（支持通配符 *）");
+        println!("  -t, --type       只处理这些扩展名，多个用逗号分隔（如 txt,md,html）");
+        println!("  -r, --recursive  包含子目录");
+        println!("  -f, --find       要查找的文本（必填，用双引号括起来，可用 \\n 表示换行）");
+        println!("  -w, --with       替换为的文本（默认为空，即删除匹配内容）");
+        println!("  -e, --regex      按正则表达式查找（替换内容中可用 $1、${{name}} 引用分组）");
+        println!("  -c, --case       不区分大小写匹配");
+        println!("  --max-size       跳过大于此大小的文件（默认10MB）");
+        println!("  -j, --threads    扫描线程数（默认按CPU核心数）");
+        println!("  -v, --preview    只预览修改（不写入文件）");
+        println!("  -b, --backup     备份原文件（加.bak）");
+        println!();
+        println!("  保持原文件的编码（UTF-8/GBK/UTF-16）、BOM 和换行符（CRLF/LF），");
+        println!("  二进制文件和无法无损保存编码的文件会自动跳过；执行前显示差异并确认。");
+        println!();
+
+        println!("实用示例:");
+        println!("  预览替换: -n \"*.txt\" -f \"旧公司名\" -w \"新公司名\" -r -v");
+        println!("  替换并备份: -t html,css -f \"http://\" -w \"https://\" -r -b");
+        println!(
+            "  正则替换日期格式: -t md -e -f \"(\\d{{4}})/(\\d{{2}})/(\\d{{2}})\" -w \"$1-$2-$3\""
+        );
+        println!("  删除行尾的 TODO 注释: -t rs -e -f \" *// TODO.*$\" -r");
+
+        utils::print_compact_separator();
+    }
+
+    /// 执行命令
+    fn execute_command(input: &str) -> HekitResult<()> {
+        if input.trim().is_empty() {
+            Self::show_usage();
+            return Ok(());
+        }
+
+        let matches = crate::features::common::execute_common_command(
+            input,
+            "replace",
+            BatchReplaceConfig::build_clap_command,
+            Self::show_usage,
+        )?;
+
+        if input.trim() == "help" {
+            return Ok(());
+        }
+
+        let config = BatchReplaceConfig::from_matches(&matches)
+            .map_err(|e| crate::error::HekitError::UserInput(format!("配置错误: {}", e)))?;
+        let core = BatchReplaceCore::new(config)?;
+        core.execute()
+    }
+}
+
+/// 运行交互式界面
+pub fn run_interactive() -> HekitResult<()> {
+    crate::features::common::run_interactive(
+        ReplaceTool::tool_name(),
+        ReplaceTool::execute_command,
+        ReplaceTool::show_usage,
+    )
+}
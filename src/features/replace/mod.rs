@@ -0,0 +1,7 @@
+pub mod config;
+pub mod core;
+pub mod interface;
+
+pub use config::BatchReplaceConfig;
+pub use core::BatchReplaceCore;
+pub use interface::{run_interactive, ReplaceTool};
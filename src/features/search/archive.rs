@@ -0,0 +1,157 @@
+use bzip2::read::BzDecoder;
+use chrono::{Local, NaiveDate, TimeZone};
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 支持搜索的压缩包格式（与压缩工具生成的格式一致）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    TarGz,
+    TarBz2,
+}
+
+/// 压缩包中的一个文件条目
+pub struct ArchiveEntry<'a> {
+    /// 条目在压缩包内的路径（使用 `/` 分隔）
+    pub name: String,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    /// 条目内容（只在需要时读取）
+    pub reader: &'a mut dyn Read,
+}
+
+impl ArchiveKind {
+    /// 根据文件名判断压缩包格式
+    pub fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if name.ends_with(".tar.bz2") || name.ends_with(".tbz2") {
+            Some(ArchiveKind::TarBz2)
+        } else {
+            None
+        }
+    }
+}
+
+/// 压缩包内条目的显示路径，如 `backup.zip!/docs/readme.txt`
+pub fn entry_path(archive: &Path, name: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "{}!/{}",
+        archive.display(),
+        name.trim_start_matches('/')
+    ))
+}
+
+/// 拆分压缩包条目的显示路径，返回 (压缩包路径, 条目名)；普通文件返回 None
+pub fn split_entry_path(path: &Path) -> Option<(PathBuf, String)> {
+    let text = path.to_string_lossy();
+    text.match_indices("!/").find_map(|(index, _)| {
+        let archive = PathBuf::from(&text[..index]);
+        (ArchiveKind::detect(&archive).is_some() && archive.is_file())
+            .then(|| (archive, text[index + 2..].to_string()))
+    })
+}
+
+/// 在压缩包中查找指定条目并交给 visit 处理，条目不存在时返回 None
+pub fn with_entry<T, F>(archive: &Path, name: &str, visit: F) -> io::Result<Option<T>>
+where
+    F: FnOnce(ArchiveEntry) -> io::Result<T>,
+{
+    let kind = ArchiveKind::detect(archive)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "不支持的压缩包格式"))?;
+    let mut visit = Some(visit);
+    let mut result = None;
+    for_each_entry(archive, kind, |entry| {
+        if entry.name.trim_start_matches('/') == name {
+            if let Some(visit) = visit.take() {
+                result = Some(visit(entry));
+            }
+        }
+    })?;
+    result.transpose()
+}
+
+/// 依次访问压缩包中的所有文件条目（跳过目录和无法读取的条目）
+pub fn for_each_entry<F>(path: &Path, kind: ArchiveKind, mut visit: F) -> io::Result<()>
+where
+    F: FnMut(ArchiveEntry),
+{
+    let file = File::open(path)?;
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(file)?;
+            for index in 0..archive.len() {
+                // 加密或损坏的条目直接跳过
+                let Ok(mut entry) = archive.by_index(index) else {
+                    continue;
+                };
+                if entry.is_dir() {
+                    continue;
+                }
+                let name = entry.name().to_string();
+                let size = entry.size();
+                let modified = zip_time(entry.last_modified());
+                visit(ArchiveEntry {
+                    name,
+                    size,
+                    modified,
+                    reader: &mut entry,
+                });
+            }
+            Ok(())
+        }
+        ArchiveKind::TarGz => for_each_tar_entry(GzDecoder::new(file), visit),
+        ArchiveKind::TarBz2 => for_each_tar_entry(BzDecoder::new(file), visit),
+    }
+}
+
+/// 访问 tar 包中的文件条目
+fn for_each_tar_entry<R: Read, F>(reader: R, mut visit: F) -> io::Result<()>
+where
+    F: FnMut(ArchiveEntry),
+{
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.to_string_lossy().replace('\\', "/");
+        let name = path.trim_start_matches("./").to_string();
+        let size = entry.size();
+        let modified = entry
+            .header()
+            .mtime()
+            .ok()
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+        visit(ArchiveEntry {
+            name,
+            size,
+            modified,
+            reader: &mut entry,
+        });
+    }
+    Ok(())
+}
+
+/// zip 条目时间（本地时间，无时区信息）转换为系统时间
+fn zip_time(time: zip::DateTime) -> Option<SystemTime> {
+    let datetime =
+        NaiveDate::from_ymd_opt(time.year().into(), time.month().into(), time.day().into())?
+            .and_hms_opt(
+                time.hour().into(),
+                time.minute().into(),
+                time.second().into(),
+            )?;
+    Local
+        .from_local_datetime(&datetime)
+        .earliest()
+        .map(SystemTime::from)
+}
@@ -1,17 +1,192 @@
+use crate::features::export::ExportFormat;
+use crate::features::magic::FileCategory;
+use crate::features::parse;
+use crate::features::search::query::Query;
+use crate::features::search::walker;
 use anyhow::{anyhow, Result};
 use clap::{Arg, Command};
+use glob::Pattern;
+use std::fs::Metadata;
 use std::path::PathBuf;
+use std::time::SystemTime;
 
 /// 批量搜索配置结构体
 #[derive(Debug, Clone)]
 pub struct BatchSearchConfig {
     pub path: PathBuf,
     pub name_pattern: String,
-    pub file_type: Option<String>,
+    /// 扩展名列表（为空时不限制）
+    pub file_types: Vec<String>,
+    /// 按内容识别的 MIME 类型（通配符，如 image/*）
+    pub mime: Option<Pattern>,
+    /// 按内容识别的文件大类
+    pub kind: Option<FileCategory>,
     pub min_size: Option<u64>,
     pub max_size: Option<u64>,
     pub recursive: bool,
     pub case_insensitive: bool,
+    pub content: Option<String>,
+    pub context: usize,
+    pub dates: DateFilters,
+    pub threads: usize,
+    pub sort: Option<SortKey>,
+    pub reverse: bool,
+    pub limit: Option<usize>,
+    pub output: Option<PathBuf>,
+    pub archives: bool,
+    pub index: bool,
+    pub query: Option<Query>,
+    pub max_depth: Option<usize>,
+    pub min_depth: usize,
+    pub follow_symlinks: bool,
+    pub one_file_system: bool,
+}
+
+/// 索引管理命令（`index build|update|status|remove`）
+#[derive(Debug, Clone)]
+pub enum IndexCommand {
+    /// 完整构建索引
+    Build(PathBuf),
+    /// 增量更新索引（不存在时完整构建）
+    Update(PathBuf),
+    /// 查看索引状态，不指定目录时列出全部索引
+    Status(Option<PathBuf>),
+    /// 删除索引
+    Remove(PathBuf),
+}
+
+impl IndexCommand {
+    /// 构建 index 子命令
+    fn build_clap_command() -> Command {
+        let dir_arg = |required: bool| {
+            Arg::new("dir")
+                .value_name("目录")
+                .help("索引的根目录")
+                .required(required)
+        };
+        Command::new("index")
+            .about("管理文件索引")
+            .subcommand_required(true)
+            .subcommand(
+                Command::new("build")
+                    .about("完整构建索引")
+                    .arg(dir_arg(true)),
+            )
+            .subcommand(
+                Command::new("update")
+                    .about("按目录修改时间增量更新索引")
+                    .arg(dir_arg(true)),
+            )
+            .subcommand(
+                Command::new("status")
+                    .about("查看索引状态")
+                    .arg(dir_arg(false)),
+            )
+            .subcommand(Command::new("remove").about("删除索引").arg(dir_arg(true)))
+    }
+
+    /// 从 index 子命令的参数解析
+    pub fn from_matches(matches: &clap::ArgMatches) -> Result<Self> {
+        let (name, sub) = matches
+            .subcommand()
+            .ok_or_else(|| anyhow!("缺少索引操作（build, update, status, remove）"))?;
+        let dir = sub.get_one::<String>("dir").map(PathBuf::from);
+        if let Some(dir) = &dir {
+            if !dir.is_dir() {
+                return Err(anyhow!("目录不存在: {}", dir.display()));
+            }
+        }
+
+        match (name, dir) {
+            ("build", Some(dir)) => Ok(IndexCommand::Build(dir)),
+            ("update", Some(dir)) => Ok(IndexCommand::Update(dir)),
+            ("status", dir) => Ok(IndexCommand::Status(dir)),
+            ("remove", Some(dir)) => Ok(IndexCommand::Remove(dir)),
+            (other, _) => Err(anyhow!("无效的索引操作: {}", other)),
+        }
+    }
+}
+
+/// 搜索结果排序方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// 按文件名
+    Name,
+    /// 按文件大小
+    Size,
+    /// 按修改时间
+    Mtime,
+    /// 按完整路径
+    Path,
+}
+
+impl SortKey {
+    /// 从参数解析排序方式
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.to_lowercase().as_str() {
+            "name" => Some(SortKey::Name),
+            "size" => Some(SortKey::Size),
+            "mtime" => Some(SortKey::Mtime),
+            "path" => Some(SortKey::Path),
+            _ => None,
+        }
+    }
+}
+
+/// 按时间筛选的条件（after 包含边界，before 不包含）
+#[derive(Debug, Clone, Default)]
+pub struct DateFilters {
+    pub modified_after: Option<SystemTime>,
+    pub modified_before: Option<SystemTime>,
+    pub created_after: Option<SystemTime>,
+    pub created_before: Option<SystemTime>,
+    pub accessed_after: Option<SystemTime>,
+}
+
+impl DateFilters {
+    /// 是否未设置任何时间条件
+    pub fn is_empty(&self) -> bool {
+        self.modified_after.is_none()
+            && self.modified_before.is_none()
+            && self.created_after.is_none()
+            && self.created_before.is_none()
+            && self.accessed_after.is_none()
+    }
+
+    /// 检查文件时间是否满足条件（系统不支持的时间视为不满足）
+    pub fn matches(&self, metadata: &Metadata) -> bool {
+        self.matches_times(
+            metadata.modified().ok(),
+            metadata.created().ok(),
+            metadata.accessed().ok(),
+        )
+    }
+
+    /// 检查给定的修改、创建、访问时间是否满足条件（None 表示时间不可用）
+    pub fn matches_times(
+        &self,
+        modified: Option<SystemTime>,
+        created: Option<SystemTime>,
+        accessed: Option<SystemTime>,
+    ) -> bool {
+        let in_range =
+            |time: Option<SystemTime>, after: Option<SystemTime>, before: Option<SystemTime>| {
+                if after.is_none() && before.is_none() {
+                    return true;
+                }
+                match time {
+                    Some(time) => {
+                        after.is_none_or(|after| time >= after)
+                            && before.is_none_or(|before| time < before)
+                    }
+                    None => false,
+                }
+            };
+
+        in_range(modified, self.modified_after, self.modified_before)
+            && in_range(created, self.created_after, self.created_before)
+            && in_range(accessed, self.accessed_after, None)
+    }
 }
 
 impl BatchSearchConfig {
@@ -19,6 +194,8 @@ impl BatchSearchConfig {
     pub fn build_clap_command() -> Command {
         Command::new("search")
             .about("批量搜索工具")
+            .args_conflicts_with_subcommands(true)
+            .subcommand(IndexCommand::build_clap_command())
             .arg(
                 Arg::new("path")
                     .short('d')
@@ -40,19 +217,31 @@ impl BatchSearchConfig {
                     .short('t')
                     .long("type")
                     .value_name("文件类型")
-                    .help("按文件类型筛选（如 txt, jpg, pdf）"),
+                    .help("按扩展名筛选，多个用逗号分隔（如 jpg,png,webp）"),
+            )
+            .arg(
+                Arg::new("mime")
+                    .long("mime")
+                    .value_name("MIME类型")
+                    .help("按文件内容识别的 MIME 类型筛选（支持通配符，如 image/*）"),
+            )
+            .arg(
+                Arg::new("kind")
+                    .long("kind")
+                    .value_name("大类")
+                    .help("按文件内容识别的大类筛选: image, video, audio, archive, document, code"),
             )
             .arg(
                 Arg::new("min-size")
                     .long("min-size")
                     .value_name("最小大小")
-                    .help("最小文件大小（字节）"),
+                    .help("最小文件大小（如 1024、10K、5MB、1.5GiB）"),
             )
             .arg(
                 Arg::new("max-size")
                     .long("max-size")
                     .value_name("最大大小")
-                    .help("最大文件大小（字节）"),
+                    .help("最大文件大小（如 1024、10K、5MB、1.5GiB）"),
             )
             .arg(
                 Arg::new("recursive")
@@ -61,6 +250,31 @@ impl BatchSearchConfig {
                     .help("递归搜索子目录")
                     .action(clap::ArgAction::SetTrue),
             )
+            .arg(
+                Arg::new("max-depth")
+                    .long("max-depth")
+                    .value_name("层数")
+                    .help("最多进入的目录层数（搜索目录下的文件为第1层，指定后自动递归）"),
+            )
+            .arg(
+                Arg::new("min-depth")
+                    .long("min-depth")
+                    .value_name("层数")
+                    .help("只显示不浅于此层数的文件（指定后自动递归）"),
+            )
+            .arg(
+                Arg::new("follow-symlinks")
+                    .short('L')
+                    .long("follow-symlinks")
+                    .help("跟随指向目录的符号链接（自动跳过循环）")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("one-file-system")
+                    .long("one-file-system")
+                    .help("不进入其他文件系统（挂载点、网络盘）")
+                    .action(clap::ArgAction::SetTrue),
+            )
             .arg(
                 Arg::new("case")
                     .short('c')
@@ -68,6 +282,133 @@ impl BatchSearchConfig {
                     .help("不区分大小写匹配")
                     .action(clap::ArgAction::SetTrue),
             )
+            .arg(
+                Arg::new("content")
+                    .long("content")
+                    .value_name("正则")
+                    .help("搜索文件内容（正则表达式）"),
+            )
+            .arg(
+                Arg::new("context")
+                    .short('C')
+                    .long("context")
+                    .value_name("行数")
+                    .help("显示匹配行前后的行数（默认0）")
+                    .default_value("0")
+                    .requires("content"),
+            )
+            .arg(
+                Arg::new("threads")
+                    .short('j')
+                    .long("threads")
+                    .value_name("线程数")
+                    .help("遍历线程数（默认按CPU核心数，至少4）"),
+            )
+            .arg(
+                Arg::new("sorted")
+                    .long("sorted")
+                    .help("搜索结束后按路径排序输出（等同于 --sort path）")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with("sort"),
+            )
+            .arg(
+                Arg::new("sort")
+                    .long("sort")
+                    .value_name("排序方式")
+                    .help("搜索结束后排序输出: name, size, mtime, path（默认升序）"),
+            )
+            .arg(
+                Arg::new("reverse")
+                    .long("reverse")
+                    .help("按降序排序")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("limit")
+                    .long("limit")
+                    .value_name("数量")
+                    .help("最多显示的结果数（未排序时达到数量即停止搜索）"),
+            )
+            .arg(
+                Arg::new("output")
+                    .short('o')
+                    .long("output")
+                    .value_name("结果文件")
+                    .help("导出结果到文件（.csv, .json, .txt，包含大小和时间）"),
+            )
+            .arg(
+                Arg::new("archives")
+                    .long("archives")
+                    .help("同时搜索 zip/tar.gz/tar.bz2 压缩包内的文件")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("query")
+                    .short('q')
+                    .long("query")
+                    .value_name("表达式")
+                    .help("查询表达式，如 \"name:*.log and (size>10M or mtime<30d)\""),
+            )
+            .arg(
+                Arg::new("index")
+                    .long("index")
+                    .help("使用已构建的索引搜索（不遍历磁盘）")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with_all([
+                        "content",
+                        "archives",
+                        "follow-symlinks",
+                        "one-file-system",
+                        "threads",
+                        "created-after",
+                        "created-before",
+                        "accessed",
+                    ]),
+            )
+            .arg(
+                Arg::new("newer")
+                    .long("newer")
+                    .value_name("时间")
+                    .help("修改时间在此之后（如 3d, 2w 或 2024-01-31）")
+                    .conflicts_with("modified-after"),
+            )
+            .arg(
+                Arg::new("older")
+                    .long("older")
+                    .value_name("时间")
+                    .help("修改时间在此之前（如 1y 或 2024-01-31）")
+                    .conflicts_with("modified-before"),
+            )
+            .arg(
+                Arg::new("modified-after")
+                    .long("modified-after")
+                    .value_name("时间")
+                    .help("修改时间不早于"),
+            )
+            .arg(
+                Arg::new("modified-before")
+                    .long("modified-before")
+                    .value_name("时间")
+                    .help("修改时间早于"),
+            )
+            .arg(
+                Arg::new("created-after")
+                    .long("created-after")
+                    .value_name("时间")
+                    .help("创建时间不早于"),
+            )
+            .arg(
+                Arg::new("created-before")
+                    .long("created-before")
+                    .value_name("时间")
+                    .help("创建时间早于"),
+            )
+            .arg(
+                Arg::new("accessed")
+                    .long("accessed")
+                    .value_name("时间")
+                    .help("在此之后访问过（如 7d）"),
+            )
     }
 
     /// 修复Windows路径中的反斜杠问题
@@ -98,26 +439,129 @@ impl BatchSearchConfig {
             .map(|s| s.to_string())
             .unwrap_or_else(|| "*".to_string());
 
-        let file_type = matches.get_one::<String>("type").cloned();
-        let recursive = matches.get_flag("recursive");
+        let file_types = matches
+            .get_one::<String>("type")
+            .map(|text| parse::parse_extensions(text))
+            .transpose()?
+            .unwrap_or_default();
+        let mime = matches
+            .get_one::<String>("mime")
+            .map(|text| {
+                Pattern::new(&text.to_lowercase())
+                    .map_err(|e| anyhow!("MIME 类型模式无效: {} ({})", text, e))
+            })
+            .transpose()?;
+        let kind = matches
+            .get_one::<String>("kind")
+            .map(|text| {
+                FileCategory::parse(text).ok_or_else(|| {
+                    anyhow!(
+                        "无效的文件大类: {}（可用 image, video, audio, archive, document, code）",
+                        text
+                    )
+                })
+            })
+            .transpose()?;
+        let depth_arg = |name: &str| -> Result<Option<usize>> {
+            matches
+                .get_one::<String>(name)
+                .map(|s| s.parse::<usize>())
+                .transpose()
+                .map_err(|_| anyhow!("目录层数必须是数字"))
+        };
+        let max_depth = depth_arg("max-depth")?;
+        let min_depth = depth_arg("min-depth")?;
+        let recursive = matches.get_flag("recursive") || max_depth.is_some() || min_depth.is_some();
+        let min_depth = min_depth.unwrap_or(0);
+        let follow_symlinks = matches.get_flag("follow-symlinks");
+        let one_file_system = matches.get_flag("one-file-system");
         let case_insensitive = matches.get_flag("case");
 
         // 解析文件大小参数
         let min_size = matches
             .get_one::<String>("min-size")
-            .and_then(|s| s.parse::<u64>().ok());
+            .map(|text| parse::parse_size(text))
+            .transpose()?;
         let max_size = matches
             .get_one::<String>("max-size")
-            .and_then(|s| s.parse::<u64>().ok());
+            .map(|text| parse::parse_size(text))
+            .transpose()?;
+
+        let content = matches.get_one::<String>("content").cloned();
+        let context = matches
+            .get_one::<String>("context")
+            .map(|s| s.parse::<usize>())
+            .transpose()
+            .map_err(|_| anyhow!("上下文行数必须是数字"))?
+            .unwrap_or(0);
+
+        let time_arg = |names: &[&str]| -> Result<Option<SystemTime>> {
+            names
+                .iter()
+                .find_map(|name| matches.get_one::<String>(name))
+                .map(|text| parse::parse_time_point(text))
+                .transpose()
+        };
+        let dates = DateFilters {
+            modified_after: time_arg(&["newer", "modified-after"])?,
+            modified_before: time_arg(&["older", "modified-before"])?,
+            created_after: time_arg(&["created-after"])?,
+            created_before: time_arg(&["created-before"])?,
+            accessed_after: time_arg(&["accessed"])?,
+        };
+
+        let threads = matches
+            .get_one::<String>("threads")
+            .map(|s| s.parse::<usize>())
+            .transpose()
+            .map_err(|_| anyhow!("线程数必须是数字"))?
+            .unwrap_or_else(walker::default_threads);
+        let sort = match matches.get_one::<String>("sort") {
+            Some(text) => Some(SortKey::parse(text).ok_or_else(|| {
+                anyhow!("无效的排序方式: {}（可用 name, size, mtime, path）", text)
+            })?),
+            None if matches.get_flag("sorted") => Some(SortKey::Path),
+            None => None,
+        };
+        let reverse = matches.get_flag("reverse");
+        let limit = matches
+            .get_one::<String>("limit")
+            .map(|s| s.parse::<usize>())
+            .transpose()
+            .map_err(|_| anyhow!("结果数量上限必须是数字"))?;
+        let output = matches.get_one::<String>("output").map(PathBuf::from);
+        let archives = matches.get_flag("archives");
+        let index = matches.get_flag("index");
+        let query = matches
+            .get_one::<String>("query")
+            .map(|text| Query::parse(text, case_insensitive))
+            .transpose()?;
 
         let config = Self {
             path,
             name_pattern,
-            file_type,
+            file_types,
+            mime,
+            kind,
             min_size,
             max_size,
             recursive,
             case_insensitive,
+            content,
+            context,
+            dates,
+            threads,
+            sort,
+            reverse,
+            limit,
+            output,
+            archives,
+            index,
+            query,
+            max_depth,
+            min_depth,
+            follow_symlinks,
+            one_file_system,
         };
 
         config.validate()?;
@@ -138,6 +582,70 @@ impl BatchSearchConfig {
             return Err(anyhow!("文件名匹配模式不能为空"));
         }
 
+        if let Some(content) = &self.content {
+            if content.is_empty() {
+                return Err(anyhow!("内容搜索的正则表达式不能为空"));
+            }
+        }
+
+        if self.threads == 0 || self.threads > 64 {
+            return Err(anyhow!("线程数必须在1到64之间"));
+        }
+
+        if self.max_depth == Some(0) {
+            return Err(anyhow!("最大目录层数必须大于0"));
+        }
+
+        if let Some(max_depth) = self.max_depth {
+            if self.min_depth > max_depth {
+                return Err(anyhow!("最小目录层数不能大于最大目录层数"));
+            }
+        }
+
+        if self.limit == Some(0) {
+            return Err(anyhow!("结果数量上限必须大于0"));
+        }
+
+        if self.reverse && self.sort.is_none() {
+            return Err(anyhow!("--reverse 需要与 --sort 一起使用"));
+        }
+
+        if let Some(output) = &self.output {
+            ExportFormat::from_path(output)?;
+        }
+
+        if self.context > 100 {
+            return Err(anyhow!("上下文行数不能超过100"));
+        }
+
+        // 索引只记录修改时间，创建和访问时间条件在索引中无法判断
+        if self.index
+            && self
+                .query
+                .as_ref()
+                .is_some_and(Query::uses_created_or_accessed)
+        {
+            return Err(anyhow!(
+                "--index 不支持 ctime/atime 查询条件（索引只记录修改时间），请去掉 --index 直接搜索"
+            ));
+        }
+
+        let ranges = [
+            (
+                "修改",
+                self.dates.modified_after,
+                self.dates.modified_before,
+            ),
+            ("创建", self.dates.created_after, self.dates.created_before),
+        ];
+        for (what, after, before) in ranges {
+            if let (Some(after), Some(before)) = (after, before) {
+                if after >= before {
+                    return Err(anyhow!("{}时间范围无效：起始时间必须早于结束时间", what));
+                }
+            }
+        }
+
         if let (Some(min), Some(max)) = (self.min_size, self.max_size) {
             if min > max {
                 return Err(anyhow!("最小文件大小不能大于最大文件大小"));
@@ -147,3 +655,38 @@ impl BatchSearchConfig {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<BatchSearchConfig> {
+        let dir = std::env::temp_dir().to_string_lossy().into_owned();
+        let mut argv = vec!["search", "-d", &dir];
+        argv.extend_from_slice(args);
+        let matches = BatchSearchConfig::build_clap_command().try_get_matches_from(argv)?;
+        BatchSearchConfig::from_matches(&matches)
+    }
+
+    #[test]
+    fn index_conflicts_with_walk_only_options() {
+        for args in [
+            ["--index", "--follow-symlinks"].as_slice(),
+            &["--index", "--one-file-system"],
+            &["--index", "-j", "4"],
+            &["--index", "--created-after", "3d"],
+            &["--index", "--accessed", "7d"],
+        ] {
+            assert!(parse(args).is_err(), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn index_rejects_created_or_accessed_query_terms() {
+        let error = parse(&["--index", "-q", "size>1k or ctime<3d"]).unwrap_err();
+        assert!(error.to_string().contains("--index"));
+        assert!(parse(&["--index", "-q", "not atime<3d"]).is_err());
+        assert!(parse(&["--index", "-q", "mtime<3d and size>1k"]).is_ok());
+        assert!(parse(&["-q", "ctime<3d"]).is_ok());
+    }
+}
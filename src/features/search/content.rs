@@ -0,0 +1,283 @@
+use crate::error::{HekitError, HekitResult};
+use crate::features::encoding::{self, TextEncoding};
+use regex::{Regex, RegexBuilder};
+use std::fs;
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 内容搜索跳过的最大文件大小（64 MiB）
+const MAX_CONTENT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// 高亮匹配内容的终端颜色
+const HIGHLIGHT_START: &str = "\x1b[1;31m";
+const HIGHLIGHT_END: &str = "\x1b[0m";
+
+/// 文件内容匹配器
+pub struct ContentMatcher {
+    regex: Regex,
+    context: usize,
+    highlight: bool,
+    binary_skipped: AtomicUsize,
+    large_skipped: AtomicUsize,
+    total_matches: AtomicUsize,
+}
+
+/// 单个文件的内容匹配结果
+pub struct FileMatch {
+    pub path: PathBuf,
+    pub encoding: TextEncoding,
+    /// 匹配次数
+    pub match_count: usize,
+    /// 要显示的行：(行号, 是否为匹配行, 内容)
+    pub lines: Vec<(usize, bool, String)>,
+}
+
+impl ContentMatcher {
+    /// 创建内容匹配器（context 为匹配行前后显示的行数）
+    pub fn new(pattern: &str, case_insensitive: bool, context: usize) -> HekitResult<Self> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|e| HekitError::ArgumentParse(format!("内容正则表达式无效: {}", e)))?;
+
+        Ok(Self {
+            regex,
+            context,
+            highlight: std::io::stdout().is_terminal() && crate::utils::supports_color(),
+            binary_skipped: AtomicUsize::new(0),
+            large_skipped: AtomicUsize::new(0),
+            total_matches: AtomicUsize::new(0),
+        })
+    }
+
+    /// 搜索文件内容，无匹配、二进制文件或无法读取时返回 None
+    pub fn search(&self, path: &Path) -> Option<FileMatch> {
+        let size = fs::metadata(path).ok()?.len();
+        if size > MAX_CONTENT_SIZE {
+            self.large_skipped.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+
+        let bytes = fs::read(path).ok()?;
+        self.search_bytes(path, &bytes)
+    }
+
+    /// 搜索压缩包条目等流式内容（path 仅用于显示）
+    pub fn search_reader(
+        &self,
+        path: &Path,
+        size: u64,
+        reader: &mut dyn Read,
+    ) -> Option<FileMatch> {
+        if size > MAX_CONTENT_SIZE {
+            self.large_skipped.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+
+        let mut bytes = Vec::with_capacity(size as usize);
+        reader.read_to_end(&mut bytes).ok()?;
+        self.search_bytes(path, &bytes)
+    }
+
+    /// 在已读取的内容中查找匹配行
+    fn search_bytes(&self, path: &Path, bytes: &[u8]) -> Option<FileMatch> {
+        let (text, encoding) = match encoding::decode(bytes) {
+            Some(decoded) => decoded,
+            None => {
+                self.binary_skipped.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+        };
+
+        let all_lines: Vec<&str> = text.lines().collect();
+        let mut match_count = 0;
+        let mut matched = Vec::new();
+        for (index, line) in all_lines.iter().enumerate() {
+            let count = self.regex.find_iter(line).count();
+            if count > 0 {
+                match_count += count;
+                matched.push(index);
+            }
+        }
+        if matched.is_empty() {
+            return None;
+        }
+
+        // 合并匹配行及其上下文，按行号顺序输出
+        let mut lines = Vec::new();
+        let mut next_line = 0;
+        for &index in &matched {
+            let start = index.saturating_sub(self.context).max(next_line);
+            let end = (index + self.context).min(all_lines.len() - 1);
+            for (line_index, line) in all_lines.iter().enumerate().take(end + 1).skip(start) {
+                let is_match = matched.binary_search(&line_index).is_ok();
+                lines.push((line_index + 1, is_match, line.to_string()));
+            }
+            next_line = next_line.max(end + 1);
+        }
+
+        self.total_matches.fetch_add(match_count, Ordering::Relaxed);
+        Some(FileMatch {
+            path: path.to_path_buf(),
+            encoding,
+            match_count,
+            lines,
+        })
+    }
+
+    /// 打印文件的匹配结果（grep 风格：匹配行用 `:`，上下文行用 `-`，不连续处用 `--` 分隔）
+    pub fn print(&self, file_match: &FileMatch) {
+        let encoding = match file_match.encoding {
+            TextEncoding::Utf8 => String::new(),
+            other => format!(" [{}]", other.name()),
+        };
+        println!(
+            "{} ({} 处匹配){}",
+            file_match.path.display(),
+            file_match.match_count,
+            encoding
+        );
+
+        let mut previous = None;
+        for (line_no, is_match, text) in &file_match.lines {
+            if previous.is_some_and(|prev| line_no - prev > 1) {
+                println!("  --");
+            }
+            if *is_match {
+                println!("  {}: {}", line_no, self.highlight_line(text));
+            } else {
+                println!("  {}- {}", line_no, text);
+            }
+            previous = Some(*line_no);
+        }
+    }
+
+    /// 高亮匹配内容
+    fn highlight_line(&self, line: &str) -> String {
+        if !self.highlight {
+            return line.to_string();
+        }
+        self.regex
+            .replace_all(line, |caps: &regex::Captures| {
+                format!("{}{}{}", HIGHLIGHT_START, &caps[0], HIGHLIGHT_END)
+            })
+            .into_owned()
+    }
+
+    /// 因二进制内容跳过的文件数
+    pub fn binary_skipped(&self) -> usize {
+        self.binary_skipped.load(Ordering::Relaxed)
+    }
+
+    /// 因文件过大跳过的文件数
+    pub fn large_skipped(&self) -> usize {
+        self.large_skipped.load(Ordering::Relaxed)
+    }
+
+    /// 匹配总次数
+    pub fn total_matches(&self) -> usize {
+        self.total_matches.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(matcher: &ContentMatcher, bytes: &[u8]) -> Option<FileMatch> {
+        matcher.search_reader(Path::new("test.txt"), bytes.len() as u64, &mut &bytes[..])
+    }
+
+    fn line_numbers(file_match: &FileMatch) -> Vec<(usize, bool)> {
+        file_match
+            .lines
+            .iter()
+            .map(|(line_no, is_match, _)| (*line_no, *is_match))
+            .collect()
+    }
+
+    #[test]
+    fn overlapping_context_windows_are_merged() {
+        let text = (1..=10)
+            .map(|i| {
+                if i == 3 || i == 5 {
+                    format!("hit {}", i)
+                } else {
+                    format!("line {}", i)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let matcher = ContentMatcher::new("hit", false, 2).unwrap();
+        let file_match = search(&matcher, text.as_bytes()).unwrap();
+
+        assert_eq!(file_match.match_count, 2);
+        // 第3行和第5行的上下文重叠，每行只输出一次
+        assert_eq!(
+            line_numbers(&file_match),
+            [
+                (1, false),
+                (2, false),
+                (3, true),
+                (4, false),
+                (5, true),
+                (6, false),
+                (7, false)
+            ]
+        );
+        assert_eq!(file_match.lines[2].2, "hit 3");
+        assert_eq!(matcher.total_matches(), 2);
+    }
+
+    #[test]
+    fn context_is_clamped_at_file_edges() {
+        let matcher = ContentMatcher::new("x", false, 3).unwrap();
+        let file_match = search(&matcher, b"x\na\nb\nx x").unwrap();
+        assert_eq!(file_match.match_count, 3);
+        assert_eq!(
+            line_numbers(&file_match),
+            [(1, true), (2, false), (3, false), (4, true)]
+        );
+    }
+
+    #[test]
+    fn gbk_input_is_decoded() {
+        let (bytes, _, _) = encoding_rs::GBK.encode("第一行\n中文内容\n");
+        let matcher = ContentMatcher::new("中文", false, 0).unwrap();
+        let file_match = search(&matcher, &bytes).unwrap();
+        assert_eq!(file_match.encoding, TextEncoding::Gbk);
+        assert_eq!(file_match.lines, [(2, true, "中文内容".to_string())]);
+    }
+
+    #[test]
+    fn utf16_input_is_decoded() {
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "abc\r\nfind me\r\n".encode_utf16() {
+            bytes.extend(unit.to_le_bytes());
+        }
+        let matcher = ContentMatcher::new("find", false, 0).unwrap();
+        let file_match = search(&matcher, &bytes).unwrap();
+        assert_eq!(file_match.encoding, TextEncoding::Utf16Le);
+        assert_eq!(file_match.lines, [(2, true, "find me".to_string())]);
+    }
+
+    #[test]
+    fn binary_input_is_skipped() {
+        let matcher = ContentMatcher::new("ELF", false, 0).unwrap();
+        assert!(search(&matcher, b"\x7fELF\x02\x01\x01\x00\x00\x00ELF").is_none());
+        assert_eq!(matcher.binary_skipped(), 1);
+        assert_eq!(matcher.total_matches(), 0);
+    }
+
+    #[test]
+    fn oversized_input_is_skipped_without_reading() {
+        let matcher = ContentMatcher::new("a", false, 0).unwrap();
+        let mut reader = std::io::repeat(b'a');
+        assert!(matcher
+            .search_reader(Path::new("big.txt"), MAX_CONTENT_SIZE + 1, &mut reader)
+            .is_none());
+        assert_eq!(matcher.large_skipped(), 1);
+        assert_eq!(matcher.binary_skipped(), 0);
+    }
+}
@@ -1,10 +1,22 @@
 use crate::error::{HekitError, HekitResult};
+use crate::features::export::{self, ExportFormat};
+use crate::features::magic::{self, FileCategory};
+use crate::features::search::archive::{self, ArchiveKind};
+use crate::features::search::config::{BatchSearchConfig, DateFilters, IndexCommand, SortKey};
+use crate::features::search::content::{ContentMatcher, FileMatch};
+use crate::features::search::index::{self, FileIndex};
+use crate::features::search::query::{Query, QueryTarget};
+use crate::features::search::walker::{self, WalkOptions};
+use crate::progress::ProgressManager;
+use crate::utils;
 use glob::Pattern;
-use std::collections::VecDeque;
 use std::fs;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 #[cfg(windows)]
 use winapi::um::winuser::GetAsyncKeyState;
@@ -12,133 +24,164 @@ use winapi::um::winuser::GetAsyncKeyState;
 /// 批量搜索核心逻辑
 pub struct BatchSearchCore;
 
-impl BatchSearchCore {
-    /// 执行文件搜索 - 简化显示版本
-    pub fn search_files(
-        config: &crate::features::search::config::BatchSearchConfig,
-    ) -> HekitResult<(Vec<PathBuf>, usize)> {
-        // 预编译文件名匹配模式
-        let name_pattern = if config.case_insensitive {
-            Pattern::new(&config.name_pattern.to_lowercase())
-                .map_err(|e| HekitError::ArgumentParse(format!("文件名模式解析失败: {}", e)))?
-        } else {
-            Pattern::new(&config.name_pattern)
-                .map_err(|e| HekitError::ArgumentParse(format!("文件名模式解析失败: {}", e)))?
-        };
+/// 预编译的文件匹配条件
+struct SearchFilters {
+    name_pattern: Pattern,
+    /// 扩展名列表（为空时不限制）
+    file_types: Vec<String>,
+    /// 按内容识别的 MIME 类型
+    mime: Option<Pattern>,
+    /// 按内容识别的文件大类
+    kind: Option<FileCategory>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    case_insensitive: bool,
+    dates: DateFilters,
+    content: Option<ContentMatcher>,
+    /// 查询表达式
+    query: Option<Query>,
+    /// 是否搜索压缩包内的文件
+    archives: bool,
+    /// 无法读取的压缩包数
+    archive_errors: AtomicUsize,
+}
 
-        // 预编译文件扩展名（如果指定）- 修复：统一处理大小写
-        let file_type_lower = config.file_type.as_ref().map(|s| {
-            if config.case_insensitive {
-                s.to_lowercase()
-            } else {
-                s.clone()
-            }
-        });
+/// 单条搜索结果
+struct SearchHit {
+    path: PathBuf,
+    size: u64,
+    modified: Option<SystemTime>,
+    created: Option<SystemTime>,
+    /// 内容搜索的匹配详情
+    content: Option<FileMatch>,
+}
 
-        // 使用线程安全的共享数据结构
-        let results = Arc::new(Mutex::new(Vec::new()));
-        let skipped_dirs = Arc::new(Mutex::new(0usize));
-        let processed_dirs = Arc::new(Mutex::new(0usize));
-        let found_files = Arc::new(Mutex::new(0usize));
+impl BatchSearchCore {
+    /// 执行文件搜索 - 简化显示版本
+    pub fn search_files(config: &BatchSearchConfig) -> HekitResult<(Vec<PathBuf>, usize)> {
+        let filters = Self::build_filters(config)?;
+        if config.index {
+            return Self::search_with_index(config, &filters);
+        }
 
         // 显示搜索开始信息
-        println!(
-            "搜索: {} (模式: {})",
-            config.path.display(),
-            config.name_pattern
-        );
+        if let Some(query) = &config.query {
+            println!("查询: {}", query);
+        }
+        if let Some(kind) = config.kind {
+            println!("按内容识别类型: {}", kind.label());
+        }
+        if let Some(mime) = &config.mime {
+            println!("按内容识别 MIME: {}", mime);
+        }
+        match &config.content {
+            Some(content) => println!(
+                "搜索: {} (模式: {}, 内容: {})",
+                config.path.display(),
+                config.name_pattern,
+                content
+            ),
+            None => println!(
+                "搜索: {} (模式: {})",
+                config.path.display(),
+                config.name_pattern
+            ),
+        }
 
         let start_time = Instant::now();
-
-        // 使用广度优先搜索（BFS）
-        let mut queue = VecDeque::new();
-        queue.push_back(config.path.clone());
-
-        // 中断标志
+        let options = WalkOptions {
+            threads: config.threads,
+            recursive: config.recursive,
+            max_depth: config.max_depth,
+            min_depth: config.min_depth,
+            follow_symlinks: config.follow_symlinks,
+            one_file_system: config.one_file_system,
+        };
+        let cancel = AtomicBool::new(false);
+        let mut hits = Vec::new();
         let mut interrupted = false;
-        let mut last_check_time = Instant::now();
-
-        while let Some(current_dir) = queue.pop_front() {
-            // 检查是否被中断
-            if interrupted {
-                println!("搜索被中断");
-                break;
-            }
+        let mut limit_reached = false;
+
+        // 工作线程并行遍历并匹配文件，结果通过通道实时传回主线程输出
+        let (sender, receiver) = mpsc::channel::<SearchHit>();
+        let stats = thread::scope(|scope| {
+            let (shared_filters, shared_options, shared_cancel) = (&filters, &options, &cancel);
+            // 发送端随遍历线程结束而释放，通道随之关闭
+            let walker = scope.spawn(move || {
+                walker::walk(&config.path, shared_options, shared_cancel, |path| {
+                    if let Some(hit) = Self::match_file(path, shared_filters) {
+                        let _ = sender.send(hit);
+                    }
+                    if shared_filters.archives {
+                        if let Some(kind) = ArchiveKind::detect(path) {
+                            Self::match_archive(path, kind, shared_filters, |hit| {
+                                let _ = sender.send(hit);
+                            });
+                        }
+                    }
+                })
+            });
+
+            loop {
+                match receiver.recv_timeout(Duration::from_millis(100)) {
+                    Ok(hit) => {
+                        if limit_reached {
+                            continue;
+                        }
+                        // 排序输出时先收集，结束后统一显示
+                        if config.sort.is_none() {
+                            Self::print_hit(&hit, &filters);
+                        }
+                        hits.push(hit);
+                        // 未排序时达到数量上限即可停止遍历
+                        if config.sort.is_none() && config.limit.is_some_and(|l| hits.len() >= l) {
+                            limit_reached = true;
+                            cancel.store(true, Ordering::Relaxed);
+                        }
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
 
-            // 每处理10个目录检查一次键盘输入（避免频繁检查影响性能）
-            let processed = *processed_dirs.lock().unwrap();
-            if processed % 10 == 0 && last_check_time.elapsed() > Duration::from_millis(100) {
-                if Self::check_keyboard_input() {
+                if !interrupted && Self::check_keyboard_input() {
                     interrupted = true;
+                    cancel.store(true, Ordering::Relaxed);
                     println!("搜索被中断");
-                    break;
-                }
-                last_check_time = Instant::now();
-            }
-
-            // 尝试读取目录
-            let entries = match fs::read_dir(&current_dir) {
-                Ok(entries) => entries,
-                Err(_) => {
-                    *skipped_dirs.lock().unwrap() += 1;
-                    continue;
-                }
-            };
-
-            *processed_dirs.lock().unwrap() += 1;
-
-            // 批量处理目录项
-            let mut subdirs = Vec::new();
-            let mut files = Vec::new();
-
-            for entry in entries {
-                let entry = match entry {
-                    Ok(entry) => entry,
-                    Err(_) => continue,
-                };
-
-                let path = entry.path();
-
-                if path.is_dir() {
-                    if config.recursive {
-                        subdirs.push(path);
-                    }
-                } else {
-                    files.push(path);
                 }
             }
 
-            // 批量处理文件匹配
-            let current_found = Self::process_files_sequential_simple(
-                &files,
-                &name_pattern,
-                &file_type_lower,
-                config.min_size,
-                config.max_size,
-                config.case_insensitive,
-                results.clone(),
-            );
-
-            *found_files.lock().unwrap() += current_found;
-
-            // 添加子目录到队列
-            for subdir in subdirs {
-                queue.push_back(subdir);
-            }
-        }
-
-        let final_results = results.lock().unwrap().clone();
-        let final_skipped = *skipped_dirs.lock().unwrap();
-        let final_found = *found_files.lock().unwrap();
+            walker.join().map_err(|panic| {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                HekitError::System(format!("搜索线程异常退出，结果不完整: {}", message))
+            })
+        })?;
+
+        Self::finish_hits(config, &filters, &mut hits)?;
+
+        let final_found = hits.len();
+        let final_skipped = stats.skipped;
+        let final_results: Vec<PathBuf> = hits.into_iter().map(|hit| hit.path).collect();
         let elapsed_time = start_time.elapsed();
 
         // 简化最终统计信息显示 - 只在搜索结束时显示一次
-        if !interrupted {
+        if limit_reached {
             println!(
-                "\n搜索完成! 找到 {} 个文件 (耗时: {:.2}秒)",
+                "\n已达到结果数量上限，提前停止搜索! 找到 {} 个文件 (耗时: {:.2}秒)",
                 final_found,
                 elapsed_time.as_secs_f64()
             );
+        } else if !interrupted {
+            println!(
+                "\n搜索完成! 找到 {} 个文件 (耗时: {:.2}秒, 目录: {}, 线程: {})",
+                final_found,
+                elapsed_time.as_secs_f64(),
+                stats.dirs,
+                options.threads
+            );
         } else {
             println!(
                 "\n搜索被中断! 找到 {} 个文件 (耗时: {:.2}秒)",
@@ -147,14 +190,305 @@ impl BatchSearchCore {
             );
         }
 
+        if let Some(content) = &filters.content {
+            println!("内容共匹配 {} 处", content.total_matches());
+            if content.binary_skipped() > 0 {
+                println!("跳过 {} 个二进制文件", content.binary_skipped());
+            }
+            if content.large_skipped() > 0 {
+                println!("跳过 {} 个超过64MB的文件", content.large_skipped());
+            }
+        }
+
+        let archive_errors = filters.archive_errors.load(Ordering::Relaxed);
+        if archive_errors > 0 {
+            println!("{} 个压缩包无法读取（可能已损坏）", archive_errors);
+        }
+
         // 显示跳过的目录信息（如果有）- 只在搜索完成后显示
         if final_skipped > 0 {
             println!("因权限问题跳过 {} 个目录", final_skipped);
         }
+        if stats.loops > 0 {
+            println!("跳过 {} 个符号链接循环或重复的目录", stats.loops);
+        }
+        if stats.other_fs > 0 {
+            println!("跳过 {} 个位于其他文件系统的目录", stats.other_fs);
+        }
 
         Ok((final_results, final_skipped))
     }
 
+    /// 排序输出并导出结果（未排序的结果已在搜索过程中输出）
+    fn finish_hits(
+        config: &BatchSearchConfig,
+        filters: &SearchFilters,
+        hits: &mut Vec<SearchHit>,
+    ) -> HekitResult<()> {
+        if let Some(key) = config.sort {
+            Self::sort_hits(hits, key, config.reverse);
+            if let Some(limit) = config.limit {
+                hits.truncate(limit);
+            }
+            for hit in hits.iter() {
+                Self::print_hit(hit, filters);
+            }
+        }
+
+        if let Some(output) = &config.output {
+            Self::export_results(hits, output, filters.content.is_some())?;
+            utils::print_success(&format!("结果已导出: {}", output.display()));
+        }
+        Ok(())
+    }
+
+    /// 在索引中搜索（只支持文件名、类型、大小和修改时间条件，按内容识别类型时读取文件头）
+    fn search_with_index(
+        config: &BatchSearchConfig,
+        filters: &SearchFilters,
+    ) -> HekitResult<(Vec<PathBuf>, usize)> {
+        let start_time = Instant::now();
+        let index = FileIndex::find_covering(&config.path)?.ok_or_else(|| {
+            HekitError::Search(format!(
+                "没有覆盖 {0} 的索引，请先运行: index build {0}",
+                config.path.display()
+            ))
+        })?;
+
+        // 搜索目录在索引中的相对路径
+        let target = fs::canonicalize(&config.path)?;
+        let rel = target
+            .strip_prefix(&index.root)
+            .map(|rel| {
+                rel.components()
+                    .map(|part| part.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .unwrap_or_default();
+
+        println!(
+            "搜索索引: {} (模式: {}, 索引更新于: {})",
+            config.path.display(),
+            config.name_pattern,
+            export::format_time(Some(index.built_at))
+        );
+        Self::report_staleness(&index);
+
+        let mut hits = Vec::new();
+        let base_depth = index::relative_depth(&rel);
+        for (dir_rel, record) in index.dirs_under(&rel, config.recursive) {
+            // 与遍历磁盘时一致：搜索目录下的文件为第1层
+            let depth = index::relative_depth(dir_rel) - base_depth + 1;
+            if depth < config.min_depth || config.max_depth.is_some_and(|max| depth > max) {
+                continue;
+            }
+            let dir = index.full_path(dir_rel);
+            for file in &record.files {
+                let name = Path::new(&file.name);
+                if !Self::quick_filename_check(
+                    name,
+                    &filters.name_pattern,
+                    filters.case_insensitive,
+                ) || !Self::matches_file_type(name, filters)
+                    || !Self::matches_size(file.size, filters)
+                    || !filters.dates.matches_times(file.mtime, None, None)
+                {
+                    continue;
+                }
+                let path = dir.join(&file.name);
+                let target = QueryTarget {
+                    path: &path,
+                    size: file.size,
+                    modified: file.mtime,
+                    created: None,
+                    accessed: None,
+                };
+                if Self::matches_query(&target, filters)
+                    && Self::matches_file_content_type(&path, filters)
+                {
+                    hits.push(SearchHit {
+                        path,
+                        size: file.size,
+                        modified: file.mtime,
+                        created: None,
+                        content: None,
+                    });
+                }
+            }
+        }
+
+        if config.sort.is_none() {
+            if let Some(limit) = config.limit {
+                hits.truncate(limit);
+            }
+            for hit in &hits {
+                Self::print_hit(hit, filters);
+            }
+        }
+        Self::finish_hits(config, filters, &mut hits)?;
+
+        println!(
+            "\n搜索完成! 找到 {} 个文件 (耗时: {:.2}秒, 索引共 {} 个文件)",
+            hits.len(),
+            start_time.elapsed().as_secs_f64(),
+            index.file_count()
+        );
+        Ok((hits.into_iter().map(|hit| hit.path).collect(), 0))
+    }
+
+    /// 快速检查索引是否过期（只比较前两层目录的修改时间）
+    fn report_staleness(index: &FileIndex) {
+        let changed = index.changed_dirs(Some(1));
+        if changed > 0 {
+            utils::print_warning(&format!(
+                "索引可能已过期: {} 个目录在索引后发生变化，运行 index update {} 更新",
+                changed,
+                index.root.display()
+            ));
+            return;
+        }
+
+        let age_days = index
+            .built_at
+            .elapsed()
+            .map_or(0, |age| age.as_secs() / 86400);
+        if age_days > 0 {
+            utils::print_info(&format!(
+                "索引已 {} 天未更新，深层目录的变化可能未包含在内",
+                age_days
+            ));
+        }
+    }
+
+    /// 执行索引管理命令
+    pub fn run_index_command(command: &IndexCommand) -> HekitResult<()> {
+        match command {
+            IndexCommand::Build(dir) | IndexCommand::Update(dir) => {
+                let previous = match command {
+                    IndexCommand::Update(_) => FileIndex::load(dir)?,
+                    _ => None,
+                };
+                let incremental = previous.is_some();
+
+                let start_time = Instant::now();
+                let progress = ProgressManager::create_indeterminate("扫描目录中...");
+                let (index, stats) = FileIndex::scan(dir, previous)?;
+                drop(progress);
+                let path = index.save()?;
+
+                utils::print_success(&format!(
+                    "索引已{}: {} ({} 个文件, {} 个目录, 耗时 {:.2}秒)",
+                    if incremental { "更新" } else { "构建" },
+                    index.root.display(),
+                    index.file_count(),
+                    index.dirs.len(),
+                    start_time.elapsed().as_secs_f64()
+                ));
+                if incremental {
+                    println!(
+                        "重新读取 {} 个目录，沿用 {} 个未变化的目录",
+                        stats.read, stats.reused
+                    );
+                }
+                if stats.skipped > 0 {
+                    utils::print_warning(&format!("因权限问题跳过 {} 个目录", stats.skipped));
+                }
+                println!("索引文件: {}", path.display());
+            }
+            IndexCommand::Status(Some(dir)) => {
+                let index = FileIndex::load(dir)?.ok_or_else(|| {
+                    HekitError::Search(format!("目录没有索引: {}", dir.display()))
+                })?;
+                Self::print_index_summary(&index);
+                let changed = index.changed_dirs(None);
+                if changed > 0 {
+                    utils::print_warning(&format!(
+                        "{} 个目录在索引后发生变化，运行 index update {} 更新",
+                        changed,
+                        index.root.display()
+                    ));
+                } else {
+                    utils::print_success("索引是最新的（文件内容的原地修改需重新构建才能发现）");
+                }
+            }
+            IndexCommand::Status(None) => {
+                let indexes = FileIndex::list_all()?;
+                if indexes.is_empty() {
+                    utils::print_info("还没有索引，使用 index build <目录> 构建");
+                }
+                for index in &indexes {
+                    Self::print_index_summary(index);
+                }
+            }
+            IndexCommand::Remove(dir) => {
+                if FileIndex::remove(dir)? {
+                    utils::print_success(&format!("已删除索引: {}", dir.display()));
+                } else {
+                    utils::print_warning(&format!("目录没有索引: {}", dir.display()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 显示索引概况
+    fn print_index_summary(index: &FileIndex) {
+        println!(
+            "{}  ({} 个文件, {} 个目录, 更新于 {})",
+            index.root.display(),
+            index.file_count(),
+            index.dirs.len(),
+            export::format_time(Some(index.built_at))
+        );
+    }
+
+    /// 预编译匹配条件
+    fn build_filters(config: &BatchSearchConfig) -> HekitResult<SearchFilters> {
+        // 预编译文件名匹配模式
+        let name_pattern = if config.case_insensitive {
+            Pattern::new(&config.name_pattern.to_lowercase())
+                .map_err(|e| HekitError::ArgumentParse(format!("文件名模式解析失败: {}", e)))?
+        } else {
+            Pattern::new(&config.name_pattern)
+                .map_err(|e| HekitError::ArgumentParse(format!("文件名模式解析失败: {}", e)))?
+        };
+
+        // 预编译文件扩展名列表 - 修复：统一处理大小写
+        let file_types = config
+            .file_types
+            .iter()
+            .map(|ext| {
+                if config.case_insensitive {
+                    ext.to_lowercase()
+                } else {
+                    ext.clone()
+                }
+            })
+            .collect();
+
+        Ok(SearchFilters {
+            name_pattern,
+            file_types,
+            mime: config.mime.clone(),
+            kind: config.kind,
+            min_size: config.min_size,
+            max_size: config.max_size,
+            case_insensitive: config.case_insensitive,
+            dates: config.dates.clone(),
+            content: config
+                .content
+                .as_deref()
+                .map(|pattern| {
+                    ContentMatcher::new(pattern, config.case_insensitive, config.context)
+                })
+                .transpose()?,
+            query: config.query.clone(),
+            archives: config.archives,
+            archive_errors: AtomicUsize::new(0),
+        })
+    }
+
     /// 检查键盘输入（Windows系统专用，非阻塞）
     #[cfg(windows)]
     fn check_keyboard_input() -> bool {
@@ -167,16 +501,8 @@ impl BatchSearchCore {
     /// 检查键盘输入（非Windows系统备用方案）
     #[cfg(not(windows))]
     fn check_keyboard_input() -> bool {
-        // 对于非Windows系统，使用简单的标准输入检查
-        use std::io::{self, Read};
-
-        let mut buffer = [0u8; 1];
-        if io::stdin().read(&mut buffer).is_ok() && buffer[0] != 0 {
-            let input = buffer[0] as char;
-            input == 'q' || input == 'Q'
-        } else {
-            false
-        }
+        // 标准输入无法非阻塞读取，读取会卡住搜索，非Windows系统使用 Ctrl+C 中断
+        false
     }
 
     /// 快速文件名检查 - 避免不必要的系统调用
@@ -194,105 +520,277 @@ impl BatchSearchCore {
         name_pattern.matches(&file_name)
     }
 
-    /// 优化后的匹配条件检查 - 修复文件类型比较逻辑
-    fn matches_criteria_optimized(
-        path: &Path,
-        _name_pattern: &Pattern,
-        file_type: &Option<String>,
-        min_size: Option<u64>,
-        max_size: Option<u64>,
-        case_insensitive: bool,
-    ) -> HekitResult<bool> {
-        // 文件名已经在quick_filename_check中检查过，这里跳过
-
-        // 检查文件类型（如果指定）- 修复：正确处理大小写
-        if let Some(expected_type) = file_type {
-            if let Some(actual_ext) = path.extension().and_then(|e| e.to_str()) {
-                let actual = if case_insensitive {
-                    actual_ext.to_lowercase()
-                } else {
-                    actual_ext.to_string()
-                };
+    /// 检查文件类型（扩展名与列表中任意一项相同即可）
+    fn matches_file_type(path: &Path, filters: &SearchFilters) -> bool {
+        if filters.file_types.is_empty() {
+            return true;
+        }
 
-                // 修复：比较时考虑大小写设置
-                let expected = if case_insensitive {
-                    expected_type.to_lowercase()
-                } else {
-                    expected_type.clone()
-                };
+        let Some(actual_ext) = path.extension().and_then(|e| e.to_str()) else {
+            return false;
+        };
+        // 修复：比较时考虑大小写设置（列表已在预编译时统一大小写）
+        let actual = if filters.case_insensitive {
+            actual_ext.to_lowercase()
+        } else {
+            actual_ext.to_string()
+        };
+        filters.file_types.contains(&actual)
+    }
 
-                if actual != expected {
-                    return Ok(false);
-                }
-            } else {
-                return Ok(false);
+    /// 检查文件大小
+    fn matches_size(file_size: u64, filters: &SearchFilters) -> bool {
+        if let Some(min) = filters.min_size {
+            if file_size < min {
+                return false;
             }
         }
 
-        // 检查文件大小（只有在需要时才获取元数据）
-        if min_size.is_some() || max_size.is_some() {
-            let metadata = match fs::metadata(path) {
-                Ok(metadata) => metadata,
-                Err(_) => return Ok(false),
-            };
+        if let Some(max) = filters.max_size {
+            if file_size > max {
+                return false;
+            }
+        }
+
+        true
+    }
 
-            let file_size = metadata.len();
+    /// 检查按内容识别的类型（header 为文件头，name 用于无法识别时按扩展名判断）
+    fn matches_content_type(header: &[u8], name: &Path, filters: &SearchFilters) -> bool {
+        if filters.mime.is_none() && filters.kind.is_none() {
+            return true;
+        }
+        let Some(mime) = magic::classify(header, name) else {
+            return false;
+        };
+        filters
+            .mime
+            .as_ref()
+            .is_none_or(|pattern| pattern.matches(mime))
+            && filters
+                .kind
+                .is_none_or(|kind| FileCategory::from_mime(mime) == Some(kind))
+    }
 
-            if let Some(min) = min_size {
-                if file_size < min {
-                    return Ok(false);
-                }
+    /// 读取磁盘文件的文件头并检查类型（未指定类型条件时不读取文件）
+    fn matches_file_content_type(path: &Path, filters: &SearchFilters) -> bool {
+        if filters.mime.is_none() && filters.kind.is_none() {
+            return true;
+        }
+        let header = fs::File::open(path)
+            .and_then(|mut file| magic::read_header(&mut file))
+            .unwrap_or_default();
+        Self::matches_content_type(&header, path, filters)
+    }
+
+    /// 检查查询表达式（未指定时视为满足）
+    fn matches_query(target: &QueryTarget, filters: &SearchFilters) -> bool {
+        filters
+            .query
+            .as_ref()
+            .is_none_or(|query| query.matches(target))
+    }
+
+    /// 检查单个文件是否满足全部条件（在工作线程中调用）
+    fn match_file(path: &Path, filters: &SearchFilters) -> Option<SearchHit> {
+        // 先做不需要系统调用的检查，通过后再读取元数据
+        if !Self::quick_filename_check(path, &filters.name_pattern, filters.case_insensitive)
+            || !Self::matches_file_type(path, filters)
+        {
+            return None;
+        }
+        let metadata = fs::metadata(path).ok()?;
+        if !Self::matches_size(metadata.len(), filters) || !filters.dates.matches(&metadata) {
+            return None;
+        }
+        let target = QueryTarget {
+            path,
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            created: metadata.created().ok(),
+            accessed: metadata.accessed().ok(),
+        };
+        if !Self::matches_query(&target, filters) || !Self::matches_file_content_type(path, filters)
+        {
+            return None;
+        }
+
+        // 内容搜索开销最大，放在其他条件之后
+        let content = match &filters.content {
+            Some(matcher) => Some(matcher.search(path)?),
+            None => None,
+        };
+
+        Some(SearchHit {
+            path: path.to_path_buf(),
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            created: metadata.created().ok(),
+            content,
+        })
+    }
+
+    /// 检查压缩包内的条目，对每个满足条件的条目调用 emit（条目只有修改时间）
+    fn match_archive<F>(path: &Path, kind: ArchiveKind, filters: &SearchFilters, mut emit: F)
+    where
+        F: FnMut(SearchHit),
+    {
+        let result = archive::for_each_entry(path, kind, |entry| {
+            let entry_name = Path::new(&entry.name);
+            if !Self::quick_filename_check(
+                entry_name,
+                &filters.name_pattern,
+                filters.case_insensitive,
+            ) || !Self::matches_file_type(entry_name, filters)
+                || !Self::matches_size(entry.size, filters)
+                || !filters.dates.matches_times(entry.modified, None, None)
+            {
+                return;
             }
 
-            if let Some(max) = max_size {
-                if file_size > max {
-                    return Ok(false);
-                }
+            let display_path = archive::entry_path(path, &entry.name);
+            let target = QueryTarget {
+                path: &display_path,
+                size: entry.size,
+                modified: entry.modified,
+                created: None,
+                accessed: None,
+            };
+            if !Self::matches_query(&target, filters) {
+                return;
             }
+
+            // 已读取的文件头需要接回条目内容前面，供内容搜索使用
+            let header = if filters.mime.is_some() || filters.kind.is_some() {
+                let Ok(header) = magic::read_header(entry.reader) else {
+                    return;
+                };
+                if !Self::matches_content_type(&header, entry_name, filters) {
+                    return;
+                }
+                header
+            } else {
+                Vec::new()
+            };
+            let mut reader = io::Cursor::new(header).chain(entry.reader);
+
+            let content = match &filters.content {
+                Some(matcher) => {
+                    match matcher.search_reader(&display_path, entry.size, &mut reader) {
+                        Some(file_match) => Some(file_match),
+                        None => return,
+                    }
+                }
+                None => None,
+            };
+
+            emit(SearchHit {
+                path: display_path,
+                size: entry.size,
+                modified: entry.modified,
+                created: None,
+                content,
+            });
+        });
+
+        if result.is_err() {
+            filters.archive_errors.fetch_add(1, Ordering::Relaxed);
         }
+    }
 
-        Ok(true)
+    /// 按指定方式排序结果（相同时按路径排序，保证顺序固定）
+    fn sort_hits(hits: &mut [SearchHit], key: SortKey, reverse: bool) {
+        hits.sort_by(|a, b| {
+            let ordering = match key {
+                SortKey::Name => a.path.file_name().cmp(&b.path.file_name()),
+                SortKey::Size => a.size.cmp(&b.size),
+                SortKey::Mtime => a.modified.cmp(&b.modified),
+                SortKey::Path => std::cmp::Ordering::Equal,
+            }
+            .then_with(|| a.path.cmp(&b.path));
+            if reverse {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
     }
 
-    /// 批量处理文件匹配（简化显示版本）- 只显示找到的文件
-    fn process_files_sequential_simple(
-        files: &[PathBuf],
-        name_pattern: &Pattern,
-        file_type: &Option<String>,
-        min_size: Option<u64>,
-        max_size: Option<u64>,
-        case_insensitive: bool,
-        results: Arc<Mutex<Vec<PathBuf>>>,
-    ) -> usize {
-        // 批量处理文件，避免频繁的锁操作
-        let mut matched_files = Vec::new();
-        let mut found_count = 0;
-
-        for file in files {
-            if Self::quick_filename_check(file, name_pattern, case_insensitive) {
-                if Self::matches_criteria_optimized(
-                    file,
-                    name_pattern,
-                    file_type,
-                    min_size,
-                    max_size,
-                    case_insensitive,
-                )
-                .unwrap_or(false)
-                {
-                    matched_files.push(file.clone());
-                    found_count += 1;
-                    // 只显示找到的文件
-                    println!("{}", file.display());
+    /// 导出搜索结果（内容搜索时附带匹配次数）
+    fn export_results(hits: &[SearchHit], path: &Path, with_matches: bool) -> HekitResult<()> {
+        let match_count = |hit: &SearchHit| hit.content.as_ref().map_or(0, |m| m.match_count);
+
+        let content = match ExportFormat::from_path(path)
+            .map_err(|e| HekitError::UserInput(e.to_string()))?
+        {
+            ExportFormat::Csv => {
+                let mut lines = vec![if with_matches {
+                    "path,size,modified,created,matches".to_string()
+                } else {
+                    "path,size,modified,created".to_string()
+                }];
+                for hit in hits {
+                    let mut line = format!(
+                        "{},{},{},{}",
+                        export::csv_field(&hit.path.to_string_lossy()),
+                        hit.size,
+                        export::format_time(hit.modified),
+                        export::format_time(hit.created)
+                    );
+                    if with_matches {
+                        line.push_str(&format!(",{}", match_count(hit)));
+                    }
+                    lines.push(line);
                 }
+                lines.join("\n") + "\n"
             }
-        }
+            ExportFormat::Json => {
+                let items: Vec<String> = hits
+                    .iter()
+                    .map(|hit| {
+                        let matches = if with_matches {
+                            format!(", \"matches\": {}", match_count(hit))
+                        } else {
+                            String::new()
+                        };
+                        format!(
+                            "  {{\"path\": {}, \"size\": {}, \"modified\": {}, \"created\": {}{}}}",
+                            export::json_string(&hit.path.to_string_lossy()),
+                            hit.size,
+                            export::json_string(&export::format_time(hit.modified)),
+                            export::json_string(&export::format_time(hit.created)),
+                            matches
+                        )
+                    })
+                    .collect();
+                if items.is_empty() {
+                    "[]\n".to_string()
+                } else {
+                    format!("[\n{}\n]\n", items.join(",\n"))
+                }
+            }
+            ExportFormat::Txt => hits
+                .iter()
+                .map(|hit| {
+                    format!(
+                        "{:<19}  {:>10}  {}\n",
+                        export::format_time(hit.modified),
+                        crate::utils::format_size(hit.size),
+                        hit.path.display()
+                    )
+                })
+                .collect(),
+        };
 
-        // 一次性添加所有匹配的文件到结果中
-        if !matched_files.is_empty() {
-            results.lock().unwrap().extend(matched_files);
-        }
+        fs::write(path, content)
+            .map_err(|e| HekitError::FileOperation(format!("导出结果失败: {}", e)))
+    }
 
-        found_count
+    /// 显示一条搜索结果
+    fn print_hit(hit: &SearchHit, filters: &SearchFilters) {
+        match (&hit.content, &filters.content) {
+            (Some(file_match), Some(matcher)) => matcher.print(file_match),
+            _ => println!("{}", hit.path.display()),
+        }
     }
 }
@@ -0,0 +1,523 @@
+use crate::error::{HekitError, HekitResult};
+use crate::features::hashing::{self, HashAlgorithm};
+use crate::utils;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 索引文件头（格式变化时修改版本号，旧索引需重建）
+const INDEX_MAGIC: &[u8] = b"HEKIT-INDEX-1\n";
+
+/// 持久化的文件索引：记录根目录下每个目录的修改时间及其中文件的名称、大小、修改时间
+pub struct FileIndex {
+    /// 索引根目录（规范化后的绝对路径）
+    pub root: PathBuf,
+    /// 最近一次构建或更新的时间
+    pub built_at: SystemTime,
+    /// 相对路径（`/` 分隔，根目录为空字符串）到目录记录的映射
+    pub dirs: BTreeMap<String, DirRecord>,
+}
+
+/// 单个目录的索引记录
+#[derive(Debug, Clone)]
+pub struct DirRecord {
+    pub mtime: Option<SystemTime>,
+    pub files: Vec<FileRecord>,
+    pub subdirs: Vec<String>,
+}
+
+/// 单个文件的索引记录
+#[derive(Debug, Clone)]
+pub struct FileRecord {
+    pub name: String,
+    pub size: u64,
+    pub mtime: Option<SystemTime>,
+}
+
+/// 构建或更新索引的统计
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanStats {
+    /// 重新读取的目录数
+    pub read: usize,
+    /// 修改时间未变、直接沿用旧记录的目录数
+    pub reused: usize,
+    /// 无法读取而跳过的目录数
+    pub skipped: usize,
+}
+
+impl FileIndex {
+    /// 扫描目录构建索引；提供旧索引时只重新读取修改时间发生变化的目录
+    ///
+    /// 目录的修改时间只在增删、重命名其中的条目时变化，原地修改文件内容不会被发现，需重新构建。
+    pub fn scan(root: &Path, previous: Option<FileIndex>) -> HekitResult<(Self, ScanStats)> {
+        let root = fs::canonicalize(root).map_err(|e| {
+            HekitError::FileOperation(format!("无法访问目录 {}: {}", root.display(), e))
+        })?;
+        let built_at = SystemTime::now();
+        let (mut old_dirs, old_built_at) = match previous {
+            Some(index) if index.root == root => (index.dirs, Some(index.built_at)),
+            _ => (BTreeMap::new(), None),
+        };
+
+        let mut stats = ScanStats::default();
+        let mut dirs = BTreeMap::new();
+        let mut stack = vec![String::new()];
+        while let Some(rel) = stack.pop() {
+            let dir = root.join(&rel);
+            let mtime = fs::metadata(&dir).and_then(|m| m.modified()).ok();
+
+            // 上次构建期间被修改的目录可能只记录了一部分变化，必须重新读取
+            let unchanged = |old: &DirRecord| {
+                old.mtime.is_some()
+                    && old.mtime == mtime
+                    && old_built_at.is_some_and(|built| mtime.is_some_and(|m| m < built))
+            };
+            let record = match old_dirs.remove(&rel) {
+                Some(old) if unchanged(&old) => {
+                    stats.reused += 1;
+                    old
+                }
+                _ => match read_dir_record(&dir, mtime) {
+                    Ok(record) => {
+                        stats.read += 1;
+                        record
+                    }
+                    Err(_) => {
+                        stats.skipped += 1;
+                        continue;
+                    }
+                },
+            };
+
+            for subdir in &record.subdirs {
+                stack.push(join_relative(&rel, subdir));
+            }
+            dirs.insert(rel, record);
+        }
+
+        Ok((
+            Self {
+                root,
+                built_at,
+                dirs,
+            },
+            stats,
+        ))
+    }
+
+    /// 索引中的文件总数
+    pub fn file_count(&self) -> usize {
+        self.dirs.values().map(|dir| dir.files.len()).sum()
+    }
+
+    /// 检查记录的目录修改时间（max_depth 限制检查的层数，None 为全部），返回已变化的目录数
+    pub fn changed_dirs(&self, max_depth: Option<usize>) -> usize {
+        self.dirs
+            .iter()
+            .filter(|(rel, _)| max_depth.is_none_or(|depth| relative_depth(rel) <= depth))
+            .filter(|(rel, record)| {
+                let mtime = fs::metadata(self.root.join(rel))
+                    .and_then(|m| m.modified())
+                    .ok();
+                mtime.is_none() || mtime != record.mtime
+            })
+            .count()
+    }
+
+    /// 遍历某个子目录（相对路径）下的记录，recursive 为 false 时只包含该目录本身
+    pub fn dirs_under<'a>(
+        &'a self,
+        rel: &'a str,
+        recursive: bool,
+    ) -> impl Iterator<Item = (&'a String, &'a DirRecord)> + 'a {
+        self.dirs.iter().filter(move |(path, _)| {
+            if path.as_str() == rel {
+                return true;
+            }
+            recursive
+                && (rel.is_empty()
+                    || path
+                        .strip_prefix(rel)
+                        .is_some_and(|rest| rest.starts_with('/')))
+        })
+    }
+
+    /// 索引中某条目录记录对应的完整路径
+    pub fn full_path(&self, rel: &str) -> PathBuf {
+        if rel.is_empty() {
+            self.root.clone()
+        } else {
+            self.root.join(rel)
+        }
+    }
+
+    /// 读取指定根目录的索引，不存在时返回 None
+    pub fn load(root: &Path) -> HekitResult<Option<Self>> {
+        let root = match fs::canonicalize(root) {
+            Ok(root) => root,
+            Err(_) => return Ok(None),
+        };
+        let path = index_file(&root)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let file = File::open(&path)
+            .map_err(|e| HekitError::FileOperation(format!("读取索引失败: {}", e)))?;
+        Self::read_from(GzDecoder::new(BufReader::new(file)))
+            .map(Some)
+            .map_err(|e| {
+                HekitError::FileOperation(format!(
+                    "索引文件已损坏或版本不兼容（{}），请重新构建: {}",
+                    e,
+                    path.display()
+                ))
+            })
+    }
+
+    /// 查找覆盖指定目录的索引（该目录或其上级目录的索引）
+    pub fn find_covering(path: &Path) -> HekitResult<Option<Self>> {
+        let path = fs::canonicalize(path)
+            .map_err(|e| HekitError::FileOperation(format!("无法访问目录: {}", e)))?;
+        for dir in path.ancestors() {
+            if let Some(index) = Self::load(dir)? {
+                return Ok(Some(index));
+            }
+        }
+        Ok(None)
+    }
+
+    /// 保存索引（先写临时文件再替换，避免中断时损坏旧索引）
+    pub fn save(&self) -> HekitResult<PathBuf> {
+        let path = index_file(&self.root)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| HekitError::FileOperation(format!("创建索引目录失败: {}", e)))?;
+        }
+        let temp = path.with_extension("tmp");
+        let write = || -> io::Result<()> {
+            let file = File::create(&temp)?;
+            let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::fast());
+            self.write_to(&mut encoder)?;
+            encoder.finish()?.flush()?;
+            fs::rename(&temp, &path)
+        };
+        write().map_err(|e| {
+            let _ = fs::remove_file(&temp);
+            HekitError::FileOperation(format!("保存索引失败: {}", e))
+        })?;
+        Ok(path)
+    }
+
+    /// 删除指定根目录的索引，返回是否存在
+    pub fn remove(root: &Path) -> HekitResult<bool> {
+        let root = fs::canonicalize(root)
+            .map_err(|e| HekitError::FileOperation(format!("无法访问目录: {}", e)))?;
+        let path = index_file(&root)?;
+        if !path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(&path)
+            .map_err(|e| HekitError::FileOperation(format!("删除索引失败: {}", e)))?;
+        Ok(true)
+    }
+
+    /// 列出所有已保存的索引
+    pub fn list_all() -> HekitResult<Vec<Self>> {
+        let Some(dir) = index_dir() else {
+            return Ok(Vec::new());
+        };
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return Ok(Vec::new());
+        };
+        let mut indexes: Vec<Self> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "idx"))
+            .filter_map(|path| {
+                let file = File::open(path).ok()?;
+                Self::read_from(GzDecoder::new(BufReader::new(file))).ok()
+            })
+            .collect();
+        indexes.sort_by(|a, b| a.root.cmp(&b.root));
+        Ok(indexes)
+    }
+
+    /// 写入索引内容
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(INDEX_MAGIC)?;
+        write_str(writer, &self.root.to_string_lossy())?;
+        write_time(writer, Some(self.built_at))?;
+        write_u64(writer, self.dirs.len() as u64)?;
+        for (rel, record) in &self.dirs {
+            write_str(writer, rel)?;
+            write_time(writer, record.mtime)?;
+            write_u64(writer, record.subdirs.len() as u64)?;
+            for subdir in &record.subdirs {
+                write_str(writer, subdir)?;
+            }
+            write_u64(writer, record.files.len() as u64)?;
+            for file in &record.files {
+                write_str(writer, &file.name)?;
+                write_u64(writer, file.size)?;
+                write_time(writer, file.mtime)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 读取索引内容
+    fn read_from<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut magic = vec![0; INDEX_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if magic != INDEX_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "文件头不匹配"));
+        }
+
+        let root = PathBuf::from(read_str(&mut reader)?);
+        let built_at = read_time(&mut reader)?.unwrap_or(UNIX_EPOCH);
+        let dir_count = read_u64(&mut reader)?;
+        let mut dirs = BTreeMap::new();
+        for _ in 0..dir_count {
+            let rel = read_str(&mut reader)?;
+            let mtime = read_time(&mut reader)?;
+            let subdirs = (0..read_u64(&mut reader)?)
+                .map(|_| read_str(&mut reader))
+                .collect::<io::Result<Vec<_>>>()?;
+            let files = (0..read_u64(&mut reader)?)
+                .map(|_| {
+                    Ok(FileRecord {
+                        name: read_str(&mut reader)?,
+                        size: read_u64(&mut reader)?,
+                        mtime: read_time(&mut reader)?,
+                    })
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+            dirs.insert(
+                rel,
+                DirRecord {
+                    mtime,
+                    files,
+                    subdirs,
+                },
+            );
+        }
+
+        Ok(Self {
+            root,
+            built_at,
+            dirs,
+        })
+    }
+}
+
+/// 读取单个目录的文件和子目录（不跟随目录符号链接，避免循环）
+fn read_dir_record(dir: &Path, mtime: Option<SystemTime>) -> io::Result<DirRecord> {
+    let mut files = Vec::new();
+    let mut subdirs = Vec::new();
+    for entry in fs::read_dir(dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            subdirs.push(name);
+            continue;
+        }
+        // 指向文件的符号链接按目标文件记录
+        let Ok(metadata) = fs::metadata(entry.path()) else {
+            continue;
+        };
+        if metadata.is_file() {
+            files.push(FileRecord {
+                name,
+                size: metadata.len(),
+                mtime: metadata.modified().ok(),
+            });
+        }
+    }
+    Ok(DirRecord {
+        mtime,
+        files,
+        subdirs,
+    })
+}
+
+/// 拼接索引中的相对路径
+fn join_relative(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", parent, name)
+    }
+}
+
+/// 相对路径的层数（根目录为0）
+pub fn relative_depth(rel: &str) -> usize {
+    if rel.is_empty() {
+        0
+    } else {
+        rel.matches('/').count() + 1
+    }
+}
+
+/// 索引文件的存放目录
+fn index_dir() -> Option<PathBuf> {
+    utils::app_data_dir().map(|dir| dir.join("search_index"))
+}
+
+/// 根目录对应的索引文件（文件名取路径的哈希）
+fn index_file(root: &Path) -> HekitResult<PathBuf> {
+    let dir =
+        index_dir().ok_or_else(|| HekitError::Configuration("无法确定程序数据目录".to_string()))?;
+    let hash = hashing::hash_reader(root.to_string_lossy().as_bytes(), HashAlgorithm::Blake3)
+        .map_err(|e| HekitError::System(format!("计算索引文件名失败: {}", e)))?;
+    Ok(dir.join(format!("{}.idx", &hash[..16])))
+}
+
+fn write_u64<W: Write>(writer: &mut W, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn write_str<W: Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    write_u64(writer, value.len() as u64)?;
+    writer.write_all(value.as_bytes())
+}
+
+fn read_str<R: Read>(reader: &mut R) -> io::Result<String> {
+    let len = read_u64(reader)?;
+    let mut bytes = Vec::new();
+    reader.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// 时间以距 UNIX 纪元的纳秒数保存，0 表示不可用
+fn write_time<W: Write>(writer: &mut W, time: Option<SystemTime>) -> io::Result<()> {
+    let nanos = time
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_nanos() as u64);
+    write_u64(writer, nanos)
+}
+
+fn read_time<R: Read>(reader: &mut R) -> io::Result<Option<SystemTime>> {
+    let nanos = read_u64(reader)?;
+    Ok((nanos > 0).then(|| UNIX_EPOCH + Duration::from_nanos(nanos)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hekit_index_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), "a").unwrap();
+        fs::write(dir.join("sub").join("b.txt"), "bb").unwrap();
+        dir
+    }
+
+    /// 等待一段时间，保证后续修改的时间戳与之前不同（部分文件系统时间精度较粗）
+    fn tick() {
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    #[test]
+    fn scan_records_files_and_subdirs() {
+        let dir = temp_dir("scan");
+        let (index, stats) = FileIndex::scan(&dir, None).unwrap();
+        assert_eq!(stats.read, 2);
+        assert_eq!(stats.reused, 0);
+        assert_eq!(index.file_count(), 2);
+        assert_eq!(index.dirs[""].subdirs, vec!["sub".to_string()]);
+        assert_eq!(index.dirs["sub"].files[0].name, "b.txt");
+        assert_eq!(index.dirs["sub"].files[0].size, 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn changed_dirs_detects_added_file() {
+        let dir = temp_dir("changed");
+        let (index, _) = FileIndex::scan(&dir, None).unwrap();
+        assert_eq!(index.changed_dirs(None), 0);
+
+        tick();
+        fs::write(dir.join("sub").join("c.txt"), "c").unwrap();
+        assert_eq!(index.changed_dirs(None), 1);
+        // 只检查第1层时发现不了更深的变化
+        assert_eq!(index.changed_dirs(Some(0)), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn incremental_scan_rereads_only_changed_dirs() {
+        let dir = temp_dir("update");
+        tick();
+        let (index, _) = FileIndex::scan(&dir, None).unwrap();
+
+        let (index, stats) = FileIndex::scan(&dir, Some(index)).unwrap();
+        assert_eq!((stats.read, stats.reused), (0, 2));
+
+        tick();
+        fs::write(dir.join("sub").join("c.txt"), "c").unwrap();
+        let (index, stats) = FileIndex::scan(&dir, Some(index)).unwrap();
+        assert_eq!((stats.read, stats.reused), (1, 1));
+        assert_eq!(index.file_count(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn scan_rereads_dirs_modified_during_previous_build() {
+        let dir = temp_dir("racy");
+        let (mut index, _) = FileIndex::scan(&dir, None).unwrap();
+        // 模拟目录修改时间不早于上次构建时间的情况
+        index.built_at = UNIX_EPOCH;
+
+        let (_, stats) = FileIndex::scan(&dir, Some(index)).unwrap();
+        assert_eq!((stats.read, stats.reused), (2, 0));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn index_round_trips_through_binary_format() {
+        let dir = temp_dir("roundtrip");
+        let (index, _) = FileIndex::scan(&dir, None).unwrap();
+
+        let mut bytes = Vec::new();
+        index.write_to(&mut bytes).unwrap();
+        let loaded = FileIndex::read_from(bytes.as_slice()).unwrap();
+
+        assert_eq!(loaded.root, index.root);
+        assert_eq!(loaded.built_at, index.built_at);
+        assert_eq!(loaded.dirs.len(), index.dirs.len());
+        for (rel, record) in &index.dirs {
+            let other = &loaded.dirs[rel];
+            assert_eq!(other.mtime, record.mtime);
+            assert_eq!(other.subdirs, record.subdirs);
+            let names = |files: &[FileRecord]| {
+                files
+                    .iter()
+                    .map(|file| (file.name.clone(), file.size, file.mtime))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(names(&other.files), names(&record.files));
+        }
+
+        assert!(FileIndex::read_from(&bytes[..bytes.len() - 3]).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,6 +1,9 @@
 use crate::error::HekitResult;
 use crate::features::common::ToolInterface;
-use crate::features::search::config::BatchSearchConfig;
+use crate::features::search::config::{BatchSearchConfig, IndexCommand};
+use crate::features::search::results::ResultBrowser;
+use std::cell::RefCell;
+use std::path::PathBuf;
 
 /// 搜索工具接口
 pub struct SearchTool;
@@ -21,26 +24,88 @@ impl ToolInterface for SearchTool {
         println!("参数说明:");
         println!("  -d, --path       搜索目录（默认当前目录）");
         println!("  -n, --name       文件名模式（支持通配符 *）");
-        println!("  -t, --type       文件类型（扩展名，如：txt, jpg）");
-        println!("  -s, --size       文件大小范围（格式：min-max，如：1K-10M）");
+        println!("  -t, --type       文件类型（扩展名，多个用逗号分隔，如：jpg,png,webp）");
+        println!(
+            "  --mime           按内容识别的 MIME 类型（支持通配符，如 image/*, application/pdf）"
+        );
+        println!(
+            "  --kind           按内容识别的大类: image, video, audio, archive, document, code"
+        );
+        println!(
+            "                   （根据文件头判断，能找出扩展名错误的文件；文本类文件按扩展名判断）"
+        );
+        println!("  --min-size       最小文件大小（如 1024、10K、5MB、1.5GiB，按1024进制）");
+        println!("  --max-size       最大文件大小（同上）");
         println!("  -r, --recursive  递归搜索子目录");
-        println!("  -i, --ignore-case 忽略大小写");
-        println!("  -c, --content    搜索文件内容（暂不支持）");
+        println!("  --max-depth      最多进入的目录层数（搜索目录下的文件为第1层）");
+        println!("  --min-depth      只显示不浅于此层数的文件");
+        println!("  -L, --follow-symlinks  跟随目录符号链接（自动检测循环，默认不跟随）");
+        println!("  --one-file-system      不进入其他文件系统（挂载点、网络盘）");
+        println!("  -c, --case       不区分大小写匹配（同时作用于内容搜索）");
+        println!("  --content        搜索文件内容（正则表达式，自动识别UTF-8/GBK/UTF-16，跳过二进制文件）");
+        println!("  -C, --context    显示匹配行前后的行数");
+        println!("  -j, --threads    遍历线程数（默认按CPU核心数）");
+        println!("  --sorted         结束后按路径排序输出（默认边搜索边输出）");
+        println!("  --sort           结束后排序输出: name, size, mtime, path");
+        println!("  --reverse        按降序排序（与 --sort 一起使用）");
+        println!("  --limit          最多显示的结果数");
+        println!("  -o, --output     导出结果（.csv, .json, .txt，包含大小和时间）");
+        println!("  --archives       同时搜索 zip/tar.gz/tar.bz2 压缩包内的文件（结果显示为 包.zip!/内部路径）");
+        println!("  -q, --query      查询表达式（用双引号括起来）: name: path: ext: 通配符条件，");
+        println!("                   size/mtime/ctime/atime 比较条件，and/or/not 和括号组合");
+        println!("                   时间写相对时长时比较距今多久，mtime<30d 即30天内修改过");
+        println!("  --index          使用已构建的索引搜索（不遍历磁盘，支持名称/类型/大小/修改时间条件）");
+        println!("  --newer/--older  修改时间在此之后/之前（3d, 2w, 1y 或 2024-01-31）");
+        println!("  --modified-after/--modified-before  按修改时间筛选");
+        println!("  --created-after/--created-before    按创建时间筛选");
+        println!("  --accessed       在此之后访问过");
+        println!();
+
+        println!("结果操作（交互模式）:");
+        println!("  results  浏览最近一次的搜索结果：分页、按文件名筛选，");
+        println!("           复制/移动/删除、打开所在文件夹、查看完整元数据");
+        println!();
+
+        println!("索引管理:");
+        println!("  index build <目录>   构建索引");
+        println!("  index update <目录>  增量更新（只重新读取修改时间变化的目录）");
+        println!("  index status [目录]  查看索引及是否过期");
+        println!("  index remove <目录>  删除索引");
         println!();
 
         println!("实用示例:");
         println!("  搜索所有txt文件: --name *.txt");
-        println!("  搜索图片文件: --type jpg --type png");
-        println!("  搜索大文件: --size 10M-100M");
+        println!("  搜索图片文件: --type jpg,png,webp -r");
+        println!("  按内容找出所有视频: -r --kind video");
+        println!("  找出伪装成其他扩展名的图片: -r --mime \"image/*\" -n \"*.txt\"");
+        println!("  搜索大文件: --min-size 10MB");
+        println!("  最近3天修改的文件: -r --newer 3d");
+        println!("  一年未修改的日志: -n \"*.log\" -r --older 1y");
+        println!("  最大的10个文件: -r --sort size --reverse --limit 10");
+        println!("  导出结果列表: -n \"*.pdf\" -r -o results.csv");
+        println!("  在备份压缩包中查找文档: -n \"*.docx\" -r --archives");
+        println!(
+            "  用索引秒搜共享盘: index build D:\\共享  然后  -d D:\\共享 -r --index -n \"*.xlsx\""
+        );
+        println!("  组合条件: -r -q \"name:*.log and (size>10M or mtime<7d) and not path:*/node_modules/*\"");
+        println!("  只看两层以内的文件: -n \"*.pdf\" --max-depth 2");
+        println!("  搜索代码中的TODO: -n \"*.rs\" -r --content \"TODO|FIXME\" -C 2");
 
         utils::print_compact_separator();
     }
 
     /// 执行命令
     fn execute_command(input: &str) -> HekitResult<()> {
+        Self::search(input).map(|_| ())
+    }
+}
+
+impl SearchTool {
+    /// 执行搜索，返回结果列表（显示帮助或管理索引时返回 None）
+    fn search(input: &str) -> HekitResult<Option<Vec<PathBuf>>> {
         if input.trim().is_empty() {
             Self::show_usage();
-            return Ok(());
+            return Ok(None);
         }
 
         let matches = crate::features::common::execute_common_command(
@@ -51,7 +116,14 @@ impl ToolInterface for SearchTool {
         )?;
 
         if input.trim() == "help" {
-            return Ok(());
+            return Ok(None);
+        }
+
+        if let Some(("index", index_matches)) = matches.subcommand() {
+            let command = IndexCommand::from_matches(index_matches)
+                .map_err(|e| crate::error::HekitError::UserInput(format!("配置错误: {}", e)))?;
+            crate::features::search::core::BatchSearchCore::run_index_command(&command)?;
+            return Ok(None);
         }
 
         let config = BatchSearchConfig::from_matches(&matches)
@@ -66,15 +138,31 @@ impl ToolInterface for SearchTool {
             println!("跳过 {} 个无法访问的目录", skipped);
         }
 
-        Ok(())
+        Ok(Some(results))
     }
 }
 
-/// 运行交互式界面
+/// 运行交互式界面（保留最近一次的搜索结果，输入 results 浏览并操作）
 pub fn run_interactive() -> HekitResult<()> {
+    let last_results = RefCell::new(Vec::new());
     crate::features::common::run_interactive(
         "文件搜索",
-        SearchTool::execute_command,
+        |input| {
+            if input.eq_ignore_ascii_case("results") {
+                let mut browser = ResultBrowser::new(last_results.take());
+                let result = browser.run();
+                *last_results.borrow_mut() = browser.into_results();
+                return result;
+            }
+
+            if let Some(results) = SearchTool::search(input)? {
+                if !results.is_empty() {
+                    crate::utils::print_info("输入 results 可分页浏览、筛选并操作这些结果");
+                }
+                *last_results.borrow_mut() = results;
+            }
+            Ok(())
+        },
         SearchTool::show_usage,
     )
 }
@@ -1,6 +1,12 @@
+pub mod archive;
 pub mod config;
+pub mod content;
 pub mod core;
+pub mod index;
 pub mod interface;
+pub mod query;
+pub mod results;
+pub mod walker;
 
 pub use config::BatchSearchConfig;
 pub use core::BatchSearchCore;
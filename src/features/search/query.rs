@@ -0,0 +1,511 @@
+use crate::features::parse;
+use anyhow::{anyhow, Result};
+use glob::{MatchOptions, Pattern};
+use std::cmp::Ordering;
+use std::fmt;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// 解析后的查询表达式
+///
+/// 语法示例：`name:*.log and (size>10M or mtime<30d) and not path:*/node_modules/*`
+/// - 条件：`name:` 文件名、`path:` 完整路径（通配符）、`ext:` 扩展名、
+///   `size` 大小、`mtime`/`ctime`/`atime` 修改/创建/访问时间
+/// - 大小和时间使用比较符 `>` `>=` `<` `<=`，大小还可用 `=` 或 `:`
+/// - 时间为相对时长（`30d`）时比较的是距今多久，`mtime<30d` 表示30天内修改过；
+///   为日期（`2024-01-31`）时比较时间先后
+/// - 用 `and`、`or`、`not` 和括号组合，相邻条件之间省略 `and` 时默认为 `and`
+/// - 含空格的值用单引号括起来，如 `name:'my file*'`
+#[derive(Debug, Clone)]
+pub struct Query {
+    source: String,
+    root: QueryNode,
+    /// name 和 path 条件的通配符匹配选项
+    options: MatchOptions,
+}
+
+/// 查询表达式树
+#[derive(Debug, Clone)]
+enum QueryNode {
+    And(Box<QueryNode>, Box<QueryNode>),
+    Or(Box<QueryNode>, Box<QueryNode>),
+    Not(Box<QueryNode>),
+    Name(Pattern),
+    Path(Pattern),
+    Ext(String),
+    Size(Compare, u64),
+    Time(TimeField, Compare, SystemTime),
+}
+
+/// 比较方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compare {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+}
+
+/// 可比较的时间字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimeField {
+    Modified,
+    Created,
+    Accessed,
+}
+
+/// 查询的匹配对象（普通文件、压缩包条目或索引记录）
+pub struct QueryTarget<'a> {
+    pub path: &'a Path,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+    pub accessed: Option<SystemTime>,
+}
+
+/// 词法单元
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    And,
+    Or,
+    Not,
+    Term(String),
+}
+
+impl Query {
+    /// 解析查询表达式（case_insensitive 作用于 name 和 path 条件）
+    pub fn parse(source: &str, case_insensitive: bool) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        if tokens.is_empty() {
+            return Err(anyhow!("查询表达式不能为空"));
+        }
+
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let root = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(anyhow!("查询语法错误: 多余的 {}", token));
+        }
+
+        Ok(Self {
+            source: source.trim().to_string(),
+            root,
+            options: MatchOptions {
+                case_sensitive: !case_insensitive,
+                ..MatchOptions::new()
+            },
+        })
+    }
+
+    /// 检查对象是否满足查询（时间不可用的条件视为不满足）
+    pub fn matches(&self, target: &QueryTarget) -> bool {
+        self.root.matches(target, self.options)
+    }
+
+    /// 是否包含创建时间或访问时间条件
+    pub fn uses_created_or_accessed(&self) -> bool {
+        self.root.uses_created_or_accessed()
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl QueryNode {
+    fn uses_created_or_accessed(&self) -> bool {
+        match self {
+            QueryNode::And(left, right) | QueryNode::Or(left, right) => {
+                left.uses_created_or_accessed() || right.uses_created_or_accessed()
+            }
+            QueryNode::Not(inner) => inner.uses_created_or_accessed(),
+            QueryNode::Time(field, _, _) => *field != TimeField::Modified,
+            _ => false,
+        }
+    }
+
+    fn matches(&self, target: &QueryTarget, options: MatchOptions) -> bool {
+        match self {
+            QueryNode::And(left, right) => {
+                left.matches(target, options) && right.matches(target, options)
+            }
+            QueryNode::Or(left, right) => {
+                left.matches(target, options) || right.matches(target, options)
+            }
+            QueryNode::Not(inner) => !inner.matches(target, options),
+            QueryNode::Name(pattern) => target
+                .path
+                .file_name()
+                .is_some_and(|name| pattern.matches_with(&name.to_string_lossy(), options)),
+            QueryNode::Path(pattern) => {
+                // 统一使用 `/` 分隔，Windows 下也可以写 `*/node_modules/*`
+                let path = target.path.to_string_lossy().replace('\\', "/");
+                pattern.matches_with(&path, options)
+            }
+            QueryNode::Ext(ext) => target
+                .path
+                .extension()
+                .is_some_and(|actual| actual.to_string_lossy().eq_ignore_ascii_case(ext)),
+            QueryNode::Size(compare, size) => compare.test(target.size.cmp(size)),
+            QueryNode::Time(field, compare, time) => {
+                let actual = match field {
+                    TimeField::Modified => target.modified,
+                    TimeField::Created => target.created,
+                    TimeField::Accessed => target.accessed,
+                };
+                actual.is_some_and(|actual| compare.test(actual.cmp(time)))
+            }
+        }
+    }
+}
+
+impl Compare {
+    /// 判断比较结果是否满足
+    fn test(self, ordering: Ordering) -> bool {
+        match self {
+            Compare::Less => ordering == Ordering::Less,
+            Compare::LessEqual => ordering != Ordering::Greater,
+            Compare::Greater => ordering == Ordering::Greater,
+            Compare::GreaterEqual => ordering != Ordering::Less,
+            Compare::Equal => ordering == Ordering::Equal,
+        }
+    }
+
+    /// 比较距今时长改为比较时间点时方向相反（越久远时间越早）
+    fn flipped(self) -> Self {
+        match self {
+            Compare::Less => Compare::Greater,
+            Compare::LessEqual => Compare::GreaterEqual,
+            Compare::Greater => Compare::Less,
+            Compare::GreaterEqual => Compare::LessEqual,
+            Compare::Equal => Compare::Equal,
+        }
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::LeftParen => write!(f, "'('"),
+            Token::RightParen => write!(f, "')'"),
+            Token::And => write!(f, "'and'"),
+            Token::Or => write!(f, "'or'"),
+            Token::Not => write!(f, "'not'"),
+            Token::Term(term) => write!(f, "'{}'", term),
+        }
+    }
+}
+
+/// 递归下降解析器（优先级：not > and > or）
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<QueryNode> {
+        let mut node = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            let right = self.parse_and()?;
+            node = QueryNode::Or(Box::new(node), Box::new(right));
+        }
+        Ok(node)
+    }
+
+    fn parse_and(&mut self) -> Result<QueryNode> {
+        let mut node = self.parse_unary()?;
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.next();
+                }
+                // 相邻的条件默认按 and 组合
+                Some(Token::Term(_)) | Some(Token::Not) | Some(Token::LeftParen) => {}
+                _ => break,
+            }
+            let right = self.parse_unary()?;
+            node = QueryNode::And(Box::new(node), Box::new(right));
+        }
+        Ok(node)
+    }
+
+    fn parse_unary(&mut self) -> Result<QueryNode> {
+        match self.next() {
+            Some(Token::Not) => Ok(QueryNode::Not(Box::new(self.parse_unary()?))),
+            Some(Token::LeftParen) => {
+                let node = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RightParen) => Ok(node),
+                    _ => Err(anyhow!("查询语法错误: 缺少 ')'")),
+                }
+            }
+            Some(Token::Term(term)) => parse_term(&term),
+            Some(token) => Err(anyhow!("查询语法错误: 此处不应出现 {}", token)),
+            None => Err(anyhow!("查询语法错误: 表达式不完整")),
+        }
+    }
+}
+
+/// 拆分词法单元：括号、关键字和条件（条件中可用单引号或双引号包含空格）
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LeftParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RightParen);
+            }
+            _ => {
+                let mut word = String::new();
+                let mut quote = None;
+                while let Some(&c) = chars.peek() {
+                    match quote {
+                        Some(q) if c == q => quote = None,
+                        Some(_) => word.push(c),
+                        None if c == '\'' || c == '"' => quote = Some(c),
+                        None if c.is_whitespace() || c == ')' => break,
+                        None => word.push(c),
+                    }
+                    chars.next();
+                }
+                if quote.is_some() {
+                    return Err(anyhow!("查询语法错误: 引号未闭合"));
+                }
+
+                tokens.push(match word.to_lowercase().as_str() {
+                    "and" | "&&" => Token::And,
+                    "or" | "||" => Token::Or,
+                    "not" | "!" => Token::Not,
+                    _ => Token::Term(word),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// 解析单个条件，如 `name:*.log`、`size>10M`、`mtime<30d`
+fn parse_term(term: &str) -> Result<QueryNode> {
+    let field_end = term
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .ok_or_else(|| anyhow!("查询语法错误: 条件 '{}' 缺少比较符", term))?;
+    let (field, rest) = term.split_at(field_end);
+    let (compare, value) = [
+        (">=", Compare::GreaterEqual),
+        ("<=", Compare::LessEqual),
+        (">", Compare::Greater),
+        ("<", Compare::Less),
+        ("=", Compare::Equal),
+        (":", Compare::Equal),
+    ]
+    .iter()
+    .find_map(|(symbol, compare)| rest.strip_prefix(symbol).map(|value| (*compare, value)))
+    .ok_or_else(|| anyhow!("查询语法错误: 条件 '{}' 缺少比较符", term))?;
+    let is_colon = rest.starts_with(':');
+
+    if value.is_empty() {
+        return Err(anyhow!("查询语法错误: 条件 '{}' 缺少值", term));
+    }
+
+    let require_colon = |node: QueryNode| {
+        if is_colon {
+            Ok(node)
+        } else {
+            Err(anyhow!(
+                "查询语法错误: {} 条件需使用 ':'，如 {}:{}",
+                field,
+                field,
+                value
+            ))
+        }
+    };
+    let pattern =
+        |value: &str| Pattern::new(value).map_err(|e| anyhow!("通配符无效: {} ({})", value, e));
+
+    match field.to_lowercase().as_str() {
+        "name" => require_colon(QueryNode::Name(pattern(value)?)),
+        "path" => require_colon(QueryNode::Path(pattern(value)?)),
+        "ext" | "type" => require_colon(QueryNode::Ext(value.trim_start_matches('.').to_string())),
+        "size" => Ok(QueryNode::Size(compare, parse::parse_size(value)?)),
+        "mtime" | "modified" => parse_time_term(TimeField::Modified, compare, is_colon, value),
+        "ctime" | "created" => parse_time_term(TimeField::Created, compare, is_colon, value),
+        "atime" | "accessed" => parse_time_term(TimeField::Accessed, compare, is_colon, value),
+        other => Err(anyhow!(
+            "未知的查询条件: {}（可用 name, path, ext, size, mtime, ctime, atime）",
+            other
+        )),
+    }
+}
+
+/// 解析时间条件：相对时长比较距今多久，日期比较时间先后
+fn parse_time_term(
+    field: TimeField,
+    compare: Compare,
+    is_colon: bool,
+    value: &str,
+) -> Result<QueryNode> {
+    if compare == Compare::Equal {
+        let symbol = if is_colon { ":" } else { "=" };
+        return Err(anyhow!(
+            "查询语法错误: 时间条件不支持 '{}'，请使用 < > <= >=",
+            symbol
+        ));
+    }
+
+    let time = parse::parse_time_point(value)?;
+    let compare = if parse::parse_duration(value).is_some() {
+        compare.flipped()
+    } else {
+        compare
+    };
+    Ok(QueryNode::Time(field, compare, time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn term(text: &str) -> Token {
+        Token::Term(text.to_string())
+    }
+
+    fn target(path: &str, size: u64, age: Duration) -> (PathBuf, u64, Option<SystemTime>) {
+        (
+            PathBuf::from(path),
+            size,
+            SystemTime::now().checked_sub(age),
+        )
+    }
+
+    fn matches(query: &str, file: &(PathBuf, u64, Option<SystemTime>)) -> bool {
+        Query::parse(query, false).unwrap().matches(&QueryTarget {
+            path: &file.0,
+            size: file.1,
+            modified: file.2,
+            created: None,
+            accessed: None,
+        })
+    }
+
+    #[test]
+    fn tokenize_splits_parens_and_operators() {
+        assert_eq!(
+            tokenize("name:*.log && (size>10M or not mtime<30d)").unwrap(),
+            vec![
+                term("name:*.log"),
+                Token::And,
+                Token::LeftParen,
+                term("size>10M"),
+                Token::Or,
+                Token::Not,
+                term("mtime<30d"),
+                Token::RightParen,
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_breaks_words_at_closing_paren() {
+        assert_eq!(
+            tokenize("(ext:log)").unwrap(),
+            vec![Token::LeftParen, term("ext:log"), Token::RightParen]
+        );
+        assert_eq!(
+            tokenize("(a or b))").unwrap(),
+            vec![
+                Token::LeftParen,
+                term("a"),
+                Token::Or,
+                term("b"),
+                Token::RightParen,
+                Token::RightParen,
+            ]
+        );
+        // 引号内的右括号和空格属于值本身，左括号不会打断单词
+        assert_eq!(
+            tokenize("name:'my (1).txt') name:a(b").unwrap(),
+            vec![term("name:my (1).txt"), Token::RightParen, term("name:a(b")]
+        );
+    }
+
+    #[test]
+    fn tokenize_rejects_unclosed_quote() {
+        assert!(tokenize("name:'abc").is_err());
+    }
+
+    #[test]
+    fn parse_reports_syntax_errors() {
+        for query in [
+            "",
+            "name:a and",
+            "(name:a",
+            "name:a)",
+            "size",
+            "name>a",
+            "mtime:3d",
+            "color:red",
+            "mtime<30m",
+        ] {
+            assert!(Query::parse(query, false).is_err(), "{}", query);
+        }
+    }
+
+    #[test]
+    fn query_combines_conditions() {
+        let day = Duration::from_secs(86400);
+        let log = target("/var/log/app.log", 20 << 20, day);
+        let old = target("/var/log/old.log", 1024, 100 * day);
+        let module = target("/src/node_modules/x.log", 20 << 20, day);
+
+        let query = "name:*.log and (size>10M or mtime>30d) and not path:*/node_modules/*";
+        assert!(matches(query, &log));
+        assert!(matches(query, &old));
+        assert!(!matches(query, &module));
+
+        assert!(matches("ext:LOG size<=20M", &log));
+        assert!(!matches("mtime<12h", &log));
+        assert!(matches("mtime<2d", &log));
+    }
+
+    #[test]
+    fn uses_created_or_accessed_walks_whole_tree() {
+        let uses = |text| {
+            Query::parse(text, false)
+                .unwrap()
+                .uses_created_or_accessed()
+        };
+        assert!(!uses("name:a and mtime<3d"));
+        assert!(uses("name:a or (size>1k and not ctime<3d)"));
+        assert!(uses("atime>1w"));
+    }
+}
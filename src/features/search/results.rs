@@ -0,0 +1,762 @@
+use crate::error::{HekitError, HekitResult};
+use crate::features::export;
+use crate::features::magic::{self, FileCategory};
+use crate::features::search::archive;
+use crate::utils;
+use glob::{MatchOptions, Pattern};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 每页显示的结果数
+const PAGE_SIZE: usize = 20;
+
+/// 删除或移动前最多列出的文件数
+const PREVIEW_LIMIT: usize = 10;
+
+/// 搜索结果浏览器：分页查看、进一步筛选并对结果执行操作
+pub struct ResultBrowser {
+    /// 全部结果（移动或删除后同步更新）
+    results: Vec<PathBuf>,
+    /// 当前视图（筛选后的结果）
+    view: Vec<PathBuf>,
+    /// 已应用的筛选条件
+    filters: Vec<String>,
+    /// 当前页（从0开始）
+    page: usize,
+}
+
+impl ResultBrowser {
+    /// 创建结果浏览器
+    pub fn new(results: Vec<PathBuf>) -> Self {
+        Self {
+            view: results.clone(),
+            results,
+            filters: Vec::new(),
+            page: 0,
+        }
+    }
+
+    /// 浏览结束后的结果列表（反映移动和删除）
+    pub fn into_results(self) -> Vec<PathBuf> {
+        self.results
+    }
+
+    /// 运行浏览循环：回车或 q 退出
+    pub fn run(&mut self) -> HekitResult<()> {
+        if self.results.is_empty() {
+            utils::print_info("没有可浏览的搜索结果，请先执行一次搜索");
+            return Ok(());
+        }
+
+        self.print_page();
+        loop {
+            let input = utils::get_user_input("结果 (h 查看命令, 回车或 q 退出) > ")
+                .map_err(|e| HekitError::UserInput(format!("读取输入失败: {}", e)))?;
+            let (command, argument) = match input.split_once(char::is_whitespace) {
+                Some((command, argument)) => (command, argument.trim()),
+                None => (input.as_str(), ""),
+            };
+
+            let result = match command.to_lowercase().as_str() {
+                "" | "q" | "quit" => return Ok(()),
+                "h" | "help" => {
+                    Self::print_help();
+                    Ok(())
+                }
+                "n" | "next" => self.turn_page(1),
+                "p" | "prev" => self.turn_page(-1),
+                "l" | "list" => {
+                    self.print_page();
+                    Ok(())
+                }
+                "filter" | "f" => self.filter(argument),
+                "reset" => {
+                    self.reset();
+                    Ok(())
+                }
+                "copy" | "cp" => self.copy(argument),
+                "move" | "mv" => self.move_to(argument),
+                "delete" | "rm" => self.delete(argument),
+                "open" => self.open_folder(argument),
+                "info" => self.print_info(argument),
+                _ => Err(HekitError::UserInput(format!(
+                    "未知命令: {}（输入 h 查看可用命令）",
+                    command
+                ))),
+            };
+            if let Err(e) = result {
+                utils::print_error(&format!("执行失败: {}", e));
+            }
+        }
+    }
+
+    /// 显示可用命令
+    fn print_help() {
+        println!("可用命令:");
+        println!("  n / p               下一页 / 上一页");
+        println!("  l                   重新显示当前页");
+        println!("  filter <模式>       按文件名进一步筛选（通配符，不区分大小写，可多次叠加）");
+        println!("  reset               清除筛选，恢复全部结果");
+        println!("  copy <选择> <目录>  复制到目录（压缩包内的文件会解压出来）");
+        println!("  move <选择> <目录>  移动到目录");
+        println!("  delete <选择>       删除文件（执行前确认）");
+        println!("  open <序号>         打开所在文件夹");
+        println!("  info <序号>         显示完整元数据");
+        println!("  选择可写 all、单个序号或 1-5,8 这样的列表");
+    }
+
+    /// 显示当前页
+    fn print_page(&self) {
+        let pages = self.view.len().div_ceil(PAGE_SIZE).max(1);
+        println!();
+        if self.filters.is_empty() {
+            println!(
+                "== 共 {} 个结果，第 {}/{} 页 ==",
+                self.view.len(),
+                self.page + 1,
+                pages
+            );
+        } else {
+            println!(
+                "== 筛选后 {}/{} 个结果（{}），第 {}/{} 页 ==",
+                self.view.len(),
+                self.results.len(),
+                self.filters.join(", "),
+                self.page + 1,
+                pages
+            );
+        }
+
+        let start = self.page * PAGE_SIZE;
+        for (offset, path) in self.view.iter().skip(start).take(PAGE_SIZE).enumerate() {
+            let (size, modified, note) = match fs::metadata(path) {
+                Ok(metadata) => (
+                    utils::format_size(metadata.len()),
+                    export::format_time(metadata.modified().ok()),
+                    "",
+                ),
+                Err(_) if archive::split_entry_path(path).is_some() => {
+                    ("-".to_string(), "-".to_string(), "  [压缩包内]")
+                }
+                Err(_) => ("-".to_string(), "-".to_string(), "  [已不存在]"),
+            };
+            println!(
+                "  {:>4}. {:>10}  {:<19}  {}{}",
+                start + offset + 1,
+                size,
+                modified,
+                path.display(),
+                note
+            );
+        }
+    }
+
+    /// 翻页
+    fn turn_page(&mut self, delta: isize) -> HekitResult<()> {
+        let pages = self.view.len().div_ceil(PAGE_SIZE).max(1);
+        let page = self.page as isize + delta;
+        if page < 0 || page >= pages as isize {
+            utils::print_info(if delta > 0 {
+                "已经是最后一页"
+            } else {
+                "已经是第一页"
+            });
+            return Ok(());
+        }
+        self.page = page as usize;
+        self.print_page();
+        Ok(())
+    }
+
+    /// 按文件名进一步筛选当前视图
+    fn filter(&mut self, pattern: &str) -> HekitResult<()> {
+        let pattern = pattern.trim_matches('"');
+        if pattern.is_empty() {
+            return Err(HekitError::UserInput(
+                "请指定文件名模式，如 filter *.pdf".to_string(),
+            ));
+        }
+        let compiled = Pattern::new(pattern)
+            .map_err(|e| HekitError::ArgumentParse(format!("文件名模式解析失败: {}", e)))?;
+        let options = MatchOptions {
+            case_sensitive: false,
+            ..MatchOptions::new()
+        };
+
+        let filtered: Vec<PathBuf> = self
+            .view
+            .iter()
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|name| compiled.matches_with(&name.to_string_lossy(), options))
+            })
+            .cloned()
+            .collect();
+        if filtered.is_empty() {
+            utils::print_warning(&format!("没有文件名匹配 {} 的结果，筛选未生效", pattern));
+            return Ok(());
+        }
+
+        self.view = filtered;
+        self.filters.push(pattern.to_string());
+        self.page = 0;
+        self.print_page();
+        Ok(())
+    }
+
+    /// 清除筛选
+    fn reset(&mut self) {
+        self.view = self.results.clone();
+        self.filters.clear();
+        self.page = 0;
+        self.print_page();
+    }
+
+    /// 复制选中的文件到目录（同名文件已存在时跳过）
+    fn copy(&self, argument: &str) -> HekitResult<()> {
+        let (selected, target_dir) = self.parse_transfer(argument, "copy")?;
+        fs::create_dir_all(&target_dir)
+            .map_err(|e| HekitError::FileOperation(format!("创建目标目录失败: {}", e)))?;
+
+        let mut copied = 0;
+        let mut error_count = 0;
+        for path in &selected {
+            let Some(target) = target_path(path, &target_dir) else {
+                continue;
+            };
+            if target.exists() {
+                utils::print_warning(&format!("目标已存在，跳过: {}", target.display()));
+                error_count += 1;
+                continue;
+            }
+
+            let result = match archive::split_entry_path(path) {
+                Some((archive_path, name)) => extract_entry(&archive_path, &name, &target),
+                None => fs::copy(path, &target).map(|_| ()),
+            };
+            match result {
+                Ok(_) => {
+                    copied += 1;
+                    println!("✓ {} → {}", path.display(), target.display());
+                }
+                Err(e) => {
+                    error_count += 1;
+                    utils::print_error(&format!("复制失败 {}: {}", path.display(), e));
+                }
+            }
+        }
+
+        Self::print_summary("复制", copied, error_count);
+        Ok(())
+    }
+
+    /// 移动选中的文件到目录（压缩包内的文件不能移动）
+    fn move_to(&mut self, argument: &str) -> HekitResult<()> {
+        let (selected, target_dir) = self.parse_transfer(argument, "move")?;
+        let selected = Self::without_archive_entries(selected, "移动");
+        if selected.is_empty() {
+            return Ok(());
+        }
+
+        Self::print_preview(&selected);
+        if !confirm(&format!(
+            "确认将 {} 个文件移动到 {}? (y/N): ",
+            selected.len(),
+            target_dir.display()
+        ))? {
+            return Ok(());
+        }
+        fs::create_dir_all(&target_dir)
+            .map_err(|e| HekitError::FileOperation(format!("创建目标目录失败: {}", e)))?;
+
+        let mut moved = 0;
+        let mut error_count = 0;
+        for path in &selected {
+            let Some(target) = target_path(path, &target_dir) else {
+                continue;
+            };
+            if target.exists() {
+                utils::print_warning(&format!("目标已存在，跳过: {}", target.display()));
+                error_count += 1;
+                continue;
+            }
+
+            match move_file(path, &target) {
+                Ok(_) => {
+                    moved += 1;
+                    println!("✓ {} → {}", path.display(), target.display());
+                    self.replace_path(path, Some(target));
+                }
+                Err(e) => {
+                    error_count += 1;
+                    utils::print_error(&format!("移动失败 {}: {}", path.display(), e));
+                }
+            }
+        }
+
+        Self::print_summary("移动", moved, error_count);
+        Ok(())
+    }
+
+    /// 删除选中的文件（压缩包内的文件不能删除）
+    fn delete(&mut self, argument: &str) -> HekitResult<()> {
+        if argument.is_empty() {
+            return Err(HekitError::UserInput(
+                "请指定要删除的结果，如 delete 1-3 或 delete all".to_string(),
+            ));
+        }
+        let selected = Self::without_archive_entries(self.select(argument)?, "删除");
+        if selected.is_empty() {
+            return Ok(());
+        }
+
+        Self::print_preview(&selected);
+        if !confirm(&format!("确认删除 {} 个文件? (y/N): ", selected.len()))? {
+            return Ok(());
+        }
+
+        let mut deleted = 0;
+        let mut error_count = 0;
+        for path in &selected {
+            match fs::remove_file(path) {
+                Ok(_) => {
+                    deleted += 1;
+                    println!("✓ 已删除 {}", path.display());
+                    self.replace_path(path, None);
+                }
+                Err(e) => {
+                    error_count += 1;
+                    utils::print_error(&format!("删除失败 {}: {}", path.display(), e));
+                }
+            }
+        }
+
+        Self::print_summary("删除", deleted, error_count);
+        Ok(())
+    }
+
+    /// 在文件管理器中打开结果所在的文件夹（压缩包内的文件打开压缩包所在文件夹）
+    fn open_folder(&self, argument: &str) -> HekitResult<()> {
+        let path = self.select_one(argument, "open")?;
+        let path = archive::split_entry_path(&path)
+            .map(|(archive_path, _)| archive_path)
+            .unwrap_or(path);
+        let folder = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+
+        let mut command = if cfg!(windows) {
+            let mut command = Command::new("explorer");
+            command.arg(format!("/select,{}", path.display()));
+            command
+        } else if cfg!(target_os = "macos") {
+            let mut command = Command::new("open");
+            command.arg("-R").arg(&path);
+            command
+        } else {
+            let mut command = Command::new("xdg-open");
+            command.arg(folder);
+            command
+        };
+        command
+            .spawn()
+            .map_err(|e| HekitError::System(format!("打开文件夹失败: {}", e)))?;
+
+        utils::print_success(&format!("已打开 {}", folder.display()));
+        Ok(())
+    }
+
+    /// 显示单个结果的完整元数据
+    fn print_info(&self, argument: &str) -> HekitResult<()> {
+        let path = self.select_one(argument, "info")?;
+        println!("路径:     {}", path.display());
+
+        if let Some((archive_path, name)) = archive::split_entry_path(&path) {
+            let entry = archive::with_entry(&archive_path, &name, |entry| {
+                let header = magic::read_header(entry.reader)?;
+                Ok((
+                    entry.size,
+                    entry.modified,
+                    magic::classify(&header, Path::new(&name)),
+                ))
+            })
+            .map_err(|e| HekitError::FileOperation(format!("读取压缩包失败: {}", e)))?
+            .ok_or_else(|| HekitError::FileOperation("压缩包中已没有此文件".to_string()))?;
+            let (size, modified, mime) = entry;
+            println!("压缩包:   {}", archive_path.display());
+            println!("条目:     {}", name);
+            println!("类型:     {}", describe_mime(mime));
+            println!("大小:     {} ({} 字节)", utils::format_size(size), size);
+            println!("修改时间: {}", export::format_time(modified));
+            return Ok(());
+        }
+
+        let metadata = fs::metadata(&path)
+            .map_err(|e| HekitError::FileOperation(format!("读取文件信息失败: {}", e)))?;
+        println!("类型:     {}", describe_mime(magic::classify_file(&path)));
+        println!(
+            "大小:     {} ({} 字节)",
+            utils::format_size(metadata.len()),
+            metadata.len()
+        );
+        println!(
+            "修改时间: {}",
+            export::format_time(metadata.modified().ok())
+        );
+        println!("创建时间: {}", export::format_time(metadata.created().ok()));
+        println!(
+            "访问时间: {}",
+            export::format_time(metadata.accessed().ok())
+        );
+        println!(
+            "只读:     {}",
+            if metadata.permissions().readonly() {
+                "是"
+            } else {
+                "否"
+            }
+        );
+        print_platform_metadata(&metadata);
+        Ok(())
+    }
+
+    /// 解析 copy/move 的参数：`<选择> <目录>`
+    fn parse_transfer(
+        &self,
+        argument: &str,
+        command: &str,
+    ) -> HekitResult<(Vec<PathBuf>, PathBuf)> {
+        let usage = || {
+            HekitError::UserInput(format!(
+                "用法: {} <选择> <目录>，如 {} 1-3 D:\\备份",
+                command, command
+            ))
+        };
+        let (selection, target) = argument.split_once(char::is_whitespace).ok_or_else(usage)?;
+        let target = target.trim().trim_matches('"');
+        if target.is_empty() {
+            return Err(usage());
+        }
+        Ok((self.select(selection)?, PathBuf::from(target)))
+    }
+
+    /// 解析选择：all、单个序号或 `1-5,8` 形式的列表（序号对应当前视图）
+    fn select(&self, selection: &str) -> HekitResult<Vec<PathBuf>> {
+        if selection.eq_ignore_ascii_case("all") || selection == "*" {
+            return Ok(self.view.clone());
+        }
+
+        let invalid = || HekitError::UserInput(format!("无效的选择: {}", selection));
+        let out_of_range = |number: usize| {
+            HekitError::UserInput(format!("序号 {} 超出范围（1-{}）", number, self.view.len()))
+        };
+        let mut indices = Vec::new();
+        for part in selection.split(',').filter(|part| !part.is_empty()) {
+            let (start, end) = match part.split_once('-') {
+                Some((start, end)) => (start, end),
+                None => (part, part),
+            };
+            let start: usize = start.trim().parse().map_err(|_| invalid())?;
+            let end: usize = end.trim().parse().map_err(|_| invalid())?;
+            if start > end {
+                return Err(invalid());
+            }
+            for number in [start, end] {
+                if number == 0 || number > self.view.len() {
+                    return Err(out_of_range(number));
+                }
+            }
+            indices.extend(start - 1..end);
+        }
+        if indices.is_empty() {
+            return Err(invalid());
+        }
+
+        indices.sort_unstable();
+        indices.dedup();
+        Ok(indices.into_iter().map(|i| self.view[i].clone()).collect())
+    }
+
+    /// 解析单个序号
+    fn select_one(&self, argument: &str, command: &str) -> HekitResult<PathBuf> {
+        let number: usize = argument.parse().map_err(|_| {
+            HekitError::UserInput(format!("用法: {} <序号>，如 {} 3", command, command))
+        })?;
+        if number == 0 || number > self.view.len() {
+            return Err(HekitError::UserInput(format!(
+                "序号 {} 超出范围（1-{}）",
+                number,
+                self.view.len()
+            )));
+        }
+        Ok(self.view[number - 1].clone())
+    }
+
+    /// 去掉压缩包内的文件并提示
+    fn without_archive_entries(selected: Vec<PathBuf>, action: &str) -> Vec<PathBuf> {
+        let (entries, files): (Vec<PathBuf>, Vec<PathBuf>) = selected
+            .into_iter()
+            .partition(|path| archive::split_entry_path(path).is_some());
+        if !entries.is_empty() {
+            utils::print_warning(&format!(
+                "跳过 {} 个压缩包内的文件（不能直接{}）",
+                entries.len(),
+                action
+            ));
+        }
+        files
+    }
+
+    /// 移动或删除后更新结果列表（None 表示已删除），压缩包内的条目随压缩包一起更新
+    fn replace_path(&mut self, old: &Path, new: Option<PathBuf>) {
+        let entry_prefix = format!("{}!/", old.display());
+        for list in [&mut self.results, &mut self.view] {
+            match &new {
+                Some(new) => {
+                    for path in list.iter_mut() {
+                        if path.as_path() == old {
+                            *path = new.clone();
+                        } else if let Some(name) =
+                            path.to_string_lossy().strip_prefix(&entry_prefix)
+                        {
+                            *path = archive::entry_path(new, name);
+                        }
+                    }
+                }
+                None => list.retain(|path| {
+                    path.as_path() != old && !path.to_string_lossy().starts_with(&entry_prefix)
+                }),
+            }
+        }
+        let pages = self.view.len().div_ceil(PAGE_SIZE).max(1);
+        self.page = self.page.min(pages - 1);
+    }
+
+    /// 列出将要处理的文件
+    fn print_preview(selected: &[PathBuf]) {
+        for path in selected.iter().take(PREVIEW_LIMIT) {
+            println!("  {}", path.display());
+        }
+        if selected.len() > PREVIEW_LIMIT {
+            println!("  ... 另有 {} 个文件", selected.len() - PREVIEW_LIMIT);
+        }
+    }
+
+    /// 显示操作结果
+    fn print_summary(action: &str, success: usize, error_count: usize) {
+        if error_count == 0 {
+            utils::print_success(&format!("已{} {} 个文件", action, success));
+        } else {
+            utils::print_warning(&format!(
+                "已{} {} 个文件，{} 个失败或跳过",
+                action, success, error_count
+            ));
+        }
+    }
+}
+
+/// 确认是否执行操作
+fn confirm(prompt: &str) -> HekitResult<bool> {
+    let answer = utils::get_user_input(prompt)
+        .map_err(|e| HekitError::UserInput(format!("读取输入失败: {}", e)))?;
+    if !answer.eq_ignore_ascii_case("y") {
+        utils::print_info("已取消操作");
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// 复制或移动到目录时的目标路径（压缩包内的文件使用条目的文件名）
+fn target_path(path: &Path, target_dir: &Path) -> Option<PathBuf> {
+    let name = match archive::split_entry_path(path) {
+        Some((_, name)) => Path::new(&name).file_name()?.to_os_string(),
+        None => path.file_name()?.to_os_string(),
+    };
+    Some(target_dir.join(name))
+}
+
+/// 从压缩包中解压单个条目
+fn extract_entry(archive_path: &Path, name: &str, target: &Path) -> io::Result<()> {
+    archive::with_entry(archive_path, name, |entry| {
+        let mut output = File::create(target)?;
+        io::copy(entry.reader, &mut output).map(|_| ())
+    })?
+    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "压缩包中已没有此文件"))
+}
+
+/// 移动文件（跨文件系统时改为复制后删除，其他错误直接返回）
+fn move_file(source: &Path, target: &Path) -> io::Result<()> {
+    match fs::rename(source, target) {
+        Ok(()) => return Ok(()),
+        Err(e) if !is_cross_device(&e) => return Err(e),
+        Err(_) => {}
+    }
+    fs::copy(source, target)?;
+    if let Err(e) = fs::remove_file(source) {
+        let _ = fs::remove_file(target);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// 是否为跨文件系统重命名导致的错误
+fn is_cross_device(error: &io::Error) -> bool {
+    // EXDEV（Unix）和 ERROR_NOT_SAME_DEVICE（Windows）
+    #[cfg(unix)]
+    const CROSS_DEVICE: i32 = 18;
+    #[cfg(windows)]
+    const CROSS_DEVICE: i32 = 17;
+    #[cfg(not(any(unix, windows)))]
+    const CROSS_DEVICE: i32 = -1;
+
+    error.kind() == io::ErrorKind::CrossesDevices || error.raw_os_error() == Some(CROSS_DEVICE)
+}
+
+/// MIME 类型及其大类的描述
+fn describe_mime(mime: Option<&str>) -> String {
+    match mime {
+        Some(mime) => match FileCategory::from_mime(mime) {
+            Some(category) => format!("{} ({})", mime, category.label()),
+            None => mime.to_string(),
+        },
+        None => "未知".to_string(),
+    }
+}
+
+/// 显示平台相关的元数据（权限、所有者、硬链接数）
+#[cfg(unix)]
+fn print_platform_metadata(metadata: &fs::Metadata) {
+    use std::os::unix::fs::MetadataExt;
+    println!("权限:     {:o}", metadata.mode() & 0o7777);
+    println!("所有者:   uid {} / gid {}", metadata.uid(), metadata.gid());
+    println!("硬链接数: {}", metadata.nlink());
+    println!("inode:    {}", metadata.ino());
+}
+
+/// 显示平台相关的元数据（非Unix系统没有额外信息）
+#[cfg(not(unix))]
+fn print_platform_metadata(_metadata: &fs::Metadata) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn browser(count: usize) -> ResultBrowser {
+        ResultBrowser::new(
+            (1..=count)
+                .map(|i| PathBuf::from(format!("f{}.txt", i)))
+                .collect(),
+        )
+    }
+
+    fn names(paths: &[PathBuf]) -> Vec<String> {
+        paths.iter().map(|p| p.display().to_string()).collect()
+    }
+
+    #[test]
+    fn select_all_ranges_and_dedup() {
+        let browser = browser(10);
+        assert_eq!(browser.select("all").unwrap().len(), 10);
+        assert_eq!(browser.select("ALL").unwrap().len(), 10);
+        assert_eq!(browser.select("*").unwrap().len(), 10);
+        assert_eq!(names(&browser.select("3").unwrap()), ["f3.txt"]);
+        assert_eq!(
+            names(&browser.select("1-5,8").unwrap()),
+            ["f1.txt", "f2.txt", "f3.txt", "f4.txt", "f5.txt", "f8.txt"]
+        );
+        // 重叠和重复的序号只保留一次，并按序号排列
+        assert_eq!(
+            names(&browser.select("4,2-3,3,2").unwrap()),
+            ["f2.txt", "f3.txt", "f4.txt"]
+        );
+        assert_eq!(
+            names(&browser.select(" 9 - 10 ,").unwrap()),
+            ["f9.txt", "f10.txt"]
+        );
+    }
+
+    #[test]
+    fn select_rejects_invalid_and_out_of_range() {
+        let browser = browser(10);
+        for selection in ["5-3", "0", "11", "1-11", "0-2", "a", "1-", "", ","] {
+            assert!(browser.select(selection).is_err(), "{}", selection);
+        }
+    }
+
+    #[test]
+    fn select_uses_filtered_view() {
+        let mut browser = browser(10);
+        browser.view = vec![PathBuf::from("f7.txt"), PathBuf::from("f9.txt")];
+        assert_eq!(names(&browser.select("2").unwrap()), ["f9.txt"]);
+        assert!(browser.select("3").is_err());
+    }
+
+    #[test]
+    fn replace_path_updates_archive_entries() {
+        let dir = PathBuf::from("results");
+        let old = dir.join("a.zip");
+        let new = dir.join("moved").join("a.zip");
+        let other = dir.join("a.zip.bak");
+
+        let mut browser = ResultBrowser::new(vec![
+            old.clone(),
+            archive::entry_path(&old, "x.txt"),
+            archive::entry_path(&old, "sub/y.txt"),
+            other.clone(),
+        ]);
+        browser.replace_path(&old, Some(new.clone()));
+        let expected = vec![
+            new.clone(),
+            archive::entry_path(&new, "x.txt"),
+            archive::entry_path(&new, "sub/y.txt"),
+            other.clone(),
+        ];
+        assert_eq!(browser.results, expected);
+        assert_eq!(browser.view, expected);
+
+        browser.replace_path(&new, None);
+        assert_eq!(browser.results, vec![other.clone()]);
+        assert_eq!(browser.view, vec![other]);
+    }
+
+    #[test]
+    fn replace_path_clamps_page_after_delete() {
+        let mut browser = browser(PAGE_SIZE + 1);
+        browser.page = 1;
+        browser.replace_path(Path::new(&format!("f{}.txt", PAGE_SIZE + 1)), None);
+        assert_eq!(browser.results.len(), PAGE_SIZE);
+        assert_eq!(browser.page, 0);
+    }
+
+    #[test]
+    fn move_file_surfaces_non_cross_device_errors() {
+        let dir = std::env::temp_dir().join(format!("hekit_results_move_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("a.txt");
+        fs::write(&source, "data").unwrap();
+
+        // 目标目录不存在：不能退回到复制后删除
+        let missing = dir.join("missing").join("a.txt");
+        let error = move_file(&source, &missing).unwrap_err();
+        assert!(!is_cross_device(&error));
+        assert_eq!(fs::read_to_string(&source).unwrap(), "data");
+
+        let target = dir.join("b.txt");
+        move_file(&source, &target).unwrap();
+        assert!(!source.exists());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "data");
+
+        assert!(is_cross_device(&io::Error::from(
+            io::ErrorKind::CrossesDevices
+        )));
+        assert!(!is_cross_device(&io::Error::from(
+            io::ErrorKind::PermissionDenied
+        )));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,511 @@
+use std::collections::{HashSet, VecDeque};
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// 目录遍历选项
+#[derive(Debug, Clone, Default)]
+pub struct WalkOptions {
+    /// 工作线程数
+    pub threads: usize,
+    /// 是否进入子目录
+    pub recursive: bool,
+    /// 最大深度（根目录下的条目为第1层，None 为不限制）
+    pub max_depth: Option<usize>,
+    /// 最小深度（浅于此深度的文件不交给 visit）
+    pub min_depth: usize,
+    /// 是否跟随指向目录的符号链接（通过设备号和 inode 检测循环）
+    pub follow_symlinks: bool,
+    /// 不进入其他文件系统（挂载点、网络盘）
+    pub one_file_system: bool,
+}
+
+/// 遍历统计
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WalkStats {
+    /// 已读取的目录数
+    pub dirs: usize,
+    /// 无法读取而跳过的目录数
+    pub skipped: usize,
+    /// 因符号链接循环或重复而跳过的目录数
+    pub loops: usize,
+    /// 位于其他文件系统而跳过的目录数
+    pub other_fs: usize,
+}
+
+/// 目录的唯一标识（设备号, inode），用于检测符号链接循环
+#[cfg(unix)]
+type DirKey = (u64, u64);
+
+/// 目录的唯一标识（非Unix系统使用规范化路径）
+#[cfg(not(unix))]
+type DirKey = PathBuf;
+
+/// 文件系统标识（Unix 为设备号）
+#[cfg(unix)]
+type DeviceKey = u64;
+
+/// 文件系统标识（非Unix系统为盘符或共享根路径）
+#[cfg(not(unix))]
+type DeviceKey = PathBuf;
+
+/// 默认线程数（CPU核心数，至少4个以便掩盖网络盘的IO延迟）
+pub fn default_threads() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .max(4)
+}
+
+/// 线程间共享的遍历状态
+struct WalkState<'a> {
+    /// 每个线程一个双端队列：自己从尾部取，空闲时从其他线程头部窃取（目录及其深度）
+    queues: Vec<Mutex<VecDeque<(PathBuf, usize)>>>,
+    /// 已入队但尚未处理完的目录数，为0时遍历结束
+    pending: AtomicUsize,
+    dirs: AtomicUsize,
+    skipped: AtomicUsize,
+    loops: AtomicUsize,
+    other_fs: AtomicUsize,
+    /// 跟随符号链接时已读取过的目录
+    visited: Mutex<HashSet<DirKey>>,
+    /// 根目录所在的文件系统（只在 one_file_system 时记录）
+    root_device: Option<DeviceKey>,
+    options: &'a WalkOptions,
+    cancel: &'a AtomicBool,
+}
+
+/// 多线程遍历目录，对每个非目录项调用 visit（在工作线程中调用，顺序不固定）
+///
+/// cancel 置为 true 时尽快停止遍历。
+pub fn walk<F>(root: &Path, options: &WalkOptions, cancel: &AtomicBool, visit: F) -> WalkStats
+where
+    F: Fn(&Path) + Sync,
+{
+    let threads = options.threads.max(1);
+    let root_device = if options.one_file_system {
+        fs::metadata(root)
+            .ok()
+            .and_then(|metadata| device_key(root, &metadata))
+    } else {
+        None
+    };
+    let state = WalkState {
+        queues: (0..threads).map(|_| Mutex::new(VecDeque::new())).collect(),
+        pending: AtomicUsize::new(1),
+        dirs: AtomicUsize::new(0),
+        skipped: AtomicUsize::new(0),
+        loops: AtomicUsize::new(0),
+        other_fs: AtomicUsize::new(0),
+        visited: Mutex::new(HashSet::new()),
+        root_device,
+        options,
+        cancel,
+    };
+    state.queues[0]
+        .lock()
+        .unwrap()
+        .push_back((root.to_path_buf(), 0));
+
+    thread::scope(|scope| {
+        for id in 0..threads {
+            let state = &state;
+            let visit = &visit;
+            scope.spawn(move || worker(id, state, visit));
+        }
+    });
+
+    WalkStats {
+        dirs: state.dirs.load(Ordering::Relaxed),
+        skipped: state.skipped.load(Ordering::Relaxed),
+        loops: state.loops.load(Ordering::Relaxed),
+        other_fs: state.other_fs.load(Ordering::Relaxed),
+    }
+}
+
+/// 线程 panic 展开时设置取消标志
+struct CancelOnPanic<'a>(&'a AtomicBool);
+
+impl Drop for CancelOnPanic<'_> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.0.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 工作线程：优先处理自己队列中的目录，空闲时窃取其他线程的任务
+fn worker<F>(id: usize, state: &WalkState, visit: &F)
+where
+    F: Fn(&Path) + Sync,
+{
+    // 线程因 panic 退出时通知其他线程停止，否则未完成的目录计数永远不会归零
+    let _guard = CancelOnPanic(state.cancel);
+    let count = state.queues.len();
+    loop {
+        if state.cancel.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let job = state.queues[id].lock().unwrap().pop_back().or_else(|| {
+            (1..count).find_map(|offset| {
+                state.queues[(id + offset) % count]
+                    .lock()
+                    .unwrap()
+                    .pop_front()
+            })
+        });
+
+        match job {
+            Some((dir, depth)) => {
+                if should_enter(&dir, depth, state) {
+                    read_directory(id, &dir, depth, state, visit);
+                }
+                state.pending.fetch_sub(1, Ordering::AcqRel);
+            }
+            None if state.pending.load(Ordering::Acquire) == 0 => return,
+            None => thread::sleep(Duration::from_micros(200)),
+        }
+    }
+}
+
+/// 检查目录是否位于同一文件系统、是否已读取过（符号链接循环）
+fn should_enter(dir: &Path, depth: usize, state: &WalkState) -> bool {
+    let options = state.options;
+    if !options.follow_symlinks && !options.one_file_system {
+        return true;
+    }
+
+    let Ok(metadata) = fs::metadata(dir) else {
+        // 无法读取的目录交给 read_directory 统计
+        return true;
+    };
+
+    // 根目录本身总是读取
+    if depth > 0 {
+        if let Some(root_device) = &state.root_device {
+            if device_key(dir, &metadata).as_ref() != Some(root_device) {
+                state.other_fs.fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
+        }
+    }
+
+    if options.follow_symlinks {
+        if let Some(key) = dir_key(dir, &metadata) {
+            if !state.visited.lock().unwrap().insert(key) {
+                state.loops.fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// 读取单个目录：子目录放入本线程队列，其他条目交给 visit
+fn read_directory<F>(id: usize, dir: &Path, depth: usize, state: &WalkState, visit: &F)
+where
+    F: Fn(&Path) + Sync,
+{
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => {
+            state.skipped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+    };
+    state.dirs.fetch_add(1, Ordering::Relaxed);
+
+    let options = state.options;
+    let entry_depth = depth + 1;
+    let descend = if options.recursive {
+        options.max_depth.is_none_or(|max| entry_depth < max)
+    } else {
+        false
+    };
+
+    let mut subdirs = Vec::new();
+    for entry in entries.flatten() {
+        if state.cancel.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        let is_dir = if file_type.is_symlink() {
+            // 符号链接按目标类型处理，不跟随时忽略指向目录的链接
+            let target_is_dir = fs::metadata(&path).is_ok_and(|m| m.is_dir());
+            if target_is_dir && !options.follow_symlinks {
+                continue;
+            }
+            target_is_dir
+        } else {
+            file_type.is_dir()
+        };
+
+        if is_dir {
+            if descend {
+                subdirs.push((path, entry_depth));
+            }
+        } else if entry_depth >= options.min_depth {
+            visit(&path);
+        }
+    }
+
+    if !subdirs.is_empty() {
+        state.pending.fetch_add(subdirs.len(), Ordering::AcqRel);
+        state.queues[id].lock().unwrap().extend(subdirs);
+    }
+}
+
+#[cfg(unix)]
+fn dir_key(_dir: &Path, metadata: &Metadata) -> Option<DirKey> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn dir_key(dir: &Path, _metadata: &Metadata) -> Option<DirKey> {
+    fs::canonicalize(dir).ok()
+}
+
+#[cfg(unix)]
+fn device_key(_dir: &Path, metadata: &Metadata) -> Option<DeviceKey> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn device_key(dir: &Path, _metadata: &Metadata) -> Option<DeviceKey> {
+    let canonical = fs::canonicalize(dir).ok()?;
+    canonical
+        .components()
+        .next()
+        .map(|prefix| PathBuf::from(prefix.as_os_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("hekit_walker_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 根目录 5 个文件，8 个子目录各 5 个文件，每个子目录下 3 个子目录各 5 个文件
+    fn build_tree(root: &Path) -> usize {
+        let mut count = 0;
+        let mut add_files = |dir: &Path| {
+            for i in 0..5 {
+                fs::write(dir.join(format!("f{}.txt", i)), "x").unwrap();
+                count += 1;
+            }
+        };
+        add_files(root);
+        for a in 0..8 {
+            let sub = root.join(format!("d{}", a));
+            fs::create_dir(&sub).unwrap();
+            add_files(&sub);
+            for b in 0..3 {
+                let deep = sub.join(format!("e{}", b));
+                fs::create_dir(&deep).unwrap();
+                add_files(&deep);
+            }
+        }
+        count
+    }
+
+    fn options(threads: usize) -> WalkOptions {
+        WalkOptions {
+            threads,
+            recursive: true,
+            ..WalkOptions::default()
+        }
+    }
+
+    /// 遍历并返回排序后的相对路径
+    fn collect(root: &Path, options: &WalkOptions) -> (Vec<String>, WalkStats) {
+        let cancel = AtomicBool::new(false);
+        let visited = Mutex::new(Vec::new());
+        let stats = walk(root, options, &cancel, |path| {
+            let rel = path.strip_prefix(root).unwrap();
+            visited
+                .lock()
+                .unwrap()
+                .push(rel.to_string_lossy().replace('\\', "/"));
+        });
+        let mut visited = visited.into_inner().unwrap();
+        visited.sort();
+        (visited, stats)
+    }
+
+    #[test]
+    fn visits_every_file_exactly_once_with_many_threads() {
+        let root = temp_dir("once");
+        let total = build_tree(&root);
+
+        for threads in [1, 4, 16] {
+            let (visited, stats) = collect(&root, &options(threads));
+            let mut unique = visited.clone();
+            unique.dedup();
+            assert_eq!(visited.len(), total, "threads={}", threads);
+            assert_eq!(unique.len(), total, "threads={}", threads);
+            assert_eq!(stats.dirs, 1 + 8 + 8 * 3);
+            assert_eq!(stats.skipped, 0);
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn non_recursive_walk_only_reads_root() {
+        let root = temp_dir("flat");
+        build_tree(&root);
+
+        let options = WalkOptions {
+            recursive: false,
+            ..options(4)
+        };
+        let (visited, stats) = collect(&root, &options);
+        assert_eq!(visited, ["f0.txt", "f1.txt", "f2.txt", "f3.txt", "f4.txt"]);
+        assert_eq!(stats.dirs, 1);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn cancel_stops_the_walk() {
+        let root = temp_dir("cancel");
+        let total = build_tree(&root);
+
+        // 预先取消时不访问任何文件
+        let cancel = AtomicBool::new(true);
+        let count = AtomicUsize::new(0);
+        walk(&root, &options(4), &cancel, |_| {
+            count.fetch_add(1, Ordering::Relaxed);
+        });
+        assert_eq!(count.load(Ordering::Relaxed), 0);
+
+        // 访问到第一个文件后取消，单线程时之后不再访问
+        let cancel = AtomicBool::new(false);
+        let count = AtomicUsize::new(0);
+        walk(&root, &options(1), &cancel, |_| {
+            count.fetch_add(1, Ordering::Relaxed);
+            cancel.store(true, Ordering::Relaxed);
+        });
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+
+        let cancel = AtomicBool::new(false);
+        let count = AtomicUsize::new(0);
+        walk(&root, &options(8), &cancel, |_| {
+            if count.fetch_add(1, Ordering::Relaxed) == 10 {
+                cancel.store(true, Ordering::Relaxed);
+            }
+        });
+        assert!(count.load(Ordering::Relaxed) < total);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn panic_in_visit_propagates_instead_of_hanging() {
+        let root = temp_dir("panic");
+        build_tree(&root);
+
+        let cancel = AtomicBool::new(false);
+        let result = std::panic::catch_unwind(|| {
+            walk(&root, &options(4), &cancel, |path| {
+                if path.ends_with("d3/f2.txt") {
+                    panic!("visit failed");
+                }
+            })
+        });
+        assert!(result.is_err());
+        assert!(cancel.load(Ordering::Relaxed));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn depth_limits_count_root_entries_as_depth_one() {
+        let root = temp_dir("depth");
+        build_tree(&root);
+        let depth_of = |rel: &String| rel.matches('/').count() + 1;
+        let walk_depths = |max_depth, min_depth| {
+            let options = WalkOptions {
+                max_depth,
+                min_depth,
+                ..options(4)
+            };
+            let (visited, _) = collect(&root, &options);
+            let mut depths: Vec<usize> = visited.iter().map(depth_of).collect();
+            depths.sort();
+            depths.dedup();
+            (visited.len(), depths)
+        };
+
+        assert_eq!(walk_depths(Some(1), 0), (5, vec![1]));
+        assert_eq!(walk_depths(Some(2), 0), (45, vec![1, 2]));
+        assert_eq!(walk_depths(None, 2).0, 160);
+        assert_eq!(walk_depths(Some(3), 3), (120, vec![3]));
+        // 最大深度为1时不读取子目录
+        let options = WalkOptions {
+            max_depth: Some(1),
+            ..options(4)
+        };
+        assert_eq!(collect(&root, &options).1.dirs, 1);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_dirs_are_skipped_unless_followed() {
+        use std::os::unix::fs::symlink;
+
+        let root = temp_dir("symlink");
+        fs::create_dir(root.join("real")).unwrap();
+        fs::write(root.join("real").join("f.txt"), "x").unwrap();
+        symlink(root.join("real"), root.join("link")).unwrap();
+        symlink(root.join("real").join("f.txt"), root.join("file_link")).unwrap();
+
+        let (visited, stats) = collect(&root, &options(4));
+        assert_eq!(visited, ["file_link", "real/f.txt"]);
+        assert_eq!(stats.loops, 0);
+
+        // 跟随时链接和原目录是同一目录，只读取一次
+        let options = WalkOptions {
+            follow_symlinks: true,
+            ..options(4)
+        };
+        let (visited, stats) = collect(&root, &options);
+        assert_eq!(visited.len(), 2);
+        assert!(visited.contains(&"file_link".to_string()));
+        assert_eq!(stats.loops, 1);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_loop_terminates_and_is_counted() {
+        let root = temp_dir("loop");
+        fs::create_dir(root.join("a")).unwrap();
+        fs::write(root.join("a").join("f.txt"), "x").unwrap();
+        std::os::unix::fs::symlink("..", root.join("a").join("link")).unwrap();
+
+        let options = WalkOptions {
+            follow_symlinks: true,
+            ..options(4)
+        };
+        let (visited, stats) = collect(&root, &options);
+        assert_eq!(visited, ["a/f.txt"]);
+        assert!(stats.loops > 0);
+        assert_eq!(stats.dirs, 2);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
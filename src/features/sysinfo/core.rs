@@ -180,11 +180,7 @@ impl SysInfoCore {
         let networks = self.system.networks();
 
         // 检查是否有网络接口
-        let mut has_interfaces = false;
-        for (_interface_name, _) in networks {
-            has_interfaces = true;
-            break;
-        }
+        let has_interfaces = networks.into_iter().next().is_some();
 
         if !has_interfaces {
             println!("  未检测到网络接口");
@@ -269,7 +265,7 @@ impl SysInfoCore {
 
         // 显示前10个内存使用最多的进程
         let mut sorted_processes: Vec<_> = processes.values().collect();
-        sorted_processes.sort_by(|a, b| b.memory().cmp(&a.memory()));
+        sorted_processes.sort_by_key(|p| std::cmp::Reverse(p.memory()));
 
         println!("\n内存使用最多的进程 (前10个):");
         println!(
@@ -300,7 +296,7 @@ fn get_local_ips() -> Vec<String> {
     // 方法1: 使用UDP连接外部服务器获取本地IP
     if let Ok(socket) = std::net::UdpSocket::bind("0.0.0.0:0") {
         // 尝试连接到一个公共DNS服务器来获取本地IP
-        if let Ok(_) = socket.connect("8.8.8.8:80") {
+        if socket.connect("8.8.8.8:80").is_ok() {
             if let Ok(addr) = socket.local_addr() {
                 let ip = addr.ip();
                 if ip.is_ipv4() && !ip.is_loopback() && !ip.is_unspecified() {
@@ -192,3 +192,76 @@ pub fn print_compact_separator() {
 pub fn print_compact_command_hint() {
     println!("[help - 查看使用说明, back - 返回主菜单]");
 }
+
+/// 获取程序数据目录（Windows 为 %APPDATA%\hekit，其他系统为 ~/.config/hekit）
+pub fn app_data_dir() -> Option<std::path::PathBuf> {
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(std::path::PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(std::path::PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".config"))
+            })
+    };
+    base.map(|dir| dir.join("hekit"))
+}
+
+/// 检测终端是否支持ANSI颜色（设置 NO_COLOR 环境变量可禁用）
+pub fn supports_color() -> bool {
+    if std::env::var_os("NO_COLOR").is_some() {
+        return false;
+    }
+
+    if cfg!(windows) {
+        // 传统CMD默认不解析颜色转义序列，只在现代终端中启用
+        std::env::var("WT_SESSION").is_ok_and(|s| !s.is_empty())
+            || std::env::var("TERM_PROGRAM").is_ok_and(|t| t.contains("vscode"))
+    } else {
+        std::env::var("TERM").map_or(true, |term| term != "dumb")
+    }
+}
+
+/// 将字节数格式化为易读的大小（如 1.50 MB）
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.2} {}", size, UNITS[unit])
+    }
+}
+
+/// 文件的唯一标识（设备号, inode），用于识别同一文件的多个硬链接
+#[cfg(unix)]
+pub fn file_id(metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// 文件的唯一标识（非Unix系统不检测硬链接）
+#[cfg(not(unix))]
+pub fn file_id(_metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// 文件实际占用的磁盘空间（按已分配的块计算，稀疏文件和压缩文件可能小于文件长度）
+#[cfg(unix)]
+pub fn allocated_size(metadata: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    // st_blocks 固定以512字节为单位，与文件系统块大小无关
+    metadata.blocks() * 512
+}
+
+/// 文件占用的磁盘空间（非Unix系统按文件长度计算）
+#[cfg(not(unix))]
+pub fn allocated_size(metadata: &std::fs::Metadata) -> u64 {
+    metadata.len()
+}
@@ -17,9 +17,9 @@ impl VersionChecker {
                     return Ok((false, latest_version));
                 }
                 if Self::is_newer_version(current_version, &latest_version) {
-                    return Ok((true, latest_version));
+                    Ok((true, latest_version))
                 } else {
-                    return Ok((false, latest_version));
+                    Ok((false, latest_version))
                 }
             }
             Err(_) => Ok((false, "".to_string())),